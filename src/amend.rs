@@ -0,0 +1,349 @@
+//! Rewriting the newest commits: `update --amend`, `mdcode squash` and
+//! `mdcode rewrite-author`.
+//!
+//! Amend and squash keep the original author date (and, unless `--author` is given, the
+//! original author) so the history still says when the work was done; only the committer is
+//! updated to the current identity and time, as `git commit --amend` does. `rewrite-author`
+//! only swaps the name and email of a wrong identity and keeps every date.
+
+use crate::journal::{self, Created};
+use crate::{
+    create_commit, move_head, open_repo_with_commits, parse_author, resolve_commit_signature,
+    SignatureOptions,
+};
+use git2::{Commit, Oid, Repository, RepositoryState, Signature};
+use std::error::Error;
+
+/// Author for a rewrite of `original`: `author` (name and email) when given, else the
+/// original author, always at the original author time.
+pub fn preserved_author(
+    original: &Commit,
+    author: Option<&Signature>,
+) -> Result<Signature<'static>, Box<dyn Error>> {
+    let source = original.author();
+    let who = author.unwrap_or(&source);
+    Ok(Signature::new(
+        who.name().unwrap_or("(unknown)"),
+        who.email().unwrap_or("(unknown)"),
+        &source.when(),
+    )?)
+}
+
+/// Replace HEAD with a commit of the current index, keeping its parents and author date.
+/// `message` defaults to the original message; `sign` signs the new commit.
+pub fn amend_head_commit(
+    repo: &Repository,
+    message: Option<&str>,
+    author: Option<&Signature>,
+    committer: &Signature,
+    sign: bool,
+) -> Result<Oid, Box<dyn Error>> {
+    if repo.state() != RepositoryState::Clean {
+        return Err("cannot amend while a merge or other operation is in progress".into());
+    }
+    let head = repo.head()?.peel_to_commit()?;
+    let mut index = repo.index()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let author = preserved_author(&head, author)?;
+    let message = message.unwrap_or_else(|| head.message().unwrap_or(""));
+    let parents: Vec<Commit> = head.parents().collect();
+    let parent_refs: Vec<&Commit> = parents.iter().collect();
+    let oid = create_commit(
+        repo,
+        None,
+        &author,
+        committer,
+        message,
+        &tree,
+        &parent_refs,
+        sign,
+    )?;
+    move_head(
+        repo,
+        oid,
+        &format!("commit (amend): {}", message.lines().next().unwrap_or("")),
+    )?;
+    Ok(oid)
+}
+
+/// The newest `count` first-parent commits of HEAD (newest first). Fails when there are
+/// fewer, or when one of them is a merge.
+fn squash_range<'r>(repo: &'r Repository, count: usize) -> Result<Vec<Commit<'r>>, Box<dyn Error>> {
+    if count < 2 {
+        return Err("squash needs at least 2 commits".into());
+    }
+    let mut commits = vec![repo.head()?.peel_to_commit()?];
+    while commits.len() < count {
+        let last = commits.last().unwrap();
+        if last.parent_count() > 1 {
+            return Err(format!("cannot squash across merge commit {}", last.id()).into());
+        }
+        match last.parent(0) {
+            Ok(parent) => commits.push(parent),
+            Err(_) => {
+                return Err(format!(
+                    "only {} commits on this branch; cannot squash {}",
+                    commits.len(),
+                    count
+                )
+                .into())
+            }
+        }
+    }
+    if commits.last().unwrap().parent_count() > 1 {
+        return Err("cannot squash a merge commit".into());
+    }
+    Ok(commits)
+}
+
+/// Squash the newest `count` commits into one with HEAD's tree. The result keeps the
+/// author and author date of the oldest squashed commit; `message` defaults to the squashed
+/// messages, oldest first. Returns the new commit id (HEAD's id on `dry_run`). Journaled
+/// (see `journal`) unless `dry_run`.
+pub fn squash_commits(
+    dir: &str,
+    count: usize,
+    message: Option<&str>,
+    author: Option<&str>,
+    identity: &SignatureOptions,
+    dry_run: bool,
+) -> Result<Oid, Box<dyn Error>> {
+    let result = squash_range_into_one(dir, count, message, author, identity, dry_run);
+    if !dry_run {
+        let args = serde_json::json!({ "count": count, "message": message, "author": author });
+        journal::record(dir, "squash", args, &result, |&oid| {
+            Created::commit(Some(oid))
+        });
+    }
+    result
+}
+
+/// `squash_commits` without the journal entry.
+fn squash_range_into_one(
+    dir: &str,
+    count: usize,
+    message: Option<&str>,
+    author: Option<&str>,
+    identity: &SignatureOptions,
+    dry_run: bool,
+) -> Result<Oid, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    if repo.state() != RepositoryState::Clean {
+        return Err("cannot squash while a merge or other operation is in progress".into());
+    }
+    let commits = squash_range(&repo, count)?;
+    let oldest = commits.last().unwrap();
+    let message = match message {
+        Some(m) => m.to_string(),
+        None => commits
+            .iter()
+            .rev()
+            .map(|c| c.message().unwrap_or("").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+    if dry_run {
+        #[cfg(not(coverage))]
+        log::info!(
+            "Dry run: would squash {} commits into one with message '{}'",
+            commits.len(),
+            message.lines().next().unwrap_or("")
+        );
+        return Ok(commits[0].id());
+    }
+    let (committer, _) = resolve_commit_signature(&repo, author, identity)?;
+    let author = preserved_author(oldest, author.map(|_| &committer))?;
+    let parents: Vec<Commit> = oldest.parents().collect();
+    let parent_refs: Vec<&Commit> = parents.iter().collect();
+    let tree = commits[0].tree()?;
+    let oid = create_commit(
+        &repo,
+        None,
+        &author,
+        &committer,
+        &message,
+        &tree,
+        &parent_refs,
+        identity.sign,
+    )?;
+    let mut head = repo.head()?.resolve()?;
+    head.set_target(oid, &format!("squash: {} commits", commits.len()))?;
+    #[cfg(not(coverage))]
+    log::info!("Squashed {} commits into {}", commits.len(), oid);
+    Ok(oid)
+}
+
+/// `signature` with `name` and `email` when its email is `from` (case-insensitive), keeping
+/// its time; `None` when it does not match.
+fn replaced_identity(
+    signature: &Signature,
+    from: &str,
+    name: &str,
+    email: &str,
+) -> Result<Option<Signature<'static>>, Box<dyn Error>> {
+    if !signature
+        .email()
+        .is_some_and(|e| e.eq_ignore_ascii_case(from))
+    {
+        return Ok(None);
+    }
+    Ok(Some(Signature::new(name, email, &signature.when())?))
+}
+
+/// True when `commit` is on a remote-tracking branch (`refs/remotes/*`).
+fn on_remote_branch(repo: &Repository, commit: Oid) -> Result<bool, Box<dyn Error>> {
+    for reference in repo.references_glob("refs/remotes/*")? {
+        let Some(tip) = reference?.target() else {
+            continue;
+        };
+        if tip == commit || repo.graph_descendant_of(tip, commit)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Replace the author and committer whose email is `from` with `to` (`Name <email>`) in the
+/// newest `last` first-parent commits of HEAD, recreating them and every commit after them;
+/// dates, messages and trees stay as they were. Refuses when a commit that would change is
+/// on a remote-tracking branch unless `force`; `sign` signs the recreated commits. Returns
+/// the number of commits whose identity changed (that would change, on `dry_run`).
+/// Journaled (see `journal`) unless `dry_run`.
+pub fn rewrite_author(
+    dir: &str,
+    from: &str,
+    to: &str,
+    last: usize,
+    force: bool,
+    sign: bool,
+    dry_run: bool,
+) -> Result<usize, Box<dyn Error>> {
+    let result = rewrite_identities(dir, from, to, last, force, sign, dry_run);
+    if !dry_run {
+        let args = serde_json::json!({ "from": from, "to": to, "last": last, "force": force });
+        journal::record(dir, "rewrite-author", args, &result, |&(_, head)| {
+            Created::commit(head)
+        });
+    }
+    result.map(|(rewritten, _)| rewritten)
+}
+
+/// `rewrite_author`, also returning the new HEAD when commits were recreated.
+fn rewrite_identities(
+    dir: &str,
+    from: &str,
+    to: &str,
+    last: usize,
+    force: bool,
+    sign: bool,
+    dry_run: bool,
+) -> Result<(usize, Option<Oid>), Box<dyn Error>> {
+    let (name, email) = parse_author(to)?;
+    let repo = open_repo_with_commits(dir)?;
+    if repo.state() != RepositoryState::Clean {
+        return Err(
+            "cannot rewrite commits while a merge or other operation is in progress".into(),
+        );
+    }
+    let mut commits = vec![repo.head()?.peel_to_commit()?];
+    while commits.len() < last {
+        match commits.last().unwrap().parent(0) {
+            Ok(parent) => commits.push(parent),
+            Err(_) => {
+                return Err(format!(
+                    "only {} commits on this branch; cannot rewrite the last {}",
+                    commits.len(),
+                    last
+                )
+                .into())
+            }
+        }
+    }
+    commits.reverse();
+
+    let mut matching = Vec::new();
+    for commit in &commits {
+        let author = replaced_identity(&commit.author(), from, &name, &email)?;
+        let committer = replaced_identity(&commit.committer(), from, &name, &email)?;
+        if author.is_some() || committer.is_some() {
+            matching.push(commit.id());
+        }
+    }
+    if matching.is_empty() {
+        #[cfg(not(coverage))]
+        log::info!(
+            "No author or committer <{}> in the last {} commits; nothing to rewrite",
+            from,
+            commits.len()
+        );
+        return Ok((0, None));
+    }
+    if !force {
+        for id in &matching {
+            if on_remote_branch(&repo, *id)? {
+                return Err(format!(
+                    "commit {} is already pushed; rewriting it would diverge from the remote \
+(pass --force to rewrite anyway, then force-push)",
+                    &id.to_string()[..8]
+                )
+                .into());
+            }
+        }
+    }
+    if dry_run {
+        #[cfg(not(coverage))]
+        log::info!(
+            "Dry run: would rewrite <{}> as {} in {} of the last {} commits",
+            from,
+            to,
+            matching.len(),
+            commits.len()
+        );
+        return Ok((matching.len(), None));
+    }
+
+    // Recreate from the oldest changed commit on, pointing each at its rewritten parent.
+    let mut rewritten: Option<(Oid, Oid)> = None;
+    for commit in &commits {
+        let parents: Vec<Commit> = commit
+            .parents()
+            .enumerate()
+            .map(|(i, p)| match rewritten {
+                Some((old, new)) if i == 0 && p.id() == old => repo.find_commit(new),
+                _ => Ok(p),
+            })
+            .collect::<Result<_, _>>()?;
+        let author = replaced_identity(&commit.author(), from, &name, &email)?;
+        let committer = replaced_identity(&commit.committer(), from, &name, &email)?;
+        if author.is_none() && committer.is_none() && rewritten.is_none() {
+            continue;
+        }
+        let parent_refs: Vec<&Commit> = parents.iter().collect();
+        let oid = create_commit(
+            &repo,
+            None,
+            &author.unwrap_or_else(|| commit.author().to_owned()),
+            &committer.unwrap_or_else(|| commit.committer().to_owned()),
+            commit.message_raw().unwrap_or(""),
+            &commit.tree()?,
+            &parent_refs,
+            sign,
+        )?;
+        rewritten = Some((commit.id(), oid));
+    }
+    if let Some((_, head)) = rewritten {
+        move_head(
+            &repo,
+            head,
+            &format!("rewrite-author: {} commits", matching.len()),
+        )?;
+    }
+    #[cfg(not(coverage))]
+    log::info!(
+        "Rewrote <{}> as {} in {} commit(s)",
+        from,
+        to,
+        matching.len()
+    );
+    Ok((matching.len(), rewritten.map(|(_, head)| head)))
+}
@@ -0,0 +1,122 @@
+//! Per-region authorship of one file for `mdcode blame`.
+//!
+//! `blame_hunks` runs libgit2's blame at HEAD (or an older commit picked with a diff
+//! selector) and merges adjacent hunks from the same commit, so each entry is one
+//! contiguous region last touched by one commit. `blame_summary` aggregates those regions
+//! into each author's share of the file's lines.
+
+use crate::changes::resolve_selector;
+use crate::open_repo_with_commits;
+use git2::{BlameOptions, ObjectType, Oid};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+/// One contiguous line range last changed by a single commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameHunk {
+    pub id: Oid,
+    /// Author as `Name <email>`.
+    pub author: String,
+    /// Author time of the commit (Unix seconds).
+    pub time: i64,
+    /// First line (1-based).
+    pub start_line: usize,
+    /// Last line (1-based, inclusive).
+    pub end_line: usize,
+}
+
+impl BlameHunk {
+    pub fn lines(&self) -> usize {
+        self.end_line + 1 - self.start_line
+    }
+}
+
+/// Lines of the file attributed to one author (`blame --summary`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorShare {
+    pub author: String,
+    pub lines: usize,
+    pub percent: f64,
+}
+
+/// Blame `path` (repo-relative) at `rev`, a diff selector as accepted by `diff` (commit
+/// index, `H`, or a ref/revspec; default HEAD). Errors clearly when the file does not exist
+/// at that commit, is not a regular file, or is binary.
+pub fn blame_hunks(
+    dir: &str,
+    path: &str,
+    rev: Option<&str>,
+) -> Result<Vec<BlameHunk>, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    let rev_label = rev.unwrap_or("HEAD");
+    if rev_label.eq_ignore_ascii_case("L") {
+        return Err("blame works on commits; 'L' (the working directory) is not supported".into());
+    }
+    let commit = match rev {
+        Some(selector) => resolve_selector(&repo, dir, selector, false)?,
+        None => repo.head()?.peel_to_commit()?,
+    };
+    let path = path.replace('\\', "/");
+    let path = Path::new(path.trim_start_matches("./"));
+    let entry = commit
+        .tree()?
+        .get_path(path)
+        .map_err(|_| format!("'{}' does not exist in {}", path.display(), rev_label))?;
+    if entry.kind() != Some(ObjectType::Blob) {
+        return Err(format!("'{}' is not a file in {}", path.display(), rev_label).into());
+    }
+    if repo.find_blob(entry.id())?.is_binary() {
+        return Err(format!("'{}' is a binary file; nothing to blame", path.display()).into());
+    }
+
+    let mut opts = BlameOptions::new();
+    opts.newest_commit(commit.id());
+    let blame = repo.blame_file(path, Some(&mut opts))?;
+    let mut out: Vec<BlameHunk> = Vec::new();
+    for hunk in blame.iter() {
+        if hunk.lines_in_hunk() == 0 {
+            continue;
+        }
+        let start_line = hunk.final_start_line();
+        let end_line = start_line + hunk.lines_in_hunk() - 1;
+        if let Some(last) = out.last_mut() {
+            if last.id == hunk.final_commit_id() && last.end_line + 1 == start_line {
+                last.end_line = end_line;
+                continue;
+            }
+        }
+        let sig = hunk.final_signature();
+        out.push(BlameHunk {
+            id: hunk.final_commit_id(),
+            author: format!(
+                "{} <{}>",
+                sig.name().unwrap_or("(unknown)"),
+                sig.email().unwrap_or("(unknown)")
+            ),
+            time: sig.when().seconds(),
+            start_line,
+            end_line,
+        });
+    }
+    Ok(out)
+}
+
+/// Each author's share of the blamed lines, most lines first (ties by author).
+pub fn blame_summary(hunks: &[BlameHunk]) -> Vec<AuthorShare> {
+    let mut lines: BTreeMap<&str, usize> = BTreeMap::new();
+    for hunk in hunks {
+        *lines.entry(hunk.author.as_str()).or_default() += hunk.lines();
+    }
+    let total: usize = lines.values().sum();
+    let mut out: Vec<AuthorShare> = lines
+        .into_iter()
+        .map(|(author, lines)| AuthorShare {
+            author: author.to_string(),
+            lines,
+            percent: lines as f64 * 100.0 / total as f64,
+        })
+        .collect();
+    out.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.author.cmp(&b.author)));
+    out
+}
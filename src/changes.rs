@@ -0,0 +1,688 @@
+//! Structured changed-file listings: between two diff selectors (`compute_diff`) and per
+//! commit for `info` (`commit_history`), with rename detection.
+//!
+//! Selectors mirror the `diff` CLI: a commit index (`0` is most recent), `H` for the remote
+//! HEAD, `L` for the local working directory, or any ref/revspec git understands. A
+//! `DiffOptions::path` pattern (`PathFilter`) keeps only the matching files.
+
+use crate::{
+    colorize, detect_file_type, get_commit_by_index, get_remote_head_commit_with_options,
+    is_in_excluded_path, is_short_sha, open_repo, open_repo_with_commits, resolve_short_sha,
+    DiffOptions, TimeFormat, GREEN, RED, YELLOW,
+};
+use git2::{Delta, Diff, DiffFindOptions, Oid, Patch, Repository, Sort};
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// How a file changed between the two sides of a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    TypeChange,
+}
+
+impl ChangeKind {
+    /// One-letter code as shown by `git diff --name-status`.
+    pub fn code(&self) -> char {
+        match self {
+            ChangeKind::Added => 'A',
+            ChangeKind::Modified => 'M',
+            ChangeKind::Deleted => 'D',
+            ChangeKind::Renamed => 'R',
+            ChangeKind::TypeChange => 'T',
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Renamed => "renamed",
+            ChangeKind::TypeChange => "typechange",
+        };
+        f.write_str(s)
+    }
+}
+
+/// One changed file returned by `compute_diff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Path on the "after" side (the old path for deletions).
+    pub path: PathBuf,
+    /// Previous path, set for renames only.
+    pub old_path: Option<PathBuf>,
+    pub status: ChangeKind,
+    /// Added/removed lines (both 0 for binary files).
+    pub insertions: usize,
+    pub deletions: usize,
+    /// `detect_file_type` label for `path`, if recognized.
+    pub category: Option<&'static str>,
+}
+
+/// `diff --path` pattern: a glob such as `src/**/*.rs` (`*` stays within one directory,
+/// `**` crosses them), a file, or a directory whose files all match.
+#[derive(Debug, Clone)]
+pub struct PathFilter {
+    pattern: String,
+    glob: GlobMatcher,
+}
+
+impl PathFilter {
+    pub fn new(pattern: &str) -> Result<Self, Box<dyn Error>> {
+        let pattern = pattern.replace('\\', "/");
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("invalid --path pattern '{}': {}", pattern, e))?
+            .compile_matcher();
+        Ok(PathFilter {
+            pattern: pattern.to_string(),
+            glob,
+        })
+    }
+
+    /// The filter for `opts.path`, if one is set.
+    pub fn from_options(opts: &DiffOptions) -> Result<Option<Self>, Box<dyn Error>> {
+        opts.path.as_deref().map(PathFilter::new).transpose()
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// True when the repo-relative `path` matches the glob or lies under the pattern taken
+    /// as a directory.
+    pub fn matches(&self, path: &Path) -> bool {
+        self.glob.is_match(path) || path.starts_with(&self.pattern)
+    }
+
+    /// A change matches when either its path or, for renames, its old path does.
+    pub fn matches_change(&self, change: &FileChange) -> bool {
+        self.matches(&change.path) || change.old_path.as_deref().is_some_and(|p| self.matches(p))
+    }
+
+    /// Error for a `--path` that selected none of the changed files.
+    pub fn no_match_error(&self) -> Box<dyn Error> {
+        format!("no files matched --path '{}'", self.pattern).into()
+    }
+}
+
+/// Keep the changes matching `opts.path` (all of them when it is unset).
+fn filter_changes(
+    changes: Vec<FileChange>,
+    opts: &DiffOptions,
+) -> Result<Vec<FileChange>, Box<dyn Error>> {
+    Ok(match PathFilter::from_options(opts)? {
+        Some(filter) => changes
+            .into_iter()
+            .filter(|c| filter.matches_change(c))
+            .collect(),
+        None => changes,
+    })
+}
+
+/// Map `diff` CLI version arguments to a (before, after) selector pair.
+pub fn diff_selectors(versions: &[String]) -> (String, String) {
+    match versions {
+        [] => ("0".into(), "L".into()),
+        [l] if l.eq_ignore_ascii_case("L") => ("H".into(), "L".into()),
+        [n] => (n.clone(), "L".into()),
+        [a, b, ..] => (a.clone(), b.clone()),
+    }
+}
+
+/// True for the working tree selectors: `L`, and `W` as in `diff <dir> H W`.
+pub(crate) fn is_workdir(selector: &str) -> bool {
+    selector.eq_ignore_ascii_case("L") || selector.eq_ignore_ascii_case("W")
+}
+
+/// Resolve a diff selector (commit index, `H`, or any revspec) to a commit. `L` is not
+/// handled here; callers check `is_workdir` first.
+pub(crate) fn resolve_selector<'repo>(
+    repo: &'repo Repository,
+    dir: &str,
+    selector: &str,
+    no_fetch: bool,
+) -> Result<git2::Commit<'repo>, Box<dyn Error>> {
+    if selector.eq_ignore_ascii_case("H") {
+        return get_remote_head_commit_with_options(repo, dir, no_fetch);
+    }
+    if is_short_sha(selector) {
+        return resolve_short_sha(repo, selector);
+    }
+    if let Ok(idx) = selector.parse::<i32>() {
+        return get_commit_by_index(repo, idx)
+            .map_err(|_| format!("invalid repo index '{}'", selector).into());
+    }
+    repo.revparse_single(selector)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|_| format!("unknown diff selector '{}'", selector).into())
+}
+
+/// List the files changed between two selectors with per-file line counts, using the
+/// default rename detection settings of `DiffOptions`.
+pub fn compute_diff(
+    dir: &str,
+    before_selector: &str,
+    after_selector: &str,
+) -> Result<Vec<FileChange>, Box<dyn Error>> {
+    compute_diff_with_options(
+        dir,
+        before_selector,
+        after_selector,
+        &DiffOptions::default(),
+    )
+}
+
+/// `compute_diff` with explicit options (rename similarity threshold, `--path` filter).
+pub fn compute_diff_with_options(
+    dir: &str,
+    before_selector: &str,
+    after_selector: &str,
+    opts: &DiffOptions,
+) -> Result<Vec<FileChange>, Box<dyn Error>> {
+    if is_workdir(before_selector) {
+        return Err("the working directory ('L' or 'W') can only be used as the after side".into());
+    }
+    let repo = open_repo(dir)?;
+    let before_tree = resolve_selector(&repo, dir, before_selector, opts.no_fetch)?.tree()?;
+    let mut diff = if is_workdir(after_selector) {
+        let mut dopts = git2::DiffOptions::new();
+        dopts.include_untracked(true).recurse_untracked_dirs(true);
+        repo.diff_tree_to_workdir_with_index(Some(&before_tree), Some(&mut dopts))?
+    } else {
+        let after_tree = resolve_selector(&repo, dir, after_selector, opts.no_fetch)?.tree()?;
+        repo.diff_tree_to_tree(Some(&before_tree), Some(&after_tree), None)?
+    };
+    filter_changes(changes_from_diff(&mut diff, opts.rename_threshold)?, opts)
+}
+
+/// List the files changed between `selector` in the repository at `dir` (before) and
+/// `other_selector` in a different repository at `other_dir` (after), e.g. a fork and an
+/// upstream clone. Both sides must be commits; the working directory (`L`) is refused. The
+/// other repository's objects are made readable through an in-memory alternate of `dir`'s
+/// object database, so nothing is fetched or written.
+pub fn compute_repo_diff(
+    dir: &str,
+    selector: &str,
+    other_dir: &str,
+    other_selector: &str,
+    opts: &DiffOptions,
+) -> Result<Vec<FileChange>, Box<dyn Error>> {
+    if is_workdir(selector) || is_workdir(other_selector) {
+        return Err(
+            "comparing two repositories needs a commit on each side, not 'L' or 'W'".into(),
+        );
+    }
+    let repo = open_repo_with_commits(dir)?;
+    let other = open_repo_with_commits(other_dir)?;
+    let before_tree = resolve_selector(&repo, dir, selector, opts.no_fetch)?.tree()?;
+    let other_tree_id = resolve_selector(&other, other_dir, other_selector, opts.no_fetch)?
+        .tree()?
+        .id();
+    repo.odb()?
+        .add_disk_alternate(&other.path().join("objects").to_string_lossy())?;
+    let after_tree = repo.find_tree(other_tree_id)?;
+    let mut diff = repo.diff_tree_to_tree(Some(&before_tree), Some(&after_tree), None)?;
+    filter_changes(changes_from_diff(&mut diff, opts.rename_threshold)?, opts)
+}
+
+/// Run rename detection on `diff` (pairs at least `rename_threshold` percent similar) and
+/// convert its deltas into `FileChange`s. A rename counts as a single change.
+pub fn changes_from_diff(
+    diff: &mut Diff<'_>,
+    rename_threshold: u16,
+) -> Result<Vec<FileChange>, Box<dyn Error>> {
+    let mut find = DiffFindOptions::new();
+    find.renames(true)
+        .for_untracked(true)
+        .rename_threshold(rename_threshold);
+    diff.find_similar(Some(&mut find))?;
+
+    let mut changes = Vec::new();
+    for (idx, delta) in diff.deltas().enumerate() {
+        let status = match delta.status() {
+            Delta::Added | Delta::Untracked | Delta::Copied => ChangeKind::Added,
+            Delta::Modified => ChangeKind::Modified,
+            Delta::Deleted => ChangeKind::Deleted,
+            Delta::Renamed => ChangeKind::Renamed,
+            Delta::Typechange => ChangeKind::TypeChange,
+            _ => continue,
+        };
+        let old = delta.old_file().path().map(|p| p.to_path_buf());
+        let path = match delta
+            .new_file()
+            .path()
+            .map(|p| p.to_path_buf())
+            .or(old.clone())
+        {
+            Some(p) => p,
+            None => continue,
+        };
+        if is_in_excluded_path(&path) {
+            continue;
+        }
+        let (insertions, deletions) = match Patch::from_diff(diff, idx)? {
+            Some(patch) => {
+                let (_, added, removed) = patch.line_stats()?;
+                (added, removed)
+            }
+            None => (0, 0),
+        };
+        changes.push(FileChange {
+            category: detect_file_type(&path),
+            old_path: if status == ChangeKind::Renamed {
+                old
+            } else {
+                None
+            },
+            path,
+            status,
+            insertions,
+            deletions,
+        });
+    }
+    Ok(changes)
+}
+
+/// Total inserted and deleted lines of `diff` (`Diff::stats`), e.g. for the `update`
+/// summary.
+pub fn diff_line_stats(diff: &Diff<'_>) -> Result<(usize, usize), Box<dyn Error>> {
+    let stats = diff.stats()?;
+    Ok((stats.insertions(), stats.deletions()))
+}
+
+/// `(+X, -Y lines)` suffix for `diff_line_stats` totals.
+pub fn format_line_stats(insertions: usize, deletions: usize) -> String {
+    format!("(+{}, -{} lines)", insertions, deletions)
+}
+
+/// Render a change for `info`/`update` listings: additions green, deletions red, renames
+/// yellow as `old -> new`, everything else plain.
+pub fn render_change(change: &FileChange) -> String {
+    let path = change.path.to_string_lossy();
+    match (change.status, &change.old_path) {
+        (ChangeKind::Added, _) => colorize(GREEN, path),
+        (ChangeKind::Deleted, _) => colorize(RED, path),
+        (ChangeKind::Renamed, Some(old)) => {
+            colorize(YELLOW, format!("{} -> {}", old.to_string_lossy(), path))
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// How much of each commit's file list `info` shows (`--files`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum FileListMode {
+    /// No file list; commits are not diffed at all.
+    None,
+    /// The first `FILE_LIST_SUMMARY` entries, then how many more.
+    #[default]
+    Summary,
+    /// Every changed file.
+    Full,
+}
+
+/// Entries `FileListMode::Summary` shows before `… and N more`.
+pub const FILE_LIST_SUMMARY: usize = 5;
+
+/// The `info` file column for `changes`: each change as `render_change` colors it (a rename
+/// is one entry), comma-separated, cut after `FILE_LIST_SUMMARY` entries in `Summary` mode.
+pub fn render_file_list(changes: &[FileChange], mode: FileListMode) -> String {
+    let shown = match mode {
+        FileListMode::None => 0,
+        FileListMode::Summary => FILE_LIST_SUMMARY,
+        FileListMode::Full => changes.len(),
+    };
+    let mut list = changes
+        .iter()
+        .take(shown)
+        .map(render_change)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let rest = changes.len().saturating_sub(shown);
+    if mode == FileListMode::Summary && rest > 0 {
+        list.push_str(&format!(" … and {} more", rest));
+    }
+    list
+}
+
+/// Plain `old -> new` (renames) or path label, as used by `update --interactive` prompts.
+pub fn change_label(change: &FileChange) -> String {
+    match &change.old_path {
+        Some(old) => format!(
+            "{} -> {}",
+            old.to_string_lossy().replace('\\', "/"),
+            change.path.to_string_lossy().replace('\\', "/")
+        ),
+        None => change.path.to_string_lossy().replace('\\', "/"),
+    }
+}
+
+/// Commit message summarizing `changes` by `detect_file_type` label, e.g.
+/// `Update: 5 files (3 Rust, 2 Documentation)`. Labels are listed most files first (ties
+/// by label); unrecognized files count as "other". Used by `update --auto-message`.
+pub fn auto_commit_message(changes: &[FileChange]) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for change in changes {
+        *counts
+            .entry(change.category.unwrap_or("other"))
+            .or_default() += 1;
+    }
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    let noun = if changes.len() == 1 { "file" } else { "files" };
+    if counts.is_empty() {
+        return format!("Update: 0 {}", noun);
+    }
+    let parts: Vec<String> = counts
+        .iter()
+        .map(|(label, n)| format!("{} {}", n, label))
+        .collect();
+    format!("Update: {} {} ({})", changes.len(), noun, parts.join(", "))
+}
+
+/// Changes staged in the index relative to HEAD, with renames paired up (a staged delete
+/// plus add of a similar file is one `Renamed` change).
+pub fn staged_changes(dir: &str, rename_threshold: u16) -> Result<Vec<FileChange>, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    let head_tree = repo.head()?.peel_to_tree()?;
+    let mut diff = repo.diff_tree_to_index(Some(&head_tree), None, None)?;
+    changes_from_diff(&mut diff, rename_threshold)
+}
+
+/// One commit as listed by `info`.
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    /// Display index: 0 is the newest commit.
+    pub index: usize,
+    pub id: Oid,
+    pub summary: String,
+    /// Commit time with the committer's recorded UTC offset.
+    pub time: git2::Time,
+    /// Parent ids, first parent first.
+    pub parents: Vec<Oid>,
+    /// Files changed relative to the first parent (or everything, for a root commit).
+    pub changes: Vec<FileChange>,
+}
+
+/// Commits reachable from HEAD, oldest first, with their changed files.
+pub fn commit_history(dir: &str, rename_threshold: u16) -> Result<Vec<CommitInfo>, Box<dyn Error>> {
+    commit_history_since(dir, rename_threshold, None)
+}
+
+/// `commit_history` limited to `since..HEAD` when `since` is given.
+pub fn commit_history_since(
+    dir: &str,
+    rename_threshold: u16,
+    since: Option<Oid>,
+) -> Result<Vec<CommitInfo>, Box<dyn Error>> {
+    let opts = HistoryOptions {
+        since,
+        oldest_first: true,
+        ..HistoryOptions::default()
+    };
+    let mut out = Vec::new();
+    for_each_commit(dir, rename_threshold, &opts, |info| {
+        out.push(info);
+        Ok(())
+    })?;
+    Ok(out)
+}
+
+/// Which commits `for_each_commit` visits, and in what order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryOptions {
+    /// Only commits after this one (`since..HEAD`).
+    pub since: Option<Oid>,
+    /// Only the newest N commits.
+    pub limit: Option<usize>,
+    /// Visit oldest first (indices still count from the newest commit, 0).
+    pub oldest_first: bool,
+    /// How `info` prints commit times (`--utc`, `--relative`).
+    pub time_format: TimeFormat,
+    /// How much of the file list `info` prints; with `None` commits are not diffed and
+    /// `CommitInfo::changes` stays empty.
+    pub files: FileListMode,
+    /// Topological order (children before parents) with an ASCII graph (`info --graph`).
+    pub graph: bool,
+    /// Mark each commit `[pushed]` or `[local]` (`info --remote`).
+    pub mark_pushed: bool,
+    /// Remote branch tip the markers are judged against (`remote_tracking_tip`); `None`
+    /// when the branch is not on the remote, so every commit is local.
+    pub remote_tip: Option<Oid>,
+}
+
+/// Walk the commits reachable from HEAD, newest first unless `opts.oldest_first`, and hand
+/// each one to `f` as soon as its diff is computed. Only visited commits are diffed and no
+/// list of all commit ids is built, so output can start right away on long histories.
+/// Returns the number of commits visited.
+pub fn for_each_commit(
+    dir: &str,
+    rename_threshold: u16,
+    opts: &HistoryOptions,
+    mut f: impl FnMut(CommitInfo) -> Result<(), Box<dyn Error>>,
+) -> Result<usize, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    let order = if opts.graph {
+        Sort::TOPOLOGICAL | Sort::TIME
+    } else {
+        Sort::TIME
+    };
+    let walk = |sorting: Sort| -> Result<git2::Revwalk<'_>, git2::Error> {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push_head()?;
+        if let Some(oid) = opts.since {
+            revwalk.hide(oid)?;
+        }
+        revwalk.set_sorting(sorting)?;
+        Ok(revwalk)
+    };
+    let limit = opts.limit.unwrap_or(usize::MAX);
+    if !opts.oldest_first {
+        let mut visited = 0;
+        for (index, id) in walk(order)?.take(limit).enumerate() {
+            f(commit_info(
+                &repo,
+                id?,
+                index,
+                rename_threshold,
+                opts.files,
+            )?)?;
+            visited += 1;
+        }
+        return Ok(visited);
+    }
+    // A counting pass (ids only) tells how many of the oldest commits to skip.
+    let total = walk(Sort::TIME)?.count();
+    let shown = total.min(limit);
+    for (i, id) in walk(Sort::TIME | Sort::REVERSE)?
+        .skip(total - shown)
+        .enumerate()
+    {
+        f(commit_info(
+            &repo,
+            id?,
+            shown - 1 - i,
+            rename_threshold,
+            opts.files,
+        )?)?;
+    }
+    Ok(shown)
+}
+
+fn commit_info(
+    repo: &Repository,
+    id: Oid,
+    index: usize,
+    rename_threshold: u16,
+    files: FileListMode,
+) -> Result<CommitInfo, Box<dyn Error>> {
+    let commit = repo.find_commit(id)?;
+    let changes = if files == FileListMode::None {
+        Vec::new()
+    } else {
+        let tree = commit.tree()?;
+        let parent_tree = if commit.parent_count() > 0 {
+            Some(commit.parent(0)?.tree()?)
+        } else {
+            None
+        };
+        let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        changes_from_diff(&mut diff, rename_threshold)?
+    };
+    Ok(CommitInfo {
+        index,
+        id,
+        summary: commit.summary().unwrap_or("(no message)").to_string(),
+        time: commit.time(),
+        parents: commit.parent_ids().collect(),
+        changes,
+    })
+}
+
+/// One commit touching the file followed by `file_history`.
+#[derive(Debug, Clone)]
+pub struct FileLogEntry {
+    /// Display index (0 is the newest commit), as in `info`.
+    pub index: usize,
+    pub id: Oid,
+    pub summary: String,
+    pub time: i64,
+    /// The file's name in this commit.
+    pub path: PathBuf,
+    /// Previous name when this commit renamed the file.
+    pub old_path: Option<PathBuf>,
+    pub status: ChangeKind,
+}
+
+/// Commits that changed `path` (repo-relative), newest first. Renames are followed: once a
+/// commit shows the file as renamed, older commits are matched against its previous name.
+/// The walk stops at the commit that added the file.
+pub fn file_history(
+    dir: &str,
+    path: &str,
+    rename_threshold: u16,
+) -> Result<Vec<FileLogEntry>, Box<dyn Error>> {
+    file_history_since(dir, path, rename_threshold, None)
+}
+
+/// `file_history` limited to `since..HEAD` when `since` is given.
+pub fn file_history_since(
+    dir: &str,
+    path: &str,
+    rename_threshold: u16,
+    since: Option<Oid>,
+) -> Result<Vec<FileLogEntry>, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    let mut current = PathBuf::from(path.replace('\\', "/").trim_start_matches("./"));
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    if let Some(oid) = since {
+        revwalk.hide(oid)?;
+    }
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    let mut out = Vec::new();
+    for (index, id) in revwalk.enumerate() {
+        let commit = repo.find_commit(id?)?;
+        let tree = commit.tree()?;
+        let parent_tree = if commit.parent_count() > 0 {
+            Some(commit.parent(0)?.tree()?)
+        } else {
+            None
+        };
+        let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let change = changes_from_diff(&mut diff, rename_threshold)?
+            .into_iter()
+            .find(|c| c.path == current);
+        let Some(change) = change else { continue };
+        out.push(FileLogEntry {
+            index,
+            id: commit.id(),
+            summary: commit.summary().unwrap_or("(no message)").to_string(),
+            time: commit.time().seconds(),
+            path: change.path.clone(),
+            old_path: change.old_path.clone(),
+            status: change.status,
+        });
+        match (change.status, change.old_path) {
+            (ChangeKind::Renamed, Some(old)) => current = old,
+            (ChangeKind::Added, _) => break,
+            _ => {}
+        }
+    }
+    if out.is_empty() {
+        return Err(format!("no history found for '{}'", path).into());
+    }
+    Ok(out)
+}
+
+/// The most recent commit that touched a file (`whohas`).
+#[derive(Debug, Clone)]
+pub struct LastTouch {
+    pub id: Oid,
+    pub time: i64,
+    /// Author as `Name <email>`.
+    pub author: String,
+    pub summary: String,
+}
+
+/// Newest commit reachable from HEAD that changed `path` (repo-relative), or `None` if no
+/// commit did. Each commit's tree is diffed against its parents' trees, limited to `path`;
+/// a merge that kept one parent's version is not counted, as in `git log <path>`.
+pub fn last_commit_for_path(dir: &str, path: &str) -> Result<Option<LastTouch>, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    let path = path.replace('\\', "/");
+    let path = path.trim_start_matches("./");
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    for id in revwalk {
+        let commit = repo.find_commit(id?)?;
+        let tree = commit.tree()?;
+        // A root commit is compared against the empty tree.
+        let parent_trees = if commit.parent_count() == 0 {
+            vec![None]
+        } else {
+            commit
+                .parents()
+                .map(|p| p.tree().map(Some))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        let mut touched = true;
+        for parent_tree in &parent_trees {
+            let mut opts = git2::DiffOptions::new();
+            opts.pathspec(path).disable_pathspec_match(true);
+            let diff =
+                repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
+            if diff.deltas().len() == 0 {
+                touched = false;
+                break;
+            }
+        }
+        if touched {
+            let author = commit.author();
+            return Ok(Some(LastTouch {
+                id: commit.id(),
+                time: commit.time().seconds(),
+                author: format!(
+                    "{} <{}>",
+                    author.name().unwrap_or("(unknown)"),
+                    author.email().unwrap_or("(unknown)")
+                ),
+                summary: commit.summary().unwrap_or("(no message)").to_string(),
+            }));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,188 @@
+//! CI results for `mdcode gh_status`: check runs and commit statuses reported for HEAD.
+//!
+//! All HTTP goes through `ChecksApi`, so tests can answer with canned JSON;
+//! `GithubChecksApi` is the real implementation on top of octocrab.
+
+use crate::{colorize, open_repo_with_commits, GithubProvider, MdcodeError, RemoteProvider};
+use crate::{GREEN, RED, YELLOW};
+use serde_json::Value;
+use std::error::Error;
+
+/// Source of GitHub API responses (`path` is relative to the API root, e.g.
+/// `/repos/o/r/commits/<sha>/check-runs`).
+pub trait ChecksApi {
+    fn get_json(&self, path: &str) -> Result<Value, Box<dyn Error>>;
+}
+
+/// `ChecksApi` backed by the GitHub REST API (token from `resolve_github_token`).
+pub struct GithubChecksApi {
+    pub token_file: Option<String>,
+}
+
+impl ChecksApi for GithubChecksApi {
+    #[cfg(any(feature = "offline_gh", coverage))]
+    fn get_json(&self, _path: &str) -> Result<Value, Box<dyn Error>> {
+        Err("GitHub API is not available in offline builds".into())
+    }
+
+    #[cfg(all(not(feature = "offline_gh"), not(coverage)))]
+    fn get_json(&self, path: &str) -> Result<Value, Box<dyn Error>> {
+        let (token, _source) = crate::resolve_github_token(self.token_file.as_deref())?;
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let octocrab = octocrab::Octocrab::builder()
+                .personal_token(token)
+                .build()?;
+            let url = octocrab.absolute_url(path)?;
+            crate::with_retries("GitHub status query", || async {
+                crate::retry::json_or_failure(octocrab._get(url.clone(), None::<&()>).await).await
+            })
+            .await
+        })
+    }
+}
+
+/// Overall outcome of a commit's checks; the discriminant is the `gh_status` exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Success = 0,
+    Failure = 1,
+    Pending = 2,
+}
+
+/// One check run (GitHub Actions and other apps) or legacy commit status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    pub name: String,
+    /// `queued`, `in_progress` or `completed`.
+    pub status: String,
+    /// Set once completed: `success`, `failure`, `neutral`, `skipped`, `cancelled`, ...
+    pub conclusion: Option<String>,
+    pub url: Option<String>,
+}
+
+impl CheckResult {
+    pub fn state(&self) -> CheckState {
+        match self.conclusion.as_deref() {
+            _ if self.status != "completed" => CheckState::Pending,
+            Some("success") | Some("neutral") | Some("skipped") => CheckState::Success,
+            _ => CheckState::Failure,
+        }
+    }
+}
+
+fn field(v: &Value, key: &str) -> Option<String> {
+    v.get(key).and_then(|s| s.as_str()).map(|s| s.to_string())
+}
+
+/// Check runs and commit statuses for `sha` in `slug` (`owner/name`).
+pub fn commit_checks(
+    api: &dyn ChecksApi,
+    slug: &str,
+    sha: &str,
+) -> Result<Vec<CheckResult>, Box<dyn Error>> {
+    let mut checks = Vec::new();
+    let runs = api.get_json(&format!(
+        "/repos/{}/commits/{}/check-runs?per_page=100",
+        slug, sha
+    ))?;
+    for run in runs["check_runs"].as_array().into_iter().flatten() {
+        checks.push(CheckResult {
+            name: field(run, "name").unwrap_or_else(|| "(unnamed)".into()),
+            status: field(run, "status").unwrap_or_else(|| "queued".into()),
+            conclusion: field(run, "conclusion"),
+            url: field(run, "html_url").or_else(|| field(run, "details_url")),
+        });
+    }
+    let combined = api.get_json(&format!("/repos/{}/commits/{}/status", slug, sha))?;
+    for status in combined["statuses"].as_array().into_iter().flatten() {
+        let state = field(status, "state").unwrap_or_else(|| "pending".into());
+        let pending = state == "pending";
+        checks.push(CheckResult {
+            name: field(status, "context").unwrap_or_else(|| "(unnamed)".into()),
+            status: if pending { "pending" } else { "completed" }.into(),
+            conclusion: (!pending).then_some(state),
+            url: field(status, "target_url"),
+        });
+    }
+    Ok(checks)
+}
+
+/// Failure if any check failed, otherwise Pending if any is still running, otherwise
+/// Success. None when the commit has no checks at all.
+pub fn overall_state(checks: &[CheckResult]) -> Option<CheckState> {
+    let states: Vec<CheckState> = checks.iter().map(CheckResult::state).collect();
+    if states.is_empty() {
+        None
+    } else if states.contains(&CheckState::Failure) {
+        Some(CheckState::Failure)
+    } else if states.contains(&CheckState::Pending) {
+        Some(CheckState::Pending)
+    } else {
+        Some(CheckState::Success)
+    }
+}
+
+/// `name  status/conclusion  url`, colored by state.
+pub fn format_check_line(check: &CheckResult) -> String {
+    let color = match check.state() {
+        CheckState::Success => GREEN,
+        CheckState::Failure => RED,
+        CheckState::Pending => YELLOW,
+    };
+    let outcome = match &check.conclusion {
+        Some(c) => format!("{}/{}", check.status, c),
+        None => check.status.clone(),
+    };
+    format!(
+        "{}  {}  {}",
+        check.name,
+        colorize(color, outcome),
+        check.url.as_deref().unwrap_or("-")
+    )
+}
+
+/// Print the CI checks for HEAD of `directory` (origin must be a GitHub repository).
+/// Returns the overall state; a commit without checks counts as Success.
+pub fn gh_status_with_api(
+    directory: &str,
+    api: &dyn ChecksApi,
+) -> Result<CheckState, Box<dyn Error>> {
+    let repo = open_repo_with_commits(directory)?;
+    let remote = repo
+        .find_remote("origin")
+        .map_err(|_| "Remote 'origin' not found")?;
+    let url = remote.url().unwrap_or_default().to_string();
+    let slug = GithubProvider::default()
+        .parse_slug(&url)
+        .ok_or_else(|| format!("origin '{}' is not a GitHub repository", url))?;
+    let sha = repo.head()?.peel_to_commit()?.id().to_string();
+    let checks = commit_checks(api, &slug, &sha)?;
+    #[cfg(not(coverage))]
+    {
+        println!("CI checks for {} at {}:", slug, &sha[..7]);
+        if checks.is_empty() {
+            println!("No checks reported for this commit.");
+        }
+        for check in &checks {
+            println!("  {}", format_check_line(check));
+        }
+    }
+    Ok(overall_state(&checks).unwrap_or(CheckState::Success))
+}
+
+/// `gh_status_with_api` against GitHub, turning failing (1) or pending (2) checks into an
+/// `MdcodeError::ExitCode` for the process exit status.
+pub fn gh_status(directory: &str, token_file: Option<String>) -> Result<(), Box<dyn Error>> {
+    match gh_status_with_api(directory, &GithubChecksApi { token_file })? {
+        CheckState::Success => Ok(()),
+        CheckState::Failure => {
+            Err(MdcodeError::ExitCode(CheckState::Failure as i32, "CI checks failed".into()).into())
+        }
+        CheckState::Pending => Err(MdcodeError::ExitCode(
+            CheckState::Pending as i32,
+            "CI checks still running".into(),
+        )
+        .into()),
+    }
+}
@@ -0,0 +1,109 @@
+//! The repository config file, `.mdcode.toml` at the repository root.
+//!
+//! `load_config` reads and parses it once; each feature then reads its own key from the
+//! result: `[size_caps]` (`SizeCaps`), `patterns` under `[lfs]` (`configured_lfs_patterns`),
+//! `max_new_files` under `[update]` (`configured_max_new_files`) and `include_hidden` under
+//! `[scan]` (`HiddenPolicy`). `--config <path>` reads another file instead: `execute_cli`
+//! loads it once (`Config::load_file`) and passes it down, and `config_for` picks it over
+//! the repository's own file.
+//!
+//! Glob lists (`include_hidden`, `[lfs] patterns`) are read with `Config::glob_list` and
+//! compiled with `anchored_glob`, so both match paths the same way.
+
+use globset::{Glob, GlobBuilder};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Repository config file, at the repository root.
+pub const MDCODE_CONFIG_FILE: &str = ".mdcode.toml";
+
+/// A parsed config file and the path it was read from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The file, for messages.
+    pub path: PathBuf,
+    /// Its document; an empty table when the repository has no config file.
+    pub doc: toml::Value,
+}
+
+impl Config {
+    /// Read the config file at `path` (`--config`). Unlike `.mdcode.toml`, a missing file
+    /// is an error.
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(format!("config file '{}' does not exist", path.display()).into());
+        }
+        Config::read(path.to_path_buf())
+    }
+
+    /// Parse the file at `path`.
+    fn read(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let doc = fs::read_to_string(&path)?
+            .parse()
+            .map_err(|e| format!("invalid {}: {}", path.display(), e))?;
+        Ok(Config { path, doc })
+    }
+
+    /// The value of `key` in `[table]`, if set.
+    pub fn get(&self, table: &str, key: &str) -> Option<&toml::Value> {
+        self.doc.get(table).and_then(|t| t.get(key))
+    }
+
+    /// The list of globs at `key` in `[table]`; empty when not set, an error naming the
+    /// file when it is not a list of strings.
+    pub fn glob_list(&self, table: &str, key: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let Some(value) = self.get(table, key) else {
+            return Ok(Vec::new());
+        };
+        value
+            .as_array()
+            .and_then(|globs| {
+                globs
+                    .iter()
+                    .map(|g| g.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| self.invalid(&format!("{}.{} must be a list of globs", table, key)))
+    }
+
+    /// Error for a malformed entry, naming the file: `invalid <path>: <problem>`.
+    pub fn invalid(&self, problem: &str) -> Box<dyn Error> {
+        format!("invalid {}: {}", self.path.display(), problem).into()
+    }
+}
+
+/// `.mdcode.toml` at the root of `dir`. A missing file is an empty config; an unreadable or
+/// malformed one is an error naming the file.
+pub fn load_config(dir: &str) -> Result<Config, Box<dyn Error>> {
+    let path = Path::new(dir).join(MDCODE_CONFIG_FILE);
+    if !path.exists() {
+        return Ok(Config {
+            path,
+            doc: toml::Value::Table(Default::default()),
+        });
+    }
+    Config::read(path)
+}
+
+/// The config for commands on `dir`: `config` (the `--config` file) when given, else
+/// `load_config(dir)`.
+pub fn config_for(dir: &str, config: Option<&Config>) -> Result<Config, Box<dyn Error>> {
+    match config {
+        Some(config) => Ok(config.clone()),
+        None => load_config(dir),
+    }
+}
+
+/// Compile `pattern` for matching paths relative to the repository root, as in
+/// `.gitignore`: one containing `/` is anchored at the root (a leading `/` is dropped), one
+/// without matches the name at any depth. `*` does not cross `/`.
+pub fn anchored_glob(pattern: &str) -> Result<Glob, globset::Error> {
+    let glob = if pattern.contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+    GlobBuilder::new(&glob).literal_separator(true).build()
+}
@@ -15,6 +15,12 @@ pub fn detect_file_type(file_path: &Path) -> Option<&'static str> {
         if file_name.eq_ignore_ascii_case("CMakeLists.txt") {
             return Some("CMake");
         }
+        if file_name == ".gitkeep" {
+            return Some("Placeholder");
+        }
+    }
+    if is_ci_config(file_path) {
+        return Some("CI Config");
     }
 
     let extension = file_path.extension()?.to_str()?.to_lowercase();
@@ -87,3 +93,15 @@ pub fn detect_file_type(file_path: &Path) -> Option<&'static str> {
         _ => None,
     }
 }
+
+/// GitHub Actions workflows (`.github/workflows/*.yml`) and `.gitlab-ci.yml`.
+fn is_ci_config(file_path: &Path) -> bool {
+    let name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if name == ".gitlab-ci.yml" {
+        return true;
+    }
+    let yaml = name.ends_with(".yml") || name.ends_with(".yaml");
+    let mut dirs = file_path.parent().into_iter().flat_map(|p| p.iter().rev());
+    yaml && dirs.next().is_some_and(|d| d == "workflows")
+        && dirs.next().is_some_and(|d| d == ".github")
+}
@@ -0,0 +1,239 @@
+//! Environment checks for `mdcode doctor`, for when a first run fails with a confusing error.
+//!
+//! Each check reports `OK`, `WARN` (mdcode works but a feature is unavailable or a default
+//! is used) or `FAIL` (most commands cannot work), with a hint for anything not OK. Only a
+//! `FAIL` makes the command exit non-zero.
+
+use crate::{
+    check_git_installed, colorize, gh_cli_path, resolve_github_token,
+    resolve_signature_with_source, MdcodeError, SignatureOptions, GREEN, RED, YELLOW,
+};
+use git2::Repository;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Outcome of one `doctor` check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoctorStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// One `doctor` finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorCheck {
+    /// `git`, `identity`, `github` or `diff-tool`.
+    pub name: &'static str,
+    pub status: DoctorStatus,
+    /// What was found.
+    pub detail: String,
+    /// How to fix a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        DoctorCheck {
+            name,
+            status: DoctorStatus::Ok,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn with_hint(
+        name: &'static str,
+        status: DoctorStatus,
+        detail: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        DoctorCheck {
+            name,
+            status,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// `git` is on PATH (`check_git_installed`); reports its version.
+pub fn check_git() -> DoctorCheck {
+    if !check_git_installed() {
+        return DoctorCheck::with_hint(
+            "git",
+            DoctorStatus::Fail,
+            "git not found on PATH",
+            "install git (https://git-scm.com/downloads) and make sure `git --version` works",
+        );
+    }
+    let version = Command::new("git")
+        .arg("--version")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+    DoctorCheck::ok("git", version)
+}
+
+/// A commit identity is configured for `dir` (`resolve_signature_with_source` inside a
+/// repository; env vars and the global git config elsewhere).
+pub fn check_identity(dir: &str) -> DoctorCheck {
+    let quiet = SignatureOptions {
+        allow_fallback_identity: false,
+        interactive: false,
+        sign: false,
+    };
+    let found = match Repository::discover(dir) {
+        Ok(repo) => resolve_signature_with_source(&repo, &quiet)
+            .ok()
+            .map(|(sig, src)| {
+                (
+                    format!(
+                        "{} <{}>",
+                        sig.name().unwrap_or("(unknown)"),
+                        sig.email().unwrap_or("(unknown)")
+                    ),
+                    src,
+                )
+            }),
+        Err(_) => identity_outside_repo(),
+    };
+    match found {
+        Some((who, source)) => DoctorCheck::ok("identity", format!("{} (source: {})", who, source)),
+        None => DoctorCheck::with_hint(
+            "identity",
+            DoctorStatus::Warn,
+            "no commit author configured",
+            "run `git config --global user.name \"Name\"` and `git config --global user.email you@example.com`, or `mdcode config --set-author \"Name <email>\"` in a repository",
+        ),
+    }
+}
+
+/// Author from env vars or the global git config, as `(Name <email>, source)`.
+fn identity_outside_repo() -> Option<(String, String)> {
+    for (name_var, email_var) in [
+        ("GIT_AUTHOR_NAME", "GIT_AUTHOR_EMAIL"),
+        ("GIT_COMMITTER_NAME", "GIT_COMMITTER_EMAIL"),
+    ] {
+        if let (Ok(name), Ok(email)) = (std::env::var(name_var), std::env::var(email_var)) {
+            return Some((
+                format!("{} <{}>", name, email),
+                format!("env:{}/{}", name_var, email_var),
+            ));
+        }
+    }
+    let cfg = git2::Config::open_default().ok()?;
+    let (name, email) = (
+        cfg.get_string("user.name").ok()?,
+        cfg.get_string("user.email").ok()?,
+    );
+    Some((
+        format!("{} <{}>", name, email),
+        "git config (global)".into(),
+    ))
+}
+
+/// The GitHub CLI (`gh_cli_path`) or an API token is available for `gh_create`/`gh_status`.
+pub fn check_github() -> DoctorCheck {
+    let token = resolve_github_token(None).ok().map(|(_, source)| source);
+    match (gh_cli_path(), token) {
+        (Some(gh), _) => DoctorCheck::ok("github", format!("GitHub CLI at {}", gh.display())),
+        (None, Some(source)) => DoctorCheck::ok(
+            "github",
+            format!("no GitHub CLI; API token from {}", source),
+        ),
+        (None, None) => DoctorCheck::with_hint(
+            "github",
+            DoctorStatus::Warn,
+            "neither the GitHub CLI nor an API token found",
+            "install gh (https://cli.github.com) and run `gh auth login`, or set GITHUB_TOKEN",
+        ),
+    }
+}
+
+/// A diff tool `diff` can launch: `MDCODE_DIFF_TOOL`, else WinMerge or windiff on PATH.
+pub fn check_diff_tool() -> DoctorCheck {
+    if let Ok(tool) = std::env::var("MDCODE_DIFF_TOOL") {
+        return match find_program(&tool) {
+            Some(path) => DoctorCheck::ok(
+                "diff-tool",
+                format!("{} (MDCODE_DIFF_TOOL)", path.display()),
+            ),
+            None => DoctorCheck::with_hint(
+                "diff-tool",
+                DoctorStatus::Warn,
+                format!("MDCODE_DIFF_TOOL is '{}' but it was not found", tool),
+                "point MDCODE_DIFF_TOOL at an installed tool taking two directories (e.g. meld)",
+            ),
+        };
+    }
+    match ["WinMergeU.exe", "windiff.exe"]
+        .iter()
+        .find_map(|p| find_program(p))
+    {
+        Some(path) => DoctorCheck::ok("diff-tool", path.display().to_string()),
+        None => DoctorCheck::with_hint(
+            "diff-tool",
+            DoctorStatus::Warn,
+            "no diff tool configured",
+            "set MDCODE_DIFF_TOOL to a tool taking two directories (e.g. meld); `diff --name-only` works without one",
+        ),
+    }
+}
+
+/// `program` as given when it is a path to a file, else the first match on PATH.
+fn find_program(program: &str) -> Option<PathBuf> {
+    let direct = Path::new(program);
+    if direct.components().count() > 1 {
+        return direct.is_file().then(|| direct.to_path_buf());
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|p| p.is_file())
+}
+
+/// Run every check, in the order they are printed.
+pub fn run_doctor_checks(dir: &str) -> Vec<DoctorCheck> {
+    vec![
+        check_git(),
+        check_identity(dir),
+        check_github(),
+        check_diff_tool(),
+    ]
+}
+
+/// `OK name: detail`, `WARN ...` or `FAIL ...`, plus an indented `hint:` line.
+pub fn format_doctor_check(check: &DoctorCheck) -> String {
+    let status = match check.status {
+        DoctorStatus::Ok => colorize(GREEN, "OK  "),
+        DoctorStatus::Warn => colorize(YELLOW, "WARN"),
+        DoctorStatus::Fail => colorize(RED, "FAIL"),
+    };
+    let mut line = format!("{} {}: {}", status, check.name, check.detail);
+    if let Some(hint) = &check.hint {
+        line.push_str(&format!("\n     hint: {}", hint));
+    }
+    line
+}
+
+/// `mdcode doctor`: print the checks; any `FAIL` becomes exit status 1.
+pub fn doctor_command(dir: &str) -> Result<Vec<DoctorCheck>, Box<dyn Error>> {
+    let checks = run_doctor_checks(dir);
+    #[cfg(not(coverage))]
+    for check in &checks {
+        log::info!("{}", format_doctor_check(check));
+    }
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == DoctorStatus::Fail)
+        .count();
+    if failed > 0 {
+        return Err(MdcodeError::ExitCode(
+            1,
+            format!("{} of {} doctor checks failed", failed, checks.len()),
+        )
+        .into());
+    }
+    Ok(checks)
+}
@@ -0,0 +1,78 @@
+//! Loading `KEY=VALUE` pairs from a dotenv file into the environment (`--env-file`).
+//!
+//! Variables that are already set keep their value, so the shell (or CI) can always
+//! override the file. The format is the common dotenv subset: blank lines and `#` comments
+//! are skipped, a leading `export ` is allowed, and values may be wrapped in single or
+//! double quotes (inside double quotes `\n`, `\"` and `\\` are unescaped). An unquoted
+//! value ends at ` #`.
+
+use std::error::Error;
+use std::fs;
+
+/// The `(key, value)` pairs of dotenv text `content`, in file order. A line that is not
+/// `KEY=VALUE` with a key of letters, digits and `_` (not starting with a digit) is an
+/// error naming its line number.
+pub fn parse_env_file(content: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut pairs = Vec::new();
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected KEY=VALUE", n + 1))?;
+        let key = key.trim();
+        let valid = !key.is_empty()
+            && !key.starts_with(|c: char| c.is_ascii_digit())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(format!("line {}: invalid variable name '{}'", n + 1, key).into());
+        }
+        pairs.push((key.to_string(), unquote(value.trim())));
+    }
+    Ok(pairs)
+}
+
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return inner.to_string();
+    }
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match (c, chars.clone().next()) {
+                ('\\', Some('n')) => out.push('\n'),
+                ('\\', Some(e @ ('"' | '\\'))) => out.push(e),
+                _ => {
+                    out.push(c);
+                    continue;
+                }
+            }
+            chars.next();
+        }
+        return out;
+    }
+    match value.find(" #") {
+        Some(i) => value[..i].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Set the variables of the dotenv file at `path` that are not set yet. Returns the names
+/// that were set.
+pub fn load_env_file(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("cannot read env file '{}': {}", path, e))?;
+    let pairs = parse_env_file(&content).map_err(|e| format!("env file '{}': {}", path, e))?;
+    let mut set = Vec::new();
+    for (key, value) in pairs {
+        if std::env::var_os(&key).is_none() {
+            std::env::set_var(&key, value);
+            set.push(key);
+        }
+    }
+    Ok(set)
+}
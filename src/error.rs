@@ -0,0 +1,163 @@
+//! Repository discovery and opening with uniform, user-facing errors.
+//!
+//! Commands open repositories through `open_repo`/`open_repo_with_commits` so a missing
+//! repository or one without commits reads the same everywhere.
+//!
+//! Each variant has its own process exit status (`MdcodeError::exit_code`), so scripts can
+//! branch on the kind of failure:
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 1 | any other error; `gh_status`: a check failed |
+//! | 2 | `gh_status`: checks still running |
+//! | 3 | no git repository in the directory |
+//! | 4 | repository has no commits yet |
+//! | 5 | working tree has uncommitted changes |
+//! | 6 | push to the remote failed |
+//! | 7 | a git command hit the `--timeout` limit |
+//! | 8 | the remote the command needs is not configured |
+
+use git2::{ErrorCode, Repository};
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors with a friendly message shared by all commands.
+#[derive(Debug)]
+pub enum MdcodeError {
+    /// The directory is missing or is not a git repository.
+    NoRepository(PathBuf),
+    /// The repository exists but HEAD has no commit yet.
+    EmptyRepository(PathBuf),
+    /// Any other git error, passed through unchanged.
+    Git(git2::Error),
+    /// The command refuses to run on uncommitted changes; carries the full message.
+    DirtyWorkingTree(String),
+    /// `git push` (branch or tag) was rejected or could not reach the remote.
+    PushFailed(String),
+    /// A git command talking to the remote was killed at the `--timeout` limit.
+    TimedOut(String),
+    /// The named remote is not configured; checked before any fetch, push or temp-dir work.
+    RemoteNotFound(String),
+    /// A result the process reports through a specific exit status (e.g. `gh_status`).
+    ExitCode(i32, String),
+}
+
+impl MdcodeError {
+    /// Process exit status for this error (see the table in the module docs).
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            MdcodeError::NoRepository(_) => 3,
+            MdcodeError::EmptyRepository(_) => 4,
+            MdcodeError::DirtyWorkingTree(_) => 5,
+            MdcodeError::PushFailed(_) => 6,
+            MdcodeError::TimedOut(_) => 7,
+            MdcodeError::RemoteNotFound(_) => 8,
+            MdcodeError::ExitCode(code, _) => *code,
+            MdcodeError::Git(_) => 1,
+        }
+    }
+}
+
+/// Process exit status for any command error: `MdcodeError::exit_code`, or 1 for errors
+/// of other types.
+pub fn exit_code_for(err: &(dyn Error + 'static)) -> i32 {
+    err.downcast_ref::<MdcodeError>()
+        .map_or(1, MdcodeError::exit_code)
+}
+
+impl fmt::Display for MdcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MdcodeError::NoRepository(dir) => {
+                write!(f, "No git repository in directory '{}'", dir.display())
+            }
+            MdcodeError::EmptyRepository(dir) => write!(
+                f,
+                "Empty repository: no commits exist in '{}'",
+                dir.display()
+            ),
+            MdcodeError::Git(e) => write!(f, "{}", e),
+            MdcodeError::RemoteNotFound(remote) => write!(
+                f,
+                "remote '{}' not found; create one with `mdcode gh_create` or `git remote add {} <url>`",
+                remote, remote
+            ),
+            MdcodeError::DirtyWorkingTree(message)
+            | MdcodeError::PushFailed(message)
+            | MdcodeError::TimedOut(message)
+            | MdcodeError::ExitCode(_, message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for MdcodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MdcodeError::Git(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<git2::Error> for MdcodeError {
+    fn from(e: git2::Error) -> Self {
+        MdcodeError::Git(e)
+    }
+}
+
+/// Open the repository at `dir`; a missing directory or non-repository maps to
+/// `MdcodeError::NoRepository`.
+pub fn open_repo(dir: &str) -> Result<Repository, MdcodeError> {
+    Repository::open(dir).map_err(|e| {
+        if e.code() == ErrorCode::NotFound {
+            MdcodeError::NoRepository(PathBuf::from(dir))
+        } else {
+            MdcodeError::Git(e)
+        }
+    })
+}
+
+/// Fail with `MdcodeError::RemoteNotFound` unless `repo` has a remote named `remote`.
+pub fn require_remote(repo: &Repository, remote: &str) -> Result<(), MdcodeError> {
+    match repo.find_remote(remote) {
+        Ok(_) => Ok(()),
+        Err(e) if matches!(e.code(), ErrorCode::NotFound | ErrorCode::InvalidSpec) => {
+            Err(MdcodeError::RemoteNotFound(remote.to_string()))
+        }
+        Err(e) => Err(MdcodeError::Git(e)),
+    }
+}
+
+/// `open_repo`, additionally requiring at least one commit (`MdcodeError::EmptyRepository`
+/// for a freshly initialized repository, e.g. one made by `cargo new`).
+pub fn open_repo_with_commits(dir: &str) -> Result<Repository, MdcodeError> {
+    let repo = open_repo(dir)?;
+    match repo.head() {
+        Ok(_) => {}
+        Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => {
+            return Err(MdcodeError::EmptyRepository(PathBuf::from(dir)))
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(repo)
+}
+
+/// The directory a command runs in: `directory` when given, otherwise the root of the
+/// repository enclosing the current directory (`Repository::discover`), so the argument can
+/// be omitted from anywhere inside a project. Bare repositories resolve to their git dir.
+pub fn resolve_directory(directory: Option<&str>) -> Result<String, MdcodeError> {
+    if let Some(dir) = directory {
+        return Ok(dir.to_string());
+    }
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let repo = Repository::discover(&cwd).map_err(|e| {
+        if e.code() == ErrorCode::NotFound {
+            MdcodeError::NoRepository(cwd.clone())
+        } else {
+            MdcodeError::Git(e)
+        }
+    })?;
+    let root = repo.workdir().unwrap_or_else(|| repo.path());
+    Ok(root.components().as_path().to_string_lossy().into_owned())
+}
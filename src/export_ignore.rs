@@ -0,0 +1,95 @@
+//! `export-ignore` from `.gitattributes`, as `git archive` honors it (`diff --export-ignore`).
+//!
+//! For a commit snapshot the patterns come from the `.gitattributes` committed at the root
+//! of that commit's tree, since the working tree's file may say something else; working
+//! tree snapshots use the file on disk. A path is left out when it, or a directory above
+//! it, matches a pattern set `export-ignore`. As in git the last matching line wins, so
+//! `-export-ignore` or `!export-ignore` on a later line includes a path again.
+
+use crate::GITATTRIBUTES_FILE;
+use git2::{Repository, Tree};
+use globset::{GlobBuilder, GlobMatcher};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// The `export-ignore` rules of one `.gitattributes`.
+#[derive(Debug, Clone, Default)]
+pub struct ExportIgnore {
+    /// Pattern and whether the line sets (true) or unsets the attribute.
+    rules: Vec<(GlobMatcher, bool)>,
+}
+
+impl ExportIgnore {
+    /// Rules of the `.gitattributes` text `content`. A pattern without `/` matches a name at
+    /// any depth, one with `/` is anchored at the root; lines that do not compile are
+    /// skipped.
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let Some(set) = fields.rev().find_map(|a| match a {
+                "export-ignore" => Some(true),
+                "-export-ignore" | "!export-ignore" => Some(false),
+                _ => None,
+            }) else {
+                continue;
+            };
+            let pattern = pattern.trim_end_matches('/');
+            let glob = if pattern.contains('/') {
+                pattern.trim_start_matches('/').to_string()
+            } else {
+                format!("**/{}", pattern)
+            };
+            if let Ok(g) = GlobBuilder::new(&glob).literal_separator(true).build() {
+                rules.push((g.compile_matcher(), set));
+            }
+        }
+        ExportIgnore { rules }
+    }
+
+    /// Rules of the `.gitattributes` committed at the root of `tree` (none without one).
+    pub fn from_tree(repo: &Repository, tree: &Tree) -> Result<Self, Box<dyn Error>> {
+        let Some(entry) = tree.get_name(GITATTRIBUTES_FILE) else {
+            return Ok(ExportIgnore::default());
+        };
+        let blob = repo.find_blob(entry.id())?;
+        Ok(ExportIgnore::parse(&String::from_utf8_lossy(
+            blob.content(),
+        )))
+    }
+
+    /// Rules of `<dir>/.gitattributes` in the working tree (none when it does not exist).
+    pub fn load(dir: &str) -> Self {
+        ExportIgnore::parse(
+            &fs::read_to_string(Path::new(dir).join(GITATTRIBUTES_FILE)).unwrap_or_default(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// True when the repo-relative `path` or one of its parent directories is
+    /// `export-ignore`.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        path.ancestors()
+            .filter(|p| !p.as_os_str().is_empty())
+            .any(|p| {
+                self.rules
+                    .iter()
+                    .rev()
+                    .find(|(glob, _)| glob.is_match(p))
+                    .is_some_and(|(_, set)| *set)
+            })
+    }
+}
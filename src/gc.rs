@@ -0,0 +1,204 @@
+//! Repository housekeeping for `mdcode gc`.
+//!
+//! Packs the object store with `git gc --auto` (`--aggressive` passed through), removes the
+//! temporary snapshot directories `mdcode diff` left in the system temp dir for the
+//! repository, and trims the operations journal to its newest entries. Object store figures
+//! are taken before and after so the command can print what it saved.
+
+use crate::{check_git_installed, open_repo, path_size, trim_journal};
+use git2::Repository;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Journal entries `gc` keeps when `--keep-journal` is not given.
+pub const DEFAULT_JOURNAL_KEEP: usize = 1000;
+
+/// Size of a repository's object store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ObjectStoreStats {
+    /// Every object in the database, loose or packed.
+    pub objects: usize,
+    pub loose_objects: usize,
+    pub loose_bytes: u64,
+    pub packs: usize,
+    /// Pack files with their indexes.
+    pub pack_bytes: u64,
+}
+
+/// What `gc_command` did (or, on a dry run, would do).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    pub before: ObjectStoreStats,
+    /// Equal to `before` when git gc did not run.
+    pub after: ObjectStoreStats,
+    pub ran_git_gc: bool,
+    pub removed_snapshots: Vec<PathBuf>,
+    pub snapshot_bytes: u64,
+    pub dropped_journal_entries: usize,
+}
+
+/// Count the objects of `repo` (through the ODB) and measure its loose objects and packs.
+pub fn object_store_stats(repo: &Repository) -> Result<ObjectStoreStats, Box<dyn Error>> {
+    let mut stats = ObjectStoreStats::default();
+    repo.odb()?.foreach(|_| {
+        stats.objects += 1;
+        true
+    })?;
+    let objects = repo.path().join("objects");
+    for entry in fs::read_dir(&objects)?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.len() != 2 || !name.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+        for object in fs::read_dir(entry.path())?.filter_map(|e| e.ok()) {
+            stats.loose_objects += 1;
+            stats.loose_bytes += object.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+    let pack = objects.join("pack");
+    if let Ok(entries) = fs::read_dir(&pack) {
+        stats.packs = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|x| x == "pack"))
+            .count();
+    }
+    stats.pack_bytes = path_size(&pack);
+    Ok(stats)
+}
+
+/// True for the part of a snapshot directory name after `<label>.<dir>.`: the commit time
+/// (`2024-05-01_120000`) or `current`, then `create_temp_dir`'s unique suffix.
+fn is_snapshot_suffix(rest: &str) -> bool {
+    let Some((stamp, unique)) = rest.rsplit_once('.') else {
+        return false;
+    };
+    let stamp_ok = stamp == "current"
+        || (stamp.len() == 17
+            && stamp.char_indices().all(|(i, c)| match i {
+                4 | 7 => c == '-',
+                10 => c == '_',
+                _ => c.is_ascii_digit(),
+            }));
+    stamp_ok && !unique.is_empty() && unique.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The `before`/`after` snapshot directories `mdcode diff` created in the system temp dir
+/// for `dir`, spelled as it was given to `diff` (`create_temp_dir` names them
+/// `<label>.<dir>.<time>.<unique>`).
+pub fn diff_snapshot_dirs(dir: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for label in ["before", "after"] {
+        let prefix = std::env::temp_dir().join(format!("{}.{}.", label, dir));
+        let (Some(parent), Some(name)) = (prefix.parent(), prefix.file_name()) else {
+            continue;
+        };
+        let name = name.to_string_lossy();
+        let Ok(entries) = fs::read_dir(parent) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_dir
+                && file_name
+                    .strip_prefix(name.as_ref())
+                    .is_some_and(is_snapshot_suffix)
+            {
+                found.push(entry.path());
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+fn run_git_gc(dir: &str, aggressive: bool) -> Result<(), Box<dyn Error>> {
+    let mut args = vec!["-C", dir, "gc", "--auto", "--quiet"];
+    if aggressive {
+        args.push("--aggressive");
+    }
+    if !Command::new("git").args(&args).status()?.success() {
+        return Err("git gc failed".into());
+    }
+    Ok(())
+}
+
+/// `mdcode gc`: pack objects, delete the repository's diff snapshots and keep only the
+/// newest `journal_keep` journal entries. Without the git CLI the packing step is skipped;
+/// `dry_run` only reports.
+pub fn gc_command(
+    dir: &str,
+    aggressive: bool,
+    journal_keep: usize,
+    dry_run: bool,
+) -> Result<GcReport, Box<dyn Error>> {
+    let repo = open_repo(dir)?;
+    let mut report = GcReport {
+        before: object_store_stats(&repo)?,
+        ..GcReport::default()
+    };
+    if !dry_run && check_git_installed() {
+        run_git_gc(dir, aggressive)?;
+        report.ran_git_gc = true;
+    }
+    report.after = if report.ran_git_gc {
+        object_store_stats(&repo)?
+    } else {
+        report.before
+    };
+
+    for snapshot in diff_snapshot_dirs(dir) {
+        report.snapshot_bytes += path_size(&snapshot);
+        if !dry_run {
+            fs::remove_dir_all(&snapshot)?;
+        }
+        report.removed_snapshots.push(snapshot);
+    }
+    report.dropped_journal_entries = trim_journal(dir, journal_keep, dry_run)?;
+
+    #[cfg(not(coverage))]
+    log_gc_report(&report, dry_run, dir);
+    Ok(report)
+}
+
+#[cfg(not(coverage))]
+fn log_gc_report(report: &GcReport, dry_run: bool, dir: &str) {
+    use crate::{colorize, format_size, BLUE, GREEN};
+    let store = |s: &ObjectStoreStats| {
+        format!(
+            "{} objects, {} loose ({}), {} packs ({})",
+            s.objects,
+            s.loose_objects,
+            format_size(s.loose_bytes),
+            s.packs,
+            format_size(s.pack_bytes)
+        )
+    };
+    log::info!("{} {}", colorize(BLUE, "Before:"), store(&report.before));
+    if report.ran_git_gc {
+        log::info!("{} {}", colorize(BLUE, "After:"), store(&report.after));
+    } else if dry_run {
+        log::info!("Dry run: would run git gc --auto in '{}'", dir);
+    } else {
+        log::warn!("git is not installed; skipped packing objects");
+    }
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    log::info!(
+        "{} {} {} diff snapshot(s) ({})",
+        colorize(GREEN, "Snapshots:"),
+        verb,
+        report.removed_snapshots.len(),
+        format_size(report.snapshot_bytes)
+    );
+    for snapshot in &report.removed_snapshots {
+        log::debug!("  {}", snapshot.display());
+    }
+    log::info!(
+        "{} {} {} old journal entries",
+        colorize(GREEN, "Journal:"),
+        if dry_run { "would drop" } else { "dropped" },
+        report.dropped_journal_entries
+    );
+}
@@ -0,0 +1,62 @@
+//! `.gitkeep` placeholders so empty directories survive a commit.
+//!
+//! Git only tracks files, so scaffolding folders such as `assets/` or `migrations/` would
+//! be missing from clones. `find_empty_dirs` lists the empty directories the source scan
+//! walks past (excluded directories and ignored paths are left alone) and `write_gitkeeps`
+//! puts a `.gitkeep` in each so it can be staged.
+
+use crate::{is_in_excluded_path, scan_walker, FileOverrides, ScanOptions};
+use ignore::gitignore::GitignoreBuilder;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the placeholder file written into empty directories.
+pub const GITKEEP_FILE: &str = ".gitkeep";
+
+/// Directories under `dir` (not `dir` itself) that have no entries at all, sorted. A
+/// directory holding only empty subdirectories is not listed; its leaves are.
+pub fn find_empty_dirs(dir: &str) -> Vec<PathBuf> {
+    find_empty_dirs_with_options(dir, &ScanOptions::default())
+}
+
+/// `find_empty_dirs` walking the tree as `scan` says.
+pub fn find_empty_dirs_with_options(dir: &str, scan: &ScanOptions) -> Vec<PathBuf> {
+    let root = Path::new(dir);
+    let overrides = FileOverrides::load(dir);
+    let gi = {
+        let mut b = GitignoreBuilder::new(dir);
+        let _ = b.add(root.join(".gitignore"));
+        b.build().ok()
+    };
+    let mut out: Vec<PathBuf> = scan_walker(dir, scan)
+        .flatten()
+        .filter(|e| e.depth() > 0 && e.file_type().is_some_and(|ft| ft.is_dir()))
+        .map(|e| e.into_path())
+        .filter(|p| {
+            let rel = p.strip_prefix(root).unwrap_or(p);
+            !is_in_excluded_path(rel)
+                && !overrides.is_excluded(p, true)
+                && !gi
+                    .as_ref()
+                    .is_some_and(|m| m.matched_path_or_any_parents(p, true).is_ignore())
+        })
+        .filter(|p| fs::read_dir(p).is_ok_and(|mut d| d.next().is_none()))
+        .collect();
+    out.sort();
+    out
+}
+
+/// Write an empty `.gitkeep` into each of `dirs` (skipped on `dry_run`) and return the
+/// placeholder paths.
+pub fn write_gitkeeps(dirs: &[PathBuf], dry_run: bool) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut written = Vec::new();
+    for d in dirs {
+        let keep = d.join(GITKEEP_FILE);
+        if !dry_run {
+            fs::write(&keep, "")?;
+        }
+        written.push(keep);
+    }
+    Ok(written)
+}
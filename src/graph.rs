@@ -0,0 +1,112 @@
+//! Lane allocation for `info --graph`, an ASCII commit graph in the style of
+//! `git log --graph --oneline`.
+//!
+//! `CommitGraph` is fed commits newest first in topological order (children before their
+//! parents), each with its parent ids, and returns the graph columns to print in front of
+//! the commit plus an optional connector line showing branches (`/`) and merges (`\`).
+//! It never looks at a repository, so any id type works.
+
+/// Graph text for one commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphRow {
+    /// Lane columns for the commit's own line, `*` marking the commit (e.g. `| *`).
+    pub commit: String,
+    /// Line printed after the commit when lanes fork, join or shift (e.g. `|\` or `|/`).
+    pub connector: Option<String>,
+}
+
+/// Active lanes: each holds the id of the commit expected next on that column.
+#[derive(Debug, Clone)]
+pub struct CommitGraph<T> {
+    lanes: Vec<T>,
+}
+
+impl<T: Clone + PartialEq> Default for CommitGraph<T> {
+    fn default() -> Self {
+        CommitGraph { lanes: Vec::new() }
+    }
+}
+
+impl<T: Clone + PartialEq> CommitGraph<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Place `id` (whose parents are `parents`, first parent first) and advance the lanes.
+    /// A commit no lane expects (a branch tip) opens a new lane on the right.
+    pub fn next(&mut self, id: &T, parents: &[T]) -> GraphRow {
+        let col = match self.lanes.iter().position(|l| l == id) {
+            Some(col) => col,
+            None => {
+                self.lanes.push(id.clone());
+                self.lanes.len() - 1
+            }
+        };
+        let commit = (0..self.lanes.len())
+            .map(|i| if i == col { "*" } else { "|" })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // A first parent already expected on a lane to the right takes over this column;
+        // that lane joins it.
+        let first = parents.first();
+        let joining =
+            first.and_then(|p| (col + 1..self.lanes.len()).find(|&k| self.lanes[k] == *p));
+
+        // Build the new lanes, marking on the connector line where each one comes from.
+        let mut lanes: Vec<T> = Vec::new();
+        let mut marks: Vec<(usize, char)> = Vec::new();
+        for (i, lane) in self.lanes.iter().enumerate() {
+            if Some(i) == joining {
+                marks.push((2 * i - 1, '/'));
+                continue;
+            }
+            if i != col {
+                marks.push(shift_mark(i, lanes.len()));
+                lanes.push(lane.clone());
+                continue;
+            }
+            match first {
+                // The first parent is on a lane to the left: this lane joins it.
+                Some(p) if lanes.contains(p) => marks.push((2 * col - 1, '/')),
+                Some(p) => {
+                    marks.push((2 * lanes.len(), '|'));
+                    lanes.push(p.clone());
+                }
+                None => {}
+            }
+            // Further parents of a merge open lanes right of the commit unless a lane
+            // already expects them.
+            for p in parents.iter().skip(1) {
+                if lanes.contains(p) || self.lanes.contains(p) {
+                    continue;
+                }
+                marks.push(((2 * lanes.len()).saturating_sub(1), '\\'));
+                lanes.push(p.clone());
+            }
+        }
+
+        let unchanged = marks.iter().all(|&(_, c)| c == '|');
+        self.lanes = lanes;
+        let connector = (!unchanged).then(|| render_marks(&marks));
+        GraphRow { commit, connector }
+    }
+}
+
+/// Mark for an old lane `i` that continues as new lane `j`.
+fn shift_mark(i: usize, j: usize) -> (usize, char) {
+    match j.cmp(&i) {
+        std::cmp::Ordering::Equal => (2 * i, '|'),
+        std::cmp::Ordering::Less => (2 * i - 1, '/'),
+        std::cmp::Ordering::Greater => (2 * i + 1, '\\'),
+    }
+}
+
+fn render_marks(marks: &[(usize, char)]) -> String {
+    let width = marks.iter().map(|&(pos, _)| pos + 1).max().unwrap_or(0);
+    let mut line = vec![' '; width];
+    for &(pos, c) in marks {
+        line[pos] = c;
+    }
+    line.into_iter().collect::<String>().trim_end().to_string()
+}
@@ -0,0 +1,90 @@
+//! Which hidden (dot) paths the source scan stages.
+//!
+//! The scan walks dotfiles, so `.github/` workflows, `.cargo/config.toml` and other
+//! project files under a dot-named path are staged like any other file. Editor and OS
+//! droppings are not project files: a path with one of `DEFAULT_HIDDEN_EXCLUDES` as a
+//! component (`.idea/`, `.vscode/`, `.DS_Store`, ...) is skipped. `--include-hidden <glob>`
+//! (repeatable) or the `include_hidden` list of the `[scan]` table in `.mdcode.toml` stages
+//! matching paths again:
+//!
+//! ```toml
+//! [scan]
+//! include_hidden = [".vscode/settings.json", ".idea/runConfigurations"]
+//! ```
+//!
+//! A glob without `/` matches a name at any depth, one with `/` is anchored at the
+//! repository root; a match on a directory includes everything below it.
+
+use crate::{anchored_glob, config_for, Config};
+use globset::{GlobSet, GlobSetBuilder};
+use std::error::Error;
+use std::path::Path;
+
+/// Dot-named directories and files of editors, IDEs and operating systems that the scan
+/// skips unless `--include-hidden` matches them.
+pub const DEFAULT_HIDDEN_EXCLUDES: &[&str] = &[
+    // Editors and IDEs
+    ".idea",
+    ".vscode",
+    ".vs",
+    ".fleet",
+    ".history",
+    ".ipynb_checkpoints",
+    // Operating systems
+    ".DS_Store",
+    ".AppleDouble",
+    ".Spotlight-V100",
+    ".Trashes",
+    ".fseventsd",
+    ".directory",
+];
+
+/// The hidden-path policy of one repository.
+#[derive(Debug, Clone, Default)]
+pub struct HiddenPolicy {
+    include: GlobSet,
+}
+
+impl HiddenPolicy {
+    /// The default exclusions with `include` globs staged again. Fails on a glob that does
+    /// not compile.
+    pub fn new(include: &[String]) -> Result<Self, Box<dyn Error>> {
+        let mut set = GlobSetBuilder::new();
+        for glob in include {
+            let pattern = glob.trim().trim_end_matches('/');
+            set.add(
+                anchored_glob(pattern)
+                    .map_err(|e| format!("invalid --include-hidden glob '{}': {}", glob, e))?,
+            );
+        }
+        Ok(HiddenPolicy {
+            include: set.build()?,
+        })
+    }
+
+    /// The policy for `dir`: the `include` globs (`--include-hidden`) plus `include_hidden`
+    /// under `[scan]` in `.mdcode.toml` (or `config`, the `--config` file).
+    pub fn load(
+        dir: &str,
+        include: &[String],
+        config: Option<&Config>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut include = include.to_vec();
+        include.extend(config_for(dir, config)?.glob_list("scan", "include_hidden")?);
+        HiddenPolicy::new(&include)
+    }
+
+    /// True when the repo-relative `path` lies in, or is, an editor/OS path of
+    /// `DEFAULT_HIDDEN_EXCLUDES` and no include glob matches it or a parent directory.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let junk = path.components().any(|c| {
+            c.as_os_str()
+                .to_str()
+                .is_some_and(|name| DEFAULT_HIDDEN_EXCLUDES.contains(&name))
+        });
+        junk && !path
+            .ancestors()
+            .filter(|p| !p.as_os_str().is_empty())
+            .any(|p| self.include.is_match(p))
+    }
+}
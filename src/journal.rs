@@ -0,0 +1,222 @@
+//! Local journal of what mdcode did in a repository, shown by `mdcode history`.
+//!
+//! `new`, `update`, `squash`, `rewrite-author`, `tag`, `gh_push`, `gh_fetch` and `gh_sync`
+//! each append one JSON line to `.git/mdcode-journal.jsonl` from their own success and
+//! failure paths (`record`): the event, its arguments, the commit it created (and the tag,
+//! for `tag`) on success, or the error text on failure. An operation that created nothing,
+//! such as an `update` with no changes or a push, records no commit. Dry runs are not
+//! recorded. Writing the journal is best effort and never fails the operation.
+
+use crate::open_repo;
+use chrono::{TimeZone, Utc};
+use git2::Oid;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Journal file name inside the repository's `.git` directory.
+pub const JOURNAL_FILE: &str = "mdcode-journal.jsonl";
+
+/// One recorded mdcode operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// When the operation finished (Unix seconds).
+    pub time: i64,
+    /// `new`, `update`, `squash`, `tag`, `push`, `fetch` or `sync`.
+    pub event: String,
+    /// The command's options, as a JSON object.
+    pub args: Value,
+    /// Commit created by a successful operation.
+    pub commit: Option<String>,
+    /// Tag created by a successful `tag`.
+    pub tag: Option<String>,
+    /// Error text of a failed operation.
+    pub error: Option<String>,
+}
+
+impl JournalEntry {
+    pub fn ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "time": self.time,
+            "event": self.event,
+            "args": self.args,
+            "ok": self.ok(),
+            "commit": self.commit,
+            "tag": self.tag,
+            "error": self.error,
+        })
+    }
+
+    /// Parse one journal line; `None` for lines that are not journal entries.
+    pub fn from_json(value: &Value) -> Option<JournalEntry> {
+        let text = |key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        Some(JournalEntry {
+            time: value.get("time")?.as_i64()?,
+            event: text("event")?,
+            args: value.get("args").cloned().unwrap_or_else(|| json!({})),
+            commit: text("commit"),
+            tag: text("tag"),
+            error: text("error"),
+        })
+    }
+}
+
+/// Path of the journal for the repository at `dir`.
+pub fn journal_path(dir: &str) -> Result<PathBuf, Box<dyn Error>> {
+    Ok(open_repo(dir)?.path().join(JOURNAL_FILE))
+}
+
+/// Append `entry` to the journal of the repository at `dir`.
+pub fn append_journal(dir: &str, entry: &JournalEntry) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(journal_path(dir)?)?;
+    writeln!(file, "{}", entry.to_json())?;
+    Ok(())
+}
+
+/// Every journal entry in the order recorded (oldest first); empty when there is no
+/// journal yet. Unreadable lines are skipped.
+pub fn read_journal(dir: &str) -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+    let path = journal_path(dir)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|value| JournalEntry::from_json(&value))
+        .collect())
+}
+
+/// Drop all but the newest `keep` lines of the journal (`mdcode gc`) and return how many
+/// were dropped; with `dry_run` only count them. A missing journal drops nothing.
+pub fn trim_journal(dir: &str, keep: usize, dry_run: bool) -> Result<usize, Box<dyn Error>> {
+    let path = journal_path(dir)?;
+    if !path.exists() {
+        return Ok(0);
+    }
+    let text = fs::read_to_string(&path)?;
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let dropped = lines.len().saturating_sub(keep);
+    if dropped > 0 && !dry_run {
+        let mut kept = lines[dropped..].join("\n");
+        if !kept.is_empty() {
+            kept.push('\n');
+        }
+        fs::write(&path, kept)?;
+    }
+    Ok(dropped)
+}
+
+/// One `history` line: time, event, outcome, short commit/tag and the arguments.
+pub fn format_journal_entry(entry: &JournalEntry) -> String {
+    let when = Utc
+        .timestamp_opt(entry.time, 0)
+        .single()
+        .map(|dt| dt.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default();
+    let args: Vec<String> = entry
+        .args
+        .as_object()
+        .map(|m| {
+            m.iter()
+                .filter(|(_, v)| !matches!(v, Value::Null | Value::Bool(false)))
+                .filter(|(_, v)| !v.as_array().is_some_and(|a| a.is_empty()))
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect()
+        })
+        .unwrap_or_default();
+    let result = match &entry.error {
+        Some(e) => format!("FAILED {}", e.lines().next().unwrap_or("")),
+        None => {
+            let mut ids = Vec::new();
+            if let Some(c) = &entry.commit {
+                ids.push(c.chars().take(7).collect::<String>());
+            }
+            if let Some(t) = &entry.tag {
+                ids.push(t.clone());
+            }
+            format!("ok {}", ids.join(" ")).trim_end().to_string()
+        }
+    };
+    format!(
+        "{}  {:<6}  {}  [{}]",
+        when,
+        entry.event,
+        result,
+        args.join(" ")
+    )
+}
+
+/// `mdcode history`: print the newest `limit` entries (all by default), newest first, and
+/// return them in that order.
+pub fn history_command(
+    dir: &str,
+    limit: Option<usize>,
+) -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+    let mut entries = read_journal(dir)?;
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(usize::MAX));
+    #[cfg(not(coverage))]
+    {
+        if entries.is_empty() {
+            log::info!("No mdcode operations recorded yet.");
+        }
+        for entry in &entries {
+            log::info!("{}", format_journal_entry(entry));
+        }
+    }
+    Ok(entries)
+}
+
+/// What a journaled operation created.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Created {
+    pub commit: Option<Oid>,
+    pub tag: Option<String>,
+}
+
+impl Created {
+    pub fn commit(commit: Option<Oid>) -> Self {
+        Created { commit, tag: None }
+    }
+}
+
+/// Journal one run of `event` on the repository at `dir`, called from the operation's
+/// own success and failure paths; `created` names what a successful run made. Repositories
+/// that cannot be opened are skipped, and any error while writing is ignored.
+pub(crate) fn record<T>(
+    dir: &str,
+    event: &str,
+    args: Value,
+    result: &Result<T, Box<dyn Error>>,
+    created: impl FnOnce(&T) -> Created,
+) {
+    if open_repo(dir).is_err() {
+        return;
+    }
+    let (created, error) = match result {
+        Ok(value) => (created(value), None),
+        Err(e) => (Created::default(), Some(e.to_string())),
+    };
+    let entry = JournalEntry {
+        time: Utc::now().timestamp(),
+        event: event.to_string(),
+        args,
+        commit: created.commit.map(|id| id.to_string()),
+        tag: created.tag,
+        error,
+    };
+    if let Err(_e) = append_journal(dir, &entry) {
+        #[cfg(not(coverage))]
+        log::debug!("Could not write the mdcode journal: {}", _e);
+    }
+}
@@ -0,0 +1,230 @@
+//! Git LFS for large binary categories (`new --lfs`, `mdcode lfs enable`).
+//!
+//! Enabling LFS runs `git lfs install --local`, then writes `filter=lfs` entries to
+//! `.gitattributes` for the extensions of the Image, Audio and Font categories plus the globs
+//! under `[lfs] patterns` in `.mdcode.toml`. Files matching a `filter=lfs` pattern of
+//! `.gitattributes` are exempt from the size caps: LFS stores their content outside the
+//! repository, so there is no reason to skip them.
+
+use crate::{add_files_to_git, anchored_glob, config_for, open_repo, require_git, Config};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Git attributes file at the repository root.
+pub const GITATTRIBUTES_FILE: &str = ".gitattributes";
+
+/// `detect_file_type` categories tracked through LFS, with their extensions.
+pub const LFS_CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "Image",
+        &["jpg", "jpeg", "png", "bmp", "gif", "tiff", "webp"],
+    ),
+    (
+        "Audio",
+        &[
+            "wav", "mp3", "flac", "aac", "m4a", "ogg", "opus", "aiff", "aif", "wma", "mid", "midi",
+        ],
+    ),
+    ("Font", &["ttf", "otf", "woff", "woff2"]),
+];
+
+/// Attributes that route a pattern through LFS, as `git lfs track` writes them.
+const LFS_ATTRIBUTES: &str = "filter=lfs diff=lfs merge=lfs -text";
+
+/// The `filter=lfs` patterns of a `.gitattributes`, compiled for matching paths relative
+/// to the repository root.
+#[derive(Debug, Clone, Default)]
+pub struct LfsPatterns {
+    root: PathBuf,
+    patterns: Vec<String>,
+    set: GlobSet,
+}
+
+impl PartialEq for LfsPatterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root && self.patterns == other.patterns
+    }
+}
+
+impl Eq for LfsPatterns {}
+
+impl LfsPatterns {
+    /// Compile gitattributes-style `patterns` for the repository at `root` (see
+    /// `anchored_glob`). Fails on a pattern that does not compile.
+    pub fn new(root: &Path, patterns: &[String]) -> Result<Self, Box<dyn Error>> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(lfs_glob(pattern)?);
+        }
+        Ok(LfsPatterns {
+            root: root.to_path_buf(),
+            patterns: patterns.to_vec(),
+            set: builder.build()?,
+        })
+    }
+
+    /// The `filter=lfs` patterns of `<dir>/.gitattributes`; none when it does not exist.
+    pub fn load(dir: &str) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(Path::new(dir).join(GITATTRIBUTES_FILE)).unwrap_or_default();
+        let patterns = parse_lfs_patterns(&text);
+        if patterns.is_empty() {
+            return Ok(LfsPatterns::default());
+        }
+        LfsPatterns::new(Path::new(dir), &patterns)
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// True when `path` (relative to the root, or under it) matches an LFS pattern.
+    pub fn matches(&self, path: &Path) -> bool {
+        if self.patterns.is_empty() {
+            return false;
+        }
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        self.set.is_match(rel)
+    }
+}
+
+/// Patterns of `.gitattributes` lines that set `filter=lfs`.
+pub fn parse_lfs_patterns(gitattributes: &str) -> Vec<String> {
+    gitattributes
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| {
+            let mut fields = l.split_whitespace();
+            let pattern = fields.next()?;
+            fields
+                .any(|a| a == "filter=lfs")
+                .then(|| pattern.to_string())
+        })
+        .collect()
+}
+
+/// The `patterns` list of the `[lfs]` table in `.mdcode.toml` (or `config`, the `--config`
+/// file).
+pub fn configured_lfs_patterns(
+    dir: &str,
+    config: Option<&Config>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    config_for(dir, config)?.glob_list("lfs", "patterns")
+}
+
+/// `pattern` compiled with `anchored_glob`, or an error naming it.
+fn lfs_glob(pattern: &str) -> Result<Glob, Box<dyn Error>> {
+    anchored_glob(pattern).map_err(|e| format!("invalid LFS pattern '{}': {}", pattern, e).into())
+}
+
+/// Patterns LFS tracks for `dir`: `*.<ext>` for every extension in `LFS_CATEGORIES`, then
+/// the configured globs. Fails on a configured glob that does not compile.
+pub fn lfs_patterns_for(dir: &str, config: Option<&Config>) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut patterns: Vec<String> = LFS_CATEGORIES
+        .iter()
+        .flat_map(|(_, exts)| exts.iter().map(|e| format!("*.{}", e)))
+        .collect();
+    for pattern in configured_lfs_patterns(dir, config)? {
+        lfs_glob(&pattern)?;
+        if !patterns.contains(&pattern) {
+            patterns.push(pattern);
+        }
+    }
+    Ok(patterns)
+}
+
+/// `existing` `.gitattributes` content with an LFS line appended for each pattern not
+/// already routed through LFS.
+pub fn gitattributes_with_lfs(existing: &str, patterns: &[String]) -> String {
+    let tracked = parse_lfs_patterns(existing);
+    let mut out = existing.to_string();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    for pattern in patterns.iter().filter(|p| !tracked.contains(p)) {
+        out.push_str(&format!("{} {}\n", pattern, LFS_ATTRIBUTES));
+    }
+    out
+}
+
+/// True when `git lfs` runs.
+pub fn check_git_lfs_installed() -> bool {
+    Command::new("git")
+        .args(["lfs", "version"])
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Fail unless git and the git-lfs extension are installed.
+pub fn require_git_lfs(operation: &str) -> Result<(), Box<dyn Error>> {
+    require_git(operation, "Git LFS", None)?;
+    if check_git_lfs_installed() {
+        return Ok(());
+    }
+    Err(format!(
+        "{} requires Git LFS, but `git lfs` is not installed; install it from \
+https://git-lfs.com (e.g. `brew install git-lfs` or `apt install git-lfs`) and retry",
+        operation
+    )
+    .into())
+}
+
+/// Run `git lfs install --local` in the repository at `dir`, then write its LFS patterns
+/// into `.gitattributes` (keeping existing lines). Nothing is written when the install
+/// fails, so `.gitattributes` never names a filter git cannot run. Returns the
+/// `.gitattributes` path.
+pub fn enable_lfs(dir: &str, config: Option<&Config>) -> Result<PathBuf, Box<dyn Error>> {
+    let patterns = lfs_patterns_for(dir, config)?;
+    let output = Command::new("git")
+        .args(["-C", dir, "lfs", "install", "--local"])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "git lfs install --local failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    let path = Path::new(dir).join(GITATTRIBUTES_FILE);
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    fs::write(&path, gitattributes_with_lfs(&existing, &patterns))?;
+    Ok(path)
+}
+
+/// `mdcode lfs enable`: set up LFS in an existing repository and stage `.gitattributes`.
+/// Files committed before stay regular git blobs; the next `update` commits
+/// `.gitattributes` and stores new matching files through LFS.
+pub fn lfs_enable_command(
+    dir: &str,
+    config: Option<&Config>,
+    dry_run: bool,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    open_repo(dir)?;
+    let patterns = lfs_patterns_for(dir, config)?;
+    if dry_run {
+        #[cfg(not(coverage))]
+        log::info!(
+            "Dry run: would track {} patterns through Git LFS in {}",
+            patterns.len(),
+            GITATTRIBUTES_FILE
+        );
+        return Ok(patterns);
+    }
+    require_git_lfs("lfs enable")?;
+    let path = enable_lfs(dir, config)?;
+    add_files_to_git(dir, &[path], false)?;
+    #[cfg(not(coverage))]
+    log::info!(
+        "Git LFS enabled: {} patterns in {} (staged; run `mdcode update` to commit it)",
+        patterns.len(),
+        GITATTRIBUTES_FILE
+    );
+    Ok(patterns)
+}
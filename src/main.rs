@@ -1,26 +1,44 @@
 #[cfg(not(any(tarpaulin, coverage)))]
-use std::io::Write;
+use clap::Parser;
 #[cfg(not(any(tarpaulin, coverage)))]
-const BLUE: &str = "[94m";
+use mdcode::{colorize, Cli, BLUE};
 #[cfg(not(any(tarpaulin, coverage)))]
-const RESET: &str = "[0m";
+use std::io::Write;
 
 #[cfg(not(any(tarpaulin, coverage)))]
 fn main() {
-    env_logger::Builder::new()
+    let cli = Cli::parse();
+    if cli.no_color {
+        // Decided once, before the logger or any command prints.
+        std::env::set_var("NO_COLOR", "1");
+    }
+    let mut logger = env_logger::Builder::new();
+    logger
         .format(|buf, record| {
             if record.level() == log::Level::Error {
-                writeln!(buf, "{}Error:{} {}", BLUE, RESET, record.args())
+                writeln!(buf, "{} {}", colorize(BLUE, "Error:"), record.args())
             } else {
                 writeln!(buf, "{}", record.args())
             }
         })
-        .filter(None, log::LevelFilter::Info)
-        .init();
+        .filter(None, cli.log_level());
+    // RUST_LOG directives are applied last, so they win over -v/-vv/--quiet.
+    if let Ok(filters) = std::env::var("RUST_LOG") {
+        logger.parse_filters(&filters);
+    }
+    let pager = mdcode::start_pager_for(&cli);
+    if let Some(pager) = &pager {
+        logger.target(env_logger::Target::Pipe(Box::new(pager.writer())));
+    }
+    logger.init();
 
-    if let Err(e) = mdcode::run() {
-        eprintln!("{}Error:{} {}", BLUE, RESET, e);
-        std::process::exit(1);
+    let result = mdcode::execute_cli(cli);
+    if let Some(pager) = pager {
+        let _ = pager.finish();
+    }
+    if let Err(e) = result {
+        eprintln!("{} {}", colorize(BLUE, "Error:"), e);
+        std::process::exit(mdcode::exit_code_for(e.as_ref()));
     }
 }
 
@@ -0,0 +1,114 @@
+//! Repository metadata for release automation: `mdcode meta [--json]`.
+//!
+//! Every field except the repository name is optional: a repository without commits, tags,
+//! a `Cargo.toml` version or an `origin` remote reports `null` for what is missing rather
+//! than failing. Only a directory that is not a repository is an error.
+
+use crate::{
+    is_dirty, latest_semver_tag, open_repo, read_version_from_cargo_toml, GithubProvider,
+    GitlabProvider, RemoteProvider,
+};
+use std::error::Error;
+
+/// What `mdcode meta` reports about a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoMeta {
+    /// Directory name of the working tree.
+    pub name: String,
+    /// Branch HEAD points to (also before the first commit); `None` when detached.
+    pub branch: Option<String>,
+    /// Full SHA of the HEAD commit.
+    pub head: Option<String>,
+    /// Highest semver tag (`latest_semver_tag`).
+    pub latest_tag: Option<String>,
+    /// `[package].version` from `Cargo.toml`.
+    pub version: Option<String>,
+    /// Uncommitted changes in tracked files (`is_dirty`); `None` when it cannot be told.
+    pub dirty: Option<bool>,
+    /// `owner/name` of `origin` on GitHub (or the project path on GitLab).
+    pub origin: Option<String>,
+    /// `origin`'s URL as configured.
+    pub origin_url: Option<String>,
+}
+
+impl RepoMeta {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "branch": self.branch,
+            "head": self.head,
+            "latest_tag": self.latest_tag,
+            "version": self.version,
+            "dirty": self.dirty,
+            "origin": self.origin,
+            "origin_url": self.origin_url,
+        })
+    }
+}
+
+/// Gather `RepoMeta` for the repository at `dir`.
+pub fn repo_meta(dir: &str) -> Result<RepoMeta, Box<dyn Error>> {
+    let repo = open_repo(dir)?;
+    let root = repo.workdir().unwrap_or_else(|| repo.path());
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().trim_end_matches(".git").to_string())
+        .unwrap_or_else(|| dir.to_string());
+    let branch = repo
+        .find_reference("HEAD")
+        .ok()
+        .and_then(|h| h.symbolic_target().map(str::to_string))
+        .and_then(|t| t.strip_prefix("refs/heads/").map(str::to_string));
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|h| h.peel_to_commit().ok())
+        .map(|c| c.id().to_string());
+    let origin_url = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|r| r.url().map(str::to_string));
+    let origin = origin_url.as_deref().and_then(|url| {
+        GithubProvider::default()
+            .parse_slug(url)
+            .or_else(|| GitlabProvider.parse_slug(url))
+    });
+    Ok(RepoMeta {
+        name,
+        branch,
+        head,
+        latest_tag: latest_semver_tag(&repo).ok().flatten(),
+        version: read_version_from_cargo_toml(dir).ok().flatten(),
+        dirty: is_dirty(dir).ok(),
+        origin,
+        origin_url,
+    })
+}
+
+/// `mdcode meta`: print `repo_meta` as `key: value` lines, or as pretty JSON with `json`.
+pub fn meta_command(dir: &str, json: bool) -> Result<RepoMeta, Box<dyn Error>> {
+    let meta = repo_meta(dir)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&meta.to_json())?);
+        return Ok(meta);
+    }
+    #[cfg(not(coverage))]
+    {
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+        let dirty = match meta.dirty {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "(unknown)",
+        };
+        log::info!("name:       {}", meta.name);
+        log::info!("branch:     {}", show(&meta.branch));
+        log::info!("head:       {}", show(&meta.head));
+        log::info!("latest tag: {}", show(&meta.latest_tag));
+        log::info!("version:    {}", show(&meta.version));
+        log::info!("dirty:      {}", dirty);
+        log::info!("origin:     {}", show(&meta.origin));
+        log::info!("origin url: {}", show(&meta.origin_url));
+    }
+    Ok(meta)
+}
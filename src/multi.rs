@@ -0,0 +1,186 @@
+//! Workspace mode: `--recursive` runs `info`, `update`, `gh_push`, `gh_fetch` or `gh_sync`
+//! on each git repository directly under a parent directory.
+//!
+//! Repositories are processed in name order, each one's output after a `[name]` header
+//! line. A failing repository is reported with its `[name]` and skipped, and a summary
+//! table ends the run.
+
+use crate::{dispatch_command, Cli, Commands, Config, MdcodeError};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Result of running the command on one repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoOutcome {
+    /// Directory name of the repository under the parent.
+    pub name: String,
+    /// The command's error message, or `None` on success.
+    pub error: Option<String>,
+}
+
+/// Immediate subdirectories of `parent` that contain a `.git`, sorted by name.
+pub fn discover_child_repos(parent: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let entries =
+        fs::read_dir(parent).map_err(|e| format!("Cannot read directory '{}': {}", parent, e))?;
+    let mut repos: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir() && p.join(".git").exists())
+        .collect();
+    repos.sort();
+    Ok(repos)
+}
+
+/// Run `op` on each repository under `parent` (see `discover_child_repos`), continuing past
+/// failures. Errors only when `parent` cannot be read or holds no repositories.
+pub fn run_each<F>(parent: &str, mut op: F) -> Result<Vec<RepoOutcome>, Box<dyn Error>>
+where
+    F: FnMut(&str) -> Result<(), Box<dyn Error>>,
+{
+    let repos = discover_child_repos(parent)?;
+    if repos.is_empty() {
+        return Err(MdcodeError::NoRepository(PathBuf::from(parent)).into());
+    }
+    let mut outcomes = Vec::with_capacity(repos.len());
+    for repo in repos {
+        let name = repo_name(&repo);
+        #[cfg(not(coverage))]
+        log::info!("[{}] {}", name, repo.display());
+        let result = op(&repo.to_string_lossy());
+        let error = result.err().map(|e| e.to_string());
+        #[cfg(not(coverage))]
+        if let Some(e) = &error {
+            log::error!("[{}] {}", name, e);
+        }
+        outcomes.push(RepoOutcome { name, error });
+    }
+    Ok(outcomes)
+}
+
+/// Summary table printed at the end of a `--recursive` run: one row per repository with
+/// `ok` or `FAILED` and the first line of the error, then the totals.
+pub fn format_summary(outcomes: &[RepoOutcome]) -> String {
+    let width = outcomes.iter().map(|o| o.name.len()).max().unwrap_or(0);
+    let mut out = String::from("Summary:\n");
+    for o in outcomes {
+        match &o.error {
+            None => out.push_str(&format!("  {:<width$}  ok\n", o.name)),
+            Some(e) => out.push_str(&format!(
+                "  {:<width$}  FAILED  {}\n",
+                o.name,
+                e.lines().next().unwrap_or("")
+            )),
+        }
+    }
+    let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+    out.push_str(&format!(
+        "{} repositories: {} succeeded, {} failed",
+        outcomes.len(),
+        outcomes.len() - failed,
+        failed
+    ));
+    out
+}
+
+/// Run `cli` on every repository under `parent`, then print the summary. Fails when any
+/// repository failed.
+pub(crate) fn run_recursive(
+    cli: &Cli,
+    parent: &str,
+    config: Option<&Config>,
+) -> Result<(), Box<dyn Error>> {
+    let outcomes = run_each(parent, |dir| {
+        dispatch_command(
+            &Cli {
+                command: cli.command.for_repo(dir),
+                ..cli.clone()
+            },
+            config,
+        )
+    })?;
+    #[cfg(not(coverage))]
+    log::info!("{}", format_summary(&outcomes));
+    let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+    if failed > 0 {
+        return Err(format!("{} of {} repositories failed", failed, outcomes.len()).into());
+    }
+    Ok(())
+}
+
+fn repo_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+impl Commands {
+    /// Parent directory when this is a `--recursive` run of a command that supports it.
+    pub(crate) fn recursive_parent(&self) -> Option<String> {
+        match self {
+            Commands::Info {
+                directory,
+                recursive: true,
+                ..
+            }
+            | Commands::Update {
+                directory,
+                recursive: true,
+                ..
+            }
+            | Commands::GhPush {
+                directory,
+                recursive: true,
+                ..
+            }
+            | Commands::GhFetch {
+                directory,
+                recursive: true,
+                ..
+            }
+            | Commands::GhSync {
+                directory,
+                recursive: true,
+                ..
+            } => Some(directory.clone().unwrap_or_else(|| ".".to_string())),
+            _ => None,
+        }
+    }
+
+    /// This command aimed at the single repository `dir`.
+    fn for_repo(&self, dir: &str) -> Commands {
+        let mut command = self.clone();
+        match &mut command {
+            Commands::Info {
+                directory,
+                recursive,
+                ..
+            }
+            | Commands::Update {
+                directory,
+                recursive,
+                ..
+            }
+            | Commands::GhPush {
+                directory,
+                recursive,
+                ..
+            }
+            | Commands::GhFetch {
+                directory,
+                recursive,
+                ..
+            }
+            | Commands::GhSync {
+                directory,
+                recursive,
+                ..
+            } => {
+                *directory = Some(dir.to_string());
+                *recursive = false;
+            }
+            _ => {}
+        }
+        command
+    }
+}
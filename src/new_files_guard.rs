@@ -0,0 +1,167 @@
+//! Guard against enormous accidental commits in `mdcode update`.
+//!
+//! When a commit would add more new files than a threshold (say, a vendor dump untarred
+//! into the working tree), `update` stops and shows the top-level directories contributing
+//! most of them before asking for confirmation. Only added files count; modifications,
+//! deletions and renames never trigger it. The threshold comes from `--max-new-files`, else
+//! the `max_new_files` key of the `[update]` table in `.mdcode.toml`:
+//!
+//! ```toml
+//! [update]
+//! max_new_files = 2000   # 0 turns the guard off
+//! ```
+
+use crate::{config_for, ChangeKind, Config, FileChange};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Component;
+
+/// New-file threshold used when neither `--max-new-files` nor the config sets one.
+pub const DEFAULT_MAX_NEW_FILES: usize = 500;
+
+/// How many top-level directories the report lists.
+const REPORT_DIRS: usize = 5;
+
+/// A commit that adds more new files than allowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewFilesReport {
+    pub new_files: usize,
+    pub threshold: usize,
+    /// New files per top-level directory (`.` for files at the root), most first.
+    pub by_directory: Vec<(String, usize)>,
+}
+
+impl NewFilesReport {
+    /// Human-readable summary listing the largest contributors.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "This commit adds {} new files (limit {}). Largest contributors:\n",
+            self.new_files, self.threshold
+        );
+        for (dir, count) in self.by_directory.iter().take(REPORT_DIRS) {
+            out.push_str(&format!("  {:>6}  {}\n", count, dir));
+        }
+        let rest = self.by_directory.len().saturating_sub(REPORT_DIRS);
+        if rest > 0 {
+            out.push_str(&format!("  ... and {} more directories\n", rest));
+        }
+        out
+    }
+}
+
+/// The top-level directory of a repository-relative path, or `.` for a root file.
+fn top_level_dir(change: &FileChange) -> String {
+    let mut components = change.path.components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(first)), Some(_)) => first.to_string_lossy().into_owned(),
+        _ => ".".to_string(),
+    }
+}
+
+/// A report when `changes` add more than `threshold` new files; `None` otherwise, and
+/// always for a threshold of 0.
+pub fn check_new_files(changes: &[FileChange], threshold: usize) -> Option<NewFilesReport> {
+    let added: Vec<&FileChange> = changes
+        .iter()
+        .filter(|c| c.status == ChangeKind::Added)
+        .collect();
+    if threshold == 0 || added.len() <= threshold {
+        return None;
+    }
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for change in &added {
+        *counts.entry(top_level_dir(change)).or_default() += 1;
+    }
+    let mut by_directory: Vec<(String, usize)> = counts.into_iter().collect();
+    by_directory.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Some(NewFilesReport {
+        new_files: added.len(),
+        threshold,
+        by_directory,
+    })
+}
+
+/// The `max_new_files` key of the `[update]` table in `.mdcode.toml` (or `config`, the
+/// `--config` file), if set.
+pub fn configured_max_new_files(
+    dir: &str,
+    config: Option<&Config>,
+) -> Result<Option<usize>, Box<dyn Error>> {
+    let config = config_for(dir, config)?;
+    let Some(value) = config.get("update", "max_new_files") else {
+        return Ok(None);
+    };
+    value
+        .as_integer()
+        .and_then(|v| usize::try_from(v).ok())
+        .map(Some)
+        .ok_or_else(|| {
+            config.invalid("update.max_new_files must be a whole number (0 turns the guard off)")
+        })
+}
+
+/// The new-file threshold for `update` on `dir`: `--max-new-files` when given, else the
+/// config value, else `DEFAULT_MAX_NEW_FILES`.
+pub fn effective_max_new_files(
+    cli_max_new_files: Option<usize>,
+    dir: &str,
+    config: Option<&Config>,
+) -> Result<usize, Box<dyn Error>> {
+    Ok(match cli_max_new_files {
+        Some(n) => n,
+        None => configured_max_new_files(dir, config)?.unwrap_or(DEFAULT_MAX_NEW_FILES),
+    })
+}
+
+/// Print `report` on `output` and ask whether to commit anyway (`[y/N]`; an empty answer
+/// or end of input declines).
+pub fn confirm_new_files(
+    report: &NewFilesReport,
+    input: &mut impl std::io::BufRead,
+    output: &mut impl std::io::Write,
+) -> std::io::Result<bool> {
+    write!(output, "{}", report.render())?;
+    write!(output, "Commit all {} new files? [y/N] ", report.new_files)?;
+    output.flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Stop `update` when `changes` add more than `threshold` new files unless the user
+/// confirms on a terminal. `yes` (`--yes`) proceeds after printing the report; without a
+/// terminal the commit is refused.
+pub fn ensure_new_files_confirmed(
+    changes: &[FileChange],
+    threshold: usize,
+    yes: bool,
+) -> Result<(), Box<dyn Error>> {
+    use std::io::IsTerminal;
+    let Some(report) = check_new_files(changes, threshold) else {
+        return Ok(());
+    };
+    if yes {
+        print!("{}", report.render());
+        return Ok(());
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(format!(
+            "{}refusing to commit {} new files; pass --yes to commit them or raise --max-new-files",
+            report.render(),
+            report.new_files
+        )
+        .into());
+    }
+    if confirm_new_files(
+        &report,
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout(),
+    )? {
+        Ok(())
+    } else {
+        Err("aborted; nothing was committed (the new files stay staged)".into())
+    }
+}
@@ -0,0 +1,384 @@
+//! What `mdcode new` will do, computed before anything is written.
+//!
+//! `plan_new_repository` is the single source for both paths of `new`: `--dry-run` prints
+//! the plan and creates nothing on disk (not even the directory), while a real run creates
+//! the directory, writes the planned `.gitignore` and stages exactly the planned files.
+//!
+//! Adopting a directory that already holds work (or `new --adopt`) shows the same plan and
+//! asks before anything is created (`confirm_adoption`), since files left out of the
+//! initial commit would otherwise go unnoticed.
+
+use crate::{
+    detect_file_type, generate_gitignore_content, is_in_excluded_path,
+    list_ignored_files_with_options, open_repo, parse_author, resolve_signature_with_source,
+    scan_source_tree_with_options, scan_total_files_with_options, scan_walker, IgnoreReason,
+    ScanOptions, ScanResult, SignatureOptions, DEFAULT_MAX_FILE_MB, FALLBACK_SIGNATURE_SOURCE,
+    GITKEEP_FILE,
+};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Inputs of `plan_new_repository`.
+#[derive(Debug, Clone)]
+pub struct NewOptions {
+    /// Default size cap in MB (0 for no limit); `.mdcode.toml` categories still apply.
+    pub max_file_mb: u64,
+    /// Commit author as `Name <email>`; takes precedence over env vars and git config.
+    pub author: Option<String>,
+    /// What to do when no author identity is configured.
+    pub identity: SignatureOptions,
+    /// Commit empty directories by writing a `.gitkeep` into each (`--keep-empty-dirs`).
+    pub keep_empty_dirs: bool,
+    /// Track the binary categories through Git LFS (`--lfs`, see `enable_lfs`).
+    pub lfs: bool,
+    /// Create the repository in a non-empty directory without asking (`--yes`).
+    pub yes: bool,
+    /// How the scan for files to commit walks the tree (`--max-depth`).
+    pub scan: ScanOptions,
+}
+
+impl Default for NewOptions {
+    fn default() -> Self {
+        NewOptions {
+            max_file_mb: DEFAULT_MAX_FILE_MB,
+            author: None,
+            identity: SignatureOptions::default(),
+            keep_empty_dirs: true,
+            lfs: false,
+            yes: false,
+            scan: ScanOptions::default(),
+        }
+    }
+}
+
+/// The plan for `mdcode new` in one directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewPlan {
+    /// Directory the repository is created in.
+    pub directory: PathBuf,
+    /// The directory does not exist yet and would be created.
+    pub create_directory: bool,
+    /// The directory already has entries: `new` adopts existing work and asks first.
+    pub has_content: bool,
+    /// Content `.gitignore` would be written with.
+    pub gitignore: String,
+    /// Files that would be staged in the initial commit, as found by the scan.
+    pub files: Vec<PathBuf>,
+    /// Empty directories that would get a `.gitkeep` (only with `keep_empty_dirs`).
+    pub empty_dirs: Vec<PathBuf>,
+    /// Repo-relative paths of recognized files left out for exceeding their size cap.
+    pub skipped_by_size: Vec<String>,
+    /// Repo-relative paths of other files the scan sees but would not stage because their
+    /// type is not recognized.
+    pub unrecognized: Vec<String>,
+    /// Repo-relative directories below the root that have their own `.git`; their files are
+    /// never staged, so they are flagged before adopting.
+    pub nested_repositories: Vec<String>,
+    /// Files in the tree, recognized or not (the denominator of the final summary).
+    pub total_files: usize,
+    /// Commit author as `Name <email>`; `None` when no identity is configured and the
+    /// fallback is not allowed (a real run then prompts or refuses).
+    pub author: Option<String>,
+    /// Where `author` comes from, as in the "Using Git author" line.
+    pub author_source: String,
+}
+
+impl NewPlan {
+    /// Repo-relative path of a planned file, with `/` separators.
+    fn relative(&self, path: &Path) -> String {
+        relative_to(&self.directory, path)
+    }
+
+    /// Planned files grouped by `detect_file_type` label, each group sorted.
+    pub fn files_by_category(&self) -> BTreeMap<&'static str, Vec<String>> {
+        let mut groups: BTreeMap<&'static str, Vec<String>> = BTreeMap::new();
+        for f in &self.files {
+            groups
+                .entry(detect_file_type(f).unwrap_or("Unknown"))
+                .or_default()
+                .push(self.relative(f));
+        }
+        for paths in groups.values_mut() {
+            paths.sort();
+        }
+        groups
+    }
+
+    /// The plan as printed by `new --dry-run`.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let dir = self.directory.display();
+        if self.create_directory {
+            out.push_str(&format!("Would create directory: {}\n", dir));
+        } else {
+            out.push_str(&format!("Directory: {} (exists)\n", dir));
+        }
+        out.push_str("Would write .gitignore:\n");
+        for line in self.gitignore.lines() {
+            out.push_str(&format!("    {}\n", line));
+        }
+        out.push_str(&format!(
+            "Would stage {} of {} files:\n",
+            self.files.len(),
+            self.total_files
+        ));
+        for (label, paths) in self.files_by_category() {
+            out.push_str(&format!(
+                "  {} ({}): {}\n",
+                label,
+                paths.len(),
+                paths.join(", ")
+            ));
+        }
+        if !self.empty_dirs.is_empty() {
+            let dirs: Vec<String> = self.empty_dirs.iter().map(|d| self.relative(d)).collect();
+            out.push_str(&format!(
+                "Would add {} to {} empty directories: {}\n",
+                GITKEEP_FILE,
+                dirs.len(),
+                dirs.join(", ")
+            ));
+        }
+        if !self.skipped_by_size.is_empty() {
+            out.push_str(&format!(
+                "Skipped by size cap: {}\n",
+                self.skipped_by_size.join(", ")
+            ));
+        }
+        if !self.unrecognized.is_empty() {
+            out.push_str(&format!(
+                "Not staged (unrecognized type): {}\n",
+                self.unrecognized.join(", ")
+            ));
+        }
+        if !self.nested_repositories.is_empty() {
+            out.push_str(&format!(
+                "Warning: nested repositories, not staged: {}\n",
+                self.nested_repositories.join(", ")
+            ));
+        }
+        match &self.author {
+            Some(a) => out.push_str(&format!("Author: {} (source: {})\n", a, self.author_source)),
+            None => out.push_str(&format!("Author: none ({})\n", self.author_source)),
+        }
+        out
+    }
+}
+
+/// Work out what `new` would do in `dir` without touching the filesystem.
+pub fn plan_new_repository(dir: &str, opts: &NewOptions) -> Result<NewPlan, Box<dyn Error>> {
+    let directory = PathBuf::from(dir);
+    let exists = directory.exists();
+    let ScanResult {
+        files, empty_dirs, ..
+    } = scan_source_tree_with_options(dir, opts.max_file_mb, &opts.scan)?;
+    let empty_dirs = if opts.keep_empty_dirs {
+        empty_dirs
+    } else {
+        Vec::new()
+    };
+    let total_files = scan_total_files_with_options(dir, &opts.scan)?;
+    let skipped_by_size = if directory.is_dir() {
+        list_ignored_files_with_options(dir, opts.max_file_mb, &opts.scan)?
+            .into_iter()
+            .filter(|f| f.reason == IgnoreReason::SizeCap)
+            .map(|f| f.path)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let (author, author_source) = planned_author(dir, opts)?;
+    let has_content = fs::read_dir(&directory).is_ok_and(|mut d| d.next().is_some());
+    let (unrecognized, nested_repositories) = if directory.is_dir() {
+        let planned: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+        let unrecognized = unrecognized_files(&directory, &planned, &skipped_by_size, &opts.scan);
+        (unrecognized, nested_repositories(&directory))
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    Ok(NewPlan {
+        gitignore: generate_gitignore_content(dir)?,
+        directory,
+        create_directory: !exists,
+        has_content,
+        files,
+        empty_dirs,
+        skipped_by_size,
+        unrecognized,
+        nested_repositories,
+        total_files,
+        author,
+        author_source,
+    })
+}
+
+/// `path` relative to `root`, with `/` separators.
+fn relative_to(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Files the scan walker visits that are neither planned nor skipped by size, sorted.
+fn unrecognized_files(
+    root: &Path,
+    planned: &[&Path],
+    skipped_by_size: &[String],
+    scan: &ScanOptions,
+) -> Vec<String> {
+    let mut out: Vec<String> = scan_walker(&root.to_string_lossy(), scan)
+        .flatten()
+        .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
+        .filter(|e| !planned.contains(&e.path()))
+        .map(|e| relative_to(root, e.path()))
+        .filter(|rel| {
+            !is_in_excluded_path(Path::new(rel))
+                && rel != ".gitignore"
+                && !skipped_by_size.contains(rel)
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+/// Directories below `root` containing a `.git` entry, sorted; excluded directories such
+/// as `target/` or `node_modules/` are not searched.
+fn nested_repositories(root: &Path) -> Vec<String> {
+    let mut out: Vec<String> = ignore::WalkBuilder::new(root)
+        .standard_filters(false)
+        .filter_entry(|e| e.file_name() != ".git")
+        .build()
+        .flatten()
+        .filter(|e| e.depth() > 0 && e.file_type().is_some_and(|ft| ft.is_dir()))
+        .filter(|e| e.path().join(".git").exists())
+        .map(|e| relative_to(root, e.path()))
+        .filter(|rel| !is_in_excluded_path(Path::new(rel)))
+        .collect();
+    out.sort();
+    out
+}
+
+/// Print `plan` on `output` and ask whether to create the repository (`[y/N]`; an empty
+/// answer or end of input declines).
+pub fn confirm_adoption(
+    plan: &NewPlan,
+    input: &mut impl std::io::BufRead,
+    output: &mut impl std::io::Write,
+) -> std::io::Result<bool> {
+    write!(output, "{}", plan.render())?;
+    write!(
+        output,
+        "Create the repository and commit these {} files? [y/N] ",
+        plan.files.len()
+    )?;
+    output.flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Before `new` adopts a non-empty directory (or any directory with `adopt`): show the plan
+/// and ask on a terminal. `--yes` proceeds after printing the plan; without a terminal the
+/// adoption is refused. Directories that already have a repository with commits are left
+/// for `new` to reject.
+pub fn ensure_adoption_confirmed(
+    dir: &str,
+    opts: &NewOptions,
+    adopt: bool,
+) -> Result<(), Box<dyn Error>> {
+    use std::io::IsTerminal;
+    if open_repo(dir).is_ok_and(|r| r.head().is_ok()) {
+        return Ok(());
+    }
+    let plan = plan_new_repository(dir, opts)?;
+    if !adopt && !plan.has_content {
+        return Ok(());
+    }
+    if opts.yes {
+        print!("{}", plan.render());
+        return Ok(());
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(format!(
+            "'{}' already contains files; review the plan with --dry-run, then pass --yes to create the repository",
+            dir
+        )
+        .into());
+    }
+    if confirm_adoption(&plan, &mut std::io::stdin().lock(), &mut std::io::stdout())? {
+        Ok(())
+    } else {
+        Err("aborted; nothing was created".into())
+    }
+}
+
+/// The identity the initial commit would use, resolved like `resolve_commit_signature`
+/// but without prompting. Without a repository to read config from yet, env vars and the
+/// global git config are consulted.
+fn planned_author(
+    dir: &str,
+    opts: &NewOptions,
+) -> Result<(Option<String>, String), Box<dyn Error>> {
+    if let Some(a) = &opts.author {
+        let (name, email) = parse_author(a)?;
+        return Ok((Some(format!("{} <{}>", name, email)), "--author".into()));
+    }
+    let quiet = SignatureOptions {
+        allow_fallback_identity: opts.identity.allow_fallback_identity,
+        interactive: false,
+        sign: false,
+    };
+    if let Ok(repo) = open_repo(dir) {
+        return Ok(match resolve_signature_with_source(&repo, &quiet) {
+            Ok((sig, src)) => (
+                Some(format!(
+                    "{} <{}>",
+                    sig.name().unwrap_or("(unknown)"),
+                    sig.email().unwrap_or("(unknown)")
+                )),
+                src,
+            ),
+            Err(_) => (None, missing_identity_note(opts)),
+        });
+    }
+    for (name_var, email_var) in [
+        ("GIT_AUTHOR_NAME", "GIT_AUTHOR_EMAIL"),
+        ("GIT_COMMITTER_NAME", "GIT_COMMITTER_EMAIL"),
+    ] {
+        if let (Ok(name), Ok(email)) = (std::env::var(name_var), std::env::var(email_var)) {
+            return Ok((
+                Some(format!("{} <{}>", name, email)),
+                format!("env:{}/{}", name_var, email_var),
+            ));
+        }
+    }
+    if let Ok(cfg) = git2::Config::open_default() {
+        if let (Ok(name), Ok(email)) = (cfg.get_string("user.name"), cfg.get_string("user.email")) {
+            return Ok((
+                Some(format!("{} <{}>", name, email)),
+                "git config (global)".into(),
+            ));
+        }
+    }
+    if quiet.allow_fallback_identity {
+        return Ok((
+            Some("mdcode <mdcode@example.com>".into()),
+            FALLBACK_SIGNATURE_SOURCE.into(),
+        ));
+    }
+    Ok((None, missing_identity_note(opts)))
+}
+
+fn missing_identity_note(opts: &NewOptions) -> String {
+    if opts.identity.interactive {
+        "no identity configured; would prompt".into()
+    } else {
+        "no identity configured; pass --author or --allow-default-author".into()
+    }
+}
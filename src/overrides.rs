@@ -0,0 +1,86 @@
+//! Per-repository overrides of `.gitignore` for mdcode's file scans.
+//!
+//! `.mdcodeinclude` at the repository root lists glob patterns (gitignore syntax) for files
+//! mdcode should pick up even though `.gitignore` excludes them; `.mdcodeexclude` lists files
+//! mdcode should skip even though git would track them. Precedence: exclude > include >
+//! gitignore. Files under mdcode's built-in excluded directories (`target/`, `.git/`, ...)
+//! stay excluded.
+
+use crate::is_in_excluded_path;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// File name of the force-include pattern list.
+pub const MDCODE_INCLUDE_FILE: &str = ".mdcodeinclude";
+/// File name of the force-exclude pattern list.
+pub const MDCODE_EXCLUDE_FILE: &str = ".mdcodeexclude";
+
+/// Parsed `.mdcodeinclude`/`.mdcodeexclude` of one directory; either may be absent.
+pub struct FileOverrides {
+    root: PathBuf,
+    include: Option<Gitignore>,
+    exclude: Option<Gitignore>,
+}
+
+/// Parse `dir/name` with gitignore syntax. For the include list a pattern "ignoring" a
+/// path means it is force-included.
+fn load_patterns(dir: &Path, name: &str) -> Option<Gitignore> {
+    let path = dir.join(name);
+    if !path.is_file() {
+        return None;
+    }
+    let mut b = GitignoreBuilder::new(dir);
+    if let Some(e) = b.add(&path) {
+        #[cfg(not(coverage))]
+        log::warn!("Problem reading '{}': {}", path.display(), e);
+        #[cfg(coverage)]
+        let _ = e;
+    }
+    b.build().ok().filter(|g| !g.is_empty())
+}
+
+impl FileOverrides {
+    /// Load the override files at the root of `dir`.
+    pub fn load(dir: &str) -> Self {
+        let root = Path::new(dir);
+        FileOverrides {
+            root: root.to_path_buf(),
+            include: load_patterns(root, MDCODE_INCLUDE_FILE),
+            exclude: load_patterns(root, MDCODE_EXCLUDE_FILE),
+        }
+    }
+
+    /// True when `path` (under the root) matches `.mdcodeexclude`.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|g| g.matched_path_or_any_parents(path, is_dir).is_ignore())
+    }
+
+    /// True when `path` (under the root) matches `.mdcodeinclude` and not `.mdcodeexclude`.
+    pub fn is_included(&self, path: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_some_and(|g| g.matched_path_or_any_parents(path, false).is_ignore())
+            && !self.is_excluded(path, false)
+    }
+
+    /// Every file under the root that `.mdcodeinclude` forces in, found by a walk that
+    /// ignores all ignore files and descends at most `max_depth` levels. Empty without an
+    /// include list.
+    pub fn force_included_files(&self, max_depth: Option<usize>) -> Vec<PathBuf> {
+        if self.include.is_none() {
+            return Vec::new();
+        }
+        WalkBuilder::new(&self.root)
+            .standard_filters(false)
+            .max_depth(max_depth)
+            .build()
+            .filter_map(|r| r.ok())
+            .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
+            .map(|e| e.into_path())
+            .filter(|p| !is_in_excluded_path(p) && self.is_included(p))
+            .collect()
+    }
+}
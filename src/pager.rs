@@ -0,0 +1,112 @@
+//! Paging long output through the user's pager (`info --paginate`).
+//!
+//! `info` prints through the logger, so `main` points the logger at the pager's stdin for
+//! the run and waits for the pager to exit afterwards. Paging only happens when stdout is a
+//! terminal; piped or redirected output is written as usual.
+
+use crate::Cli;
+use std::io::{self, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// Pager used when `$PAGER` is not set.
+pub const DEFAULT_PAGER: &str = "less";
+
+/// The pager command line: `$PAGER`, else `DEFAULT_PAGER`. `None` when `$PAGER` is empty
+/// or `cat`, which turn paging off.
+pub fn pager_command() -> Option<String> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let pager = pager.trim();
+    (!pager.is_empty() && pager != "cat").then(|| pager.to_string())
+}
+
+/// True when paging was asked for and stdout is a terminal.
+pub fn should_paginate(requested: bool, stdout_is_terminal: bool) -> bool {
+    requested && stdout_is_terminal
+}
+
+/// A running pager reading what is written to its `PagerWriter`s.
+pub struct Pager {
+    child: Child,
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+}
+
+/// Writes into a pager's stdin. Once the user quits the pager, output is dropped instead
+/// of failing.
+#[derive(Clone)]
+pub struct PagerWriter(Arc<Mutex<Option<ChildStdin>>>);
+
+impl Write for PagerWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut stdin = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pipe) = stdin.as_mut() {
+            if let Err(e) = pipe.write_all(buf) {
+                if e.kind() != io::ErrorKind::BrokenPipe {
+                    return Err(e);
+                }
+                *stdin = None;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut stdin = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match stdin.as_mut().map(|pipe| pipe.flush()) {
+            Some(Err(e)) if e.kind() == io::ErrorKind::BrokenPipe => {
+                *stdin = None;
+                Ok(())
+            }
+            Some(result) => result,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Pager {
+    /// Start `command` (program and arguments separated by spaces) with a piped stdin.
+    /// `LESS` defaults to `FRX` as with git: quit when the output fits on one screen and
+    /// pass colors through.
+    pub fn start(command: &str) -> io::Result<Pager> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty pager command"))?;
+        let mut cmd = Command::new(program);
+        cmd.args(words).stdin(Stdio::piped());
+        if std::env::var_os("LESS").is_none() {
+            cmd.env("LESS", "FRX");
+        }
+        let mut child = cmd.spawn()?;
+        let stdin = Arc::new(Mutex::new(child.stdin.take()));
+        Ok(Pager { child, stdin })
+    }
+
+    /// A writer feeding the pager (for the logger).
+    pub fn writer(&self) -> PagerWriter {
+        PagerWriter(Arc::clone(&self.stdin))
+    }
+
+    /// Close the pager's input and wait until the user quits it.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.stdin.lock().unwrap_or_else(|e| e.into_inner()).take();
+        self.child.wait().map(|_| ())
+    }
+}
+
+/// Start the pager for `cli` when it asks for paging and stdout is a terminal. A pager
+/// that cannot be started is reported and the output goes to the terminal as usual.
+pub fn start_pager_for(cli: &Cli) -> Option<Pager> {
+    use std::io::IsTerminal;
+    if !should_paginate(cli.paginate(), io::stdout().is_terminal()) {
+        return None;
+    }
+    let command = pager_command()?;
+    match Pager::start(&command) {
+        Ok(pager) => Some(pager),
+        Err(e) => {
+            eprintln!("cannot start pager '{}': {}", command, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! HEAD reflog listing for `mdcode reflog`, to find commits lost by a reset or amend.
+//!
+//! `reflog_entries` reads `logs/HEAD` through libgit2's `Reflog` API, newest first, and
+//! flags the commits no local branch or tag reaches any more; those are the ones to
+//! recover (e.g. `git branch rescue <id>`).
+
+use crate::open_repo_with_commits;
+use git2::{Oid, Repository};
+use std::error::Error;
+
+/// One HEAD reflog entry; `index` is the `N` of `HEAD@{N}` (0 is the newest).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflogEntry {
+    pub index: usize,
+    /// HEAD before the update (all zeros when HEAD was unborn).
+    pub old_id: Oid,
+    /// HEAD after the update.
+    pub new_id: Oid,
+    /// Reflog message, e.g. `commit: Add parser` or `reset: moving to HEAD~1`.
+    pub message: String,
+    /// Time of the update (Unix seconds).
+    pub time: i64,
+    /// `new_id` is no longer reachable from any local branch or tag.
+    pub unreachable: bool,
+}
+
+/// Tips of all local branches and tags, peeled to commits.
+fn ref_tips(repo: &Repository) -> Result<Vec<Oid>, Box<dyn Error>> {
+    let mut tips = Vec::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        if !(reference.is_branch() || reference.is_tag()) {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            tips.push(commit.id());
+        }
+    }
+    Ok(tips)
+}
+
+/// HEAD reflog entries of the repository at `dir`, newest first, at most `limit` of them.
+pub fn reflog_entries(dir: &str, limit: Option<usize>) -> Result<Vec<ReflogEntry>, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    let tips = ref_tips(&repo)?;
+    let reflog = repo.reflog("HEAD")?;
+    let mut entries = Vec::new();
+    for (index, entry) in reflog.iter().enumerate().take(limit.unwrap_or(usize::MAX)) {
+        let new_id = entry.id_new();
+        let unreachable = repo.find_commit(new_id).is_ok()
+            && !tips.iter().any(|&tip| {
+                tip == new_id || repo.graph_descendant_of(tip, new_id).unwrap_or(false)
+            });
+        entries.push(ReflogEntry {
+            index,
+            old_id: entry.id_old(),
+            new_id,
+            message: entry.message().unwrap_or("").trim_end().to_string(),
+            time: entry.committer().when().seconds(),
+            unreachable,
+        });
+    }
+    Ok(entries)
+}
+
+/// Print the HEAD reflog of `dir` (`mdcode reflog`), marking unreachable commits.
+pub fn reflog_command(dir: &str, limit: Option<usize>) -> Result<Vec<ReflogEntry>, Box<dyn Error>> {
+    let entries = reflog_entries(dir, limit)?;
+    #[cfg(not(coverage))]
+    {
+        use crate::{colorize, RED, YELLOW};
+        use chrono::{TimeZone, Utc};
+        if entries.is_empty() {
+            log::info!("The HEAD reflog is empty.");
+        }
+        for e in &entries {
+            let when = Utc
+                .timestamp_opt(e.time, 0)
+                .single()
+                .map(|dt| dt.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            log::info!(
+                "{} {} -> {} | {} | {}{}",
+                colorize(YELLOW, format!("HEAD@{{{}}}", e.index)),
+                &e.old_id.to_string()[..7],
+                &e.new_id.to_string()[..7],
+                when,
+                e.message,
+                if e.unreachable {
+                    colorize(RED, " (unreachable)")
+                } else {
+                    String::new()
+                }
+            );
+        }
+        if let Some(lost) = entries.iter().find(|e| e.unreachable) {
+            log::info!(
+                "Recover an unreachable commit with e.g. `git branch rescue {}`",
+                &lost.new_id.to_string()[..7]
+            );
+        }
+    }
+    Ok(entries)
+}
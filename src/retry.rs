@@ -0,0 +1,225 @@
+//! Retry with exponential backoff for GitHub API calls.
+//!
+//! The decision logic (`retry_decision`) is a pure function over response metadata so it
+//! can be tested without a network; `with_retries` drives an async operation with it.
+
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+/// Total attempts (first try included) before giving up.
+pub const MAX_ATTEMPTS: u32 = 5;
+/// Backoff before the second attempt; doubled for each further attempt.
+const BASE_DELAY: Duration = Duration::from_secs(1);
+/// Upper bound for computed backoff.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Give up rather than wait longer than this for a rate-limit reset.
+pub const MAX_WAIT: Duration = Duration::from_secs(300);
+
+/// The parts of an HTTP response that matter for retrying.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseMeta {
+    /// HTTP status, or 0 when the request failed before a response arrived.
+    pub status: u16,
+    /// Raw `Retry-After` header (seconds).
+    pub retry_after: Option<String>,
+    /// `X-RateLimit-Remaining` header.
+    pub rate_limit_remaining: Option<u64>,
+    /// `X-RateLimit-Reset` header (Unix seconds).
+    pub rate_limit_reset: Option<i64>,
+}
+
+impl ResponseMeta {
+    fn is_rate_limited(&self) -> bool {
+        self.status == 429
+            || (self.status == 403
+                && (self.retry_after.is_some() || self.rate_limit_remaining == Some(0)))
+    }
+}
+
+/// A failed API call: response metadata plus a human-readable message.
+#[derive(Debug, Clone)]
+pub struct ApiFailure {
+    pub meta: ResponseMeta,
+    pub message: String,
+}
+
+impl fmt::Display for ApiFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.meta.status == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "HTTP {}: {}", self.meta.status, self.message)
+        }
+    }
+}
+
+impl Error for ApiFailure {}
+
+/// What to do after a failed attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetryDecision {
+    RetryAfter(Duration),
+    GiveUp,
+}
+
+/// Exponential backoff for the given (1-based) attempt that just failed.
+pub fn backoff_delay(attempt: u32) -> Duration {
+    let factor = 1u32 << attempt.saturating_sub(1).min(16);
+    (BASE_DELAY * factor).min(MAX_BACKOFF)
+}
+
+/// Decide whether to retry after `attempt` (1-based) failed with `meta`. `now` is the
+/// current Unix time, used with `X-RateLimit-Reset`.
+///
+/// Rate limits (429, or 403 carrying `Retry-After`/exhausted rate-limit headers) wait as
+/// the server asks; 5xx and network errors back off exponentially. Authentication errors
+/// and other 4xx responses are never retried.
+pub fn retry_decision(meta: &ResponseMeta, attempt: u32, now: i64) -> RetryDecision {
+    if attempt >= MAX_ATTEMPTS {
+        return RetryDecision::GiveUp;
+    }
+    let retry_after = meta
+        .retry_after
+        .as_deref()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let delay = if meta.is_rate_limited() {
+        match (retry_after, meta.rate_limit_reset) {
+            (Some(d), _) => d,
+            (None, Some(reset)) => Duration::from_secs((reset - now).max(1) as u64),
+            (None, None) => backoff_delay(attempt),
+        }
+    } else if meta.status == 0 || meta.status >= 500 {
+        retry_after.unwrap_or_else(|| backoff_delay(attempt))
+    } else {
+        return RetryDecision::GiveUp;
+    };
+    if delay > MAX_WAIT {
+        RetryDecision::GiveUp
+    } else {
+        RetryDecision::RetryAfter(delay)
+    }
+}
+
+/// `retry_decision` for a request that must not be sent twice, such as the POST creating a
+/// repository: only rate limits are retried, since the server refused those without acting.
+/// A 5xx or a dropped connection may come after the repository was created, so it is final.
+pub fn rate_limit_decision(meta: &ResponseMeta, attempt: u32, now: i64) -> RetryDecision {
+    if meta.is_rate_limited() {
+        retry_decision(meta, attempt, now)
+    } else {
+        RetryDecision::GiveUp
+    }
+}
+
+/// Run `op` until it succeeds, retrying per `retry_decision` (at most `MAX_ATTEMPTS`
+/// attempts). Each retry is logged as a warning; `what` names the operation.
+pub async fn with_retries<T, F, Fut>(what: &str, op: F) -> Result<T, Box<dyn Error>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ApiFailure>>,
+{
+    retry_with(what, retry_decision, op).await
+}
+
+/// `with_retries` for non-idempotent requests: retries per `rate_limit_decision`.
+pub async fn with_rate_limit_retries<T, F, Fut>(what: &str, op: F) -> Result<T, Box<dyn Error>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ApiFailure>>,
+{
+    retry_with(what, rate_limit_decision, op).await
+}
+
+async fn retry_with<T, F, Fut>(
+    what: &str,
+    decide: fn(&ResponseMeta, u32, i64) -> RetryDecision,
+    mut op: F,
+) -> Result<T, Box<dyn Error>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ApiFailure>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(v) => return Ok(v),
+            Err(failure) => {
+                let now = chrono::Utc::now().timestamp();
+                match decide(&failure.meta, attempt, now) {
+                    RetryDecision::RetryAfter(delay) => {
+                        log::warn!(
+                            "{} failed ({}); retrying in {}s (attempt {}/{})",
+                            what,
+                            failure,
+                            delay.as_secs(),
+                            attempt + 1,
+                            MAX_ATTEMPTS
+                        );
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    RetryDecision::GiveUp => {
+                        return Err(format!(
+                            "{} failed after {} attempt(s): {}",
+                            what, attempt, failure
+                        )
+                        .into())
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Turn the outcome of an octocrab `_get`/`_post` call into the JSON body or an
+/// `ApiFailure` carrying status and retry headers.
+#[cfg(not(feature = "offline_gh"))]
+pub(crate) async fn json_or_failure(
+    sent: octocrab::Result<reqwest::Response>,
+) -> Result<serde_json::Value, ApiFailure> {
+    json_with_scopes(sent).await.map(|(value, _)| value)
+}
+
+/// Like `json_or_failure`, also returning the raw `X-OAuth-Scopes` header (absent for
+/// fine-grained tokens).
+#[cfg(not(feature = "offline_gh"))]
+pub(crate) async fn json_with_scopes(
+    sent: octocrab::Result<reqwest::Response>,
+) -> Result<(serde_json::Value, Option<String>), ApiFailure> {
+    let resp = sent.map_err(|e| ApiFailure {
+        meta: ResponseMeta::default(),
+        message: e.to_string(),
+    })?;
+    let header = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    let meta = ResponseMeta {
+        status: resp.status().as_u16(),
+        retry_after: header("retry-after"),
+        rate_limit_remaining: header("x-ratelimit-remaining").and_then(|v| v.parse().ok()),
+        rate_limit_reset: header("x-ratelimit-reset").and_then(|v| v.parse().ok()),
+    };
+    let scopes = header("x-oauth-scopes");
+    let success = resp.status().is_success();
+    let body = resp.text().await.map_err(|e| ApiFailure {
+        meta: meta.clone(),
+        message: e.to_string(),
+    })?;
+    let value: serde_json::Value = serde_json::from_str(&body).unwrap_or(serde_json::Value::Null);
+    if success {
+        Ok((value, scopes))
+    } else {
+        let message = value
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("request failed")
+            .to_string();
+        Err(ApiFailure { meta, message })
+    }
+}
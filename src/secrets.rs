@@ -0,0 +1,260 @@
+//! Credential scanning before `update` commits.
+//!
+//! `find_secrets` is a pure, line-based detector for high-confidence patterns: AWS access
+//! key IDs, GitHub tokens, PEM private keys, and `password=`-style assignments whose value
+//! looks random. A line containing `mdcode:allow-secret` (e.g. as a trailing comment) is
+//! never reported. `check_staged_secrets` runs it over the blobs staged for the next commit.
+
+use git2::{Delta, Repository};
+use std::error::Error;
+use std::fmt;
+
+/// Marker that suppresses findings on the line that contains it.
+pub const ALLOW_SECRET_MARKER: &str = "mdcode:allow-secret";
+
+/// One suspected credential.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretFinding {
+    pub path: String,
+    /// 1-based line number.
+    pub line: usize,
+    pub kind: &'static str,
+}
+
+impl fmt::Display for SecretFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path, self.line, self.kind)
+    }
+}
+
+/// Identifier suffixes (lowercase, `-` read as `_`) that name a credential.
+const SECRET_KEY_SUFFIXES: &[&str] = &[
+    "password",
+    "passwd",
+    "pwd",
+    "secret",
+    "token",
+    "apikey",
+    "api_key",
+    "secret_key",
+    "access_key",
+    "private_key",
+];
+
+/// Values containing these (lowercase) are placeholders, not credentials.
+const PLACEHOLDER_WORDS: &[&str] = &[
+    "example",
+    "changeme",
+    "placeholder",
+    "dummy",
+    "password",
+    "your_",
+    "xxxx",
+    "****",
+];
+
+/// Scan `content` (the text of `path`) for likely credentials, one finding per line.
+pub fn find_secrets(path: &str, content: &str) -> Vec<SecretFinding> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.contains(ALLOW_SECRET_MARKER))
+        .filter_map(|(i, line)| {
+            line_secret_kind(line).map(|kind| SecretFinding {
+                path: path.to_string(),
+                line: i + 1,
+                kind,
+            })
+        })
+        .collect()
+}
+
+fn line_secret_kind(line: &str) -> Option<&'static str> {
+    if has_aws_key_id(line) {
+        Some("AWS access key ID")
+    } else if has_github_token(line) {
+        Some("GitHub token")
+    } else if line.contains("-----BEGIN ") && line.contains("PRIVATE KEY-----") {
+        Some("private key")
+    } else if has_secret_assignment(line) {
+        Some("hard-coded password or secret")
+    } else {
+        None
+    }
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Start offsets of `prefix` in `line` that are not preceded by a word character.
+fn word_starts<'a>(line: &'a str, prefix: &'a str) -> impl Iterator<Item = usize> + 'a {
+    line.match_indices(prefix)
+        .map(|(i, _)| i)
+        .filter(move |&i| i == 0 || !is_word_byte(line.as_bytes()[i - 1]))
+}
+
+/// `AKIA`/`ASIA` followed by exactly 16 uppercase letters or digits.
+fn has_aws_key_id(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    ["AKIA", "ASIA"].iter().any(|prefix| {
+        word_starts(line, prefix).any(|i| {
+            let body = &bytes[i + 4..];
+            body.len() >= 16
+                && body[..16]
+                    .iter()
+                    .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+                && body.get(16).is_none_or(|b| !b.is_ascii_alphanumeric())
+        })
+    })
+}
+
+/// Classic (`ghp_` etc. plus 36 characters) and fine-grained (`github_pat_`) tokens.
+fn has_github_token(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    let run = |from: usize, extra: &dyn Fn(u8) -> bool| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || extra(**b))
+            .count()
+    };
+    let classic = ["ghp_", "gho_", "ghu_", "ghs_", "ghr_"]
+        .iter()
+        .any(|prefix| word_starts(line, prefix).any(|i| run(i + 4, &|_| false) == 36));
+    classic || word_starts(line, "github_pat_").any(|i| run(i + 11, &|b| b == b'_') >= 22)
+}
+
+/// `<name>[" ]*(=|:)[ ]*<value>` where the name ends in a credential word and the value is
+/// a literal that looks random (not a placeholder, variable reference or expression).
+fn has_secret_assignment(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    bytes.iter().enumerate().any(|(op, &b)| {
+        if b != b'=' && b != b':' {
+            return false;
+        }
+        // Skip `==`, `!=`, `<=`, `>=`, `::` and `=>`; `:=` is an assignment.
+        let next = bytes.get(op + 1).copied();
+        let prev = op.checked_sub(1).map(|p| bytes[p]);
+        if (b == b'=' && next == Some(b'='))
+            || matches!(next, Some(b'>') | Some(b':'))
+            || matches!(
+                prev,
+                Some(b'=') | Some(b'!') | Some(b'<') | Some(b'>') | Some(b':')
+            )
+        {
+            return false;
+        }
+        let name = assignment_name(&line[..op]);
+        if !SECRET_KEY_SUFFIXES.iter().any(|s| name.ends_with(s)) {
+            return false;
+        }
+        let rest = line[op + 1..].trim_start_matches(['=', ' ', '\t']);
+        assignment_value(rest).is_some_and(|(value, quoted)| looks_random(value, quoted))
+    })
+}
+
+/// The identifier just before an assignment operator, lowercased with `-` as `_`.
+fn assignment_name(before: &str) -> String {
+    let trimmed = before.trim_end().trim_end_matches(['"', '\'']);
+    let start = trimmed
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    trimmed[start..].to_ascii_lowercase().replace('-', "_")
+}
+
+/// A quoted literal, or a bare word up to whitespace, `,` or `;`; true when quoted.
+fn assignment_value(rest: &str) -> Option<(&str, bool)> {
+    let quote = rest.chars().next()?;
+    if matches!(quote, '"' | '\'' | '`') {
+        let body = &rest[1..];
+        return body.find(quote).map(|end| (&body[..end], true));
+    }
+    let end = rest
+        .find(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .unwrap_or(rest.len());
+    Some((&rest[..end], false))
+}
+
+/// At least 10 characters from two or more character classes, no placeholder syntax (nor
+/// expression syntax when unquoted), and a Shannon entropy of at least 3 bits per character.
+fn looks_random(value: &str, quoted: bool) -> bool {
+    let syntax = if quoted { "{}<>$%" } else { "(){}[]<>$%&" };
+    if value.len() < 10 || value.contains(|c: char| c.is_whitespace() || syntax.contains(c)) {
+        return false;
+    }
+    let lower = value.to_ascii_lowercase();
+    if PLACEHOLDER_WORDS.iter().any(|w| lower.contains(w)) {
+        return false;
+    }
+    let classes = [
+        value.bytes().any(|b| b.is_ascii_lowercase()),
+        value.bytes().any(|b| b.is_ascii_uppercase()),
+        value.bytes().any(|b| b.is_ascii_digit()),
+        value.bytes().any(|b| !b.is_ascii_alphanumeric()),
+    ];
+    classes.iter().filter(|c| **c).count() >= 2 && shannon_entropy(value) >= 3.0
+}
+
+/// Shannon entropy of `s` in bits per byte.
+fn shannon_entropy(s: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for b in s.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = s.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Scan the text blobs staged in the index (added or modified relative to HEAD).
+pub fn staged_secret_findings(repo: &Repository) -> Result<Vec<SecretFinding>, Box<dyn Error>> {
+    let head_tree = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+    let mut findings = Vec::new();
+    for delta in diff.deltas() {
+        if !matches!(
+            delta.status(),
+            Delta::Added | Delta::Modified | Delta::Renamed | Delta::Copied | Delta::Typechange
+        ) {
+            continue;
+        }
+        let file = delta.new_file();
+        let Some(path) = file.path() else { continue };
+        let blob = repo.find_blob(file.id())?;
+        if blob.is_binary() {
+            continue;
+        }
+        let Ok(text) = std::str::from_utf8(blob.content()) else {
+            continue;
+        };
+        findings.extend(find_secrets(&path.to_string_lossy(), text));
+    }
+    Ok(findings)
+}
+
+/// Fail with the list of findings when the staged changes appear to contain credentials.
+pub fn check_staged_secrets(repo: &Repository) -> Result<(), Box<dyn Error>> {
+    let findings = staged_secret_findings(repo)?;
+    if findings.is_empty() {
+        return Ok(());
+    }
+    let list: Vec<String> = findings.iter().map(|f| format!("  {}", f)).collect();
+    Err(format!(
+        "refusing to commit: possible secrets in staged files:\n{}\nRemove them, add '{}' \
+         to a line that is a false positive, or pass --no-secret-scan",
+        list.join("\n"),
+        ALLOW_SECRET_MARKER
+    )
+    .into())
+}
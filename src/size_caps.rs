@@ -0,0 +1,172 @@
+//! Size caps for auto-staging, per file type, from `.mdcode.toml`.
+//!
+//! The `[size_caps]` table at the repository root maps `detect_file_type` labels
+//! (case-insensitive) to a cap in MB, plus a `default` for every other file:
+//!
+//! ```toml
+//! [size_caps]
+//! Image = 5
+//! Audio = 0   # 0 means no limit
+//! default = 50
+//! ```
+//!
+//! `--max-file-mb` replaces `default` only; a category listed in the table keeps its own cap.
+//! `--config <path>` reads another file instead of the one at the repository root.
+//! Files matching a Git LFS pattern of `.gitattributes` have no cap (see `LfsPatterns`).
+
+use crate::{
+    config_for, detect_file_type, Config, LfsPatterns, GITATTRIBUTES_FILE, MDCODE_CONFIG_FILE,
+};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// `AppliedCap::category` of files matching a Git LFS pattern.
+pub const LFS_CAP_SOURCE: &str = "Git LFS";
+
+/// Cap used when neither `--max-file-mb` nor a `default` key is given.
+pub const DEFAULT_MAX_FILE_MB: u64 = 50;
+
+/// Bytes in a cap of `mb` megabytes; `None` (no limit) for 0.
+pub fn cap_bytes(mb: u64) -> Option<u64> {
+    (mb > 0).then(|| mb.saturating_mul(1024).saturating_mul(1024))
+}
+
+/// True when a file of `size` bytes exceeds a cap of `mb` megabytes (never for 0).
+pub fn exceeds_cap(size: u64, mb: u64) -> bool {
+    cap_bytes(mb).is_some_and(|cap| size > cap)
+}
+
+/// The `[size_caps]` table of a repository; empty when there is none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeCaps {
+    /// The `default` key, in MB.
+    pub default_mb: Option<u64>,
+    /// Per-category caps in MB, keyed by lowercased `detect_file_type` label.
+    pub categories: BTreeMap<String, u64>,
+    /// `filter=lfs` patterns of the repository's `.gitattributes`; matching files are
+    /// never capped.
+    pub lfs: LfsPatterns,
+    /// The `--config` file the caps were read from; `None` for `.mdcode.toml`.
+    pub config_file: Option<PathBuf>,
+}
+
+/// The cap that applies to one file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppliedCap {
+    /// Cap in MB (0 means no limit).
+    pub mb: u64,
+    /// The `[size_caps]` category that set it (`LFS_CAP_SOURCE` for files tracked by Git
+    /// LFS), or `None` for the default cap.
+    pub category: Option<&'static str>,
+}
+
+impl AppliedCap {
+    /// True when a file of `size` bytes exceeds this cap.
+    pub fn exceeded_by(&self, size: u64) -> bool {
+        exceeds_cap(size, self.mb)
+    }
+}
+
+impl SizeCaps {
+    /// Parse the `[size_caps]` table of a `.mdcode.toml` document.
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        SizeCaps::from_doc(&text.parse()?)
+    }
+
+    /// The `[size_caps]` table of a parsed config document.
+    fn from_doc(doc: &toml::Value) -> Result<Self, Box<dyn Error>> {
+        let mut caps = SizeCaps::default();
+        let Some(table) = doc.get("size_caps") else {
+            return Ok(caps);
+        };
+        let table = table
+            .as_table()
+            .ok_or("[size_caps] must be a table of `Category = MB` entries")?;
+        for (key, value) in table {
+            let mb = value
+                .as_integer()
+                .and_then(|v| u64::try_from(v).ok())
+                .ok_or_else(|| {
+                    format!(
+                        "size_caps.{} must be a whole number of MB (0 for no limit)",
+                        key
+                    )
+                })?;
+            if key.eq_ignore_ascii_case("default") {
+                caps.default_mb = Some(mb);
+            } else {
+                caps.categories.insert(key.to_lowercase(), mb);
+            }
+        }
+        Ok(caps)
+    }
+
+    /// Load `.mdcode.toml` from the root of `dir` (or `config`, the `--config` file) and
+    /// the LFS patterns of `dir/.gitattributes`. A missing root file means no caps; an
+    /// unreadable or malformed one is an error naming the file.
+    pub fn load(dir: &str, config: Option<&Config>) -> Result<Self, Box<dyn Error>> {
+        let caps = {
+            let config = config_for(dir, config)?;
+            SizeCaps::from_doc(&config.doc).map_err(|e| config.invalid(&e.to_string()))?
+        };
+        Ok(SizeCaps {
+            lfs: LfsPatterns::load(dir)?,
+            config_file: config.map(|c| c.path.clone()),
+            ..caps
+        })
+    }
+
+    /// Where `cap` came from, for skip notices: `default cap, --max-file-mb` or
+    /// `size_caps.Image in .mdcode.toml`.
+    pub fn source(&self, cap: &AppliedCap) -> String {
+        match cap.category {
+            Some(LFS_CAP_SOURCE) => format!("Git LFS pattern in {}", GITATTRIBUTES_FILE),
+            Some(label) => format!(
+                "size_caps.{} in {}",
+                label,
+                self.config_file
+                    .as_ref()
+                    .map_or(MDCODE_CONFIG_FILE.to_string(), |p| p.display().to_string())
+            ),
+            None => "default cap, --max-file-mb".to_string(),
+        }
+    }
+
+    /// The cap for `path`: none for files tracked by Git LFS, else its category's entry
+    /// when there is one, else `default_mb`.
+    pub fn cap_for(&self, path: &Path, default_mb: u64) -> AppliedCap {
+        if self.lfs.matches(path) {
+            return AppliedCap {
+                mb: 0,
+                category: Some(LFS_CAP_SOURCE),
+            };
+        }
+        detect_file_type(path)
+            .and_then(|label| {
+                self.categories
+                    .get(&label.to_lowercase())
+                    .map(|&mb| AppliedCap {
+                        mb,
+                        category: Some(label),
+                    })
+            })
+            .unwrap_or(AppliedCap {
+                mb: default_mb,
+                category: None,
+            })
+    }
+}
+
+/// The default cap for commands run on `dir`: `--max-file-mb` when given, else the
+/// `default` key of `[size_caps]` (in `config`, the `--config` file, when given), else
+/// `DEFAULT_MAX_FILE_MB`.
+pub fn effective_max_file_mb(
+    cli_max_file_mb: Option<u64>,
+    dir: &str,
+    config: Option<&Config>,
+) -> u64 {
+    cli_max_file_mb
+        .or_else(|| SizeCaps::load(dir, config).ok().and_then(|c| c.default_mb))
+        .unwrap_or(DEFAULT_MAX_FILE_MB)
+}
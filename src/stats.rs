@@ -0,0 +1,273 @@
+//! Repository health snapshot for `mdcode stats`.
+//!
+//! Everything is computed from the HEAD tree and object database rather than the working
+//! directory. Blobs are inspected one at a time (sizes via the ODB header only), and line
+//! counting is skipped for blobs larger than a configurable cap.
+
+use crate::{detect_file_type, open_repo_with_commits, path_size};
+use chrono::{TimeZone, Utc};
+use git2::{ObjectType, Sort, TreeWalkMode, TreeWalkResult};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::Path;
+
+/// Categories whose files are binary assets; lines are not counted for them.
+const BINARY_CATEGORIES: &[&str] = &["Image", "Icon", "Cursor", "Audio", "Font"];
+
+/// Per-category file and line totals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CategoryStats {
+    pub files: usize,
+    /// Lines in text blobs of this category (0 for binary categories).
+    pub lines: usize,
+}
+
+/// Snapshot returned by `repo_stats`.
+#[derive(Debug, Clone, Default)]
+pub struct RepoStats {
+    pub tracked_files: usize,
+    /// Keyed by `detect_file_type` label; unrecognized files are counted under "Other".
+    pub categories: BTreeMap<String, CategoryStats>,
+    pub total_lines: usize,
+    /// Text blobs whose lines were not counted because they exceeded the cap.
+    pub skipped_line_counts: usize,
+    pub object_count: usize,
+    pub git_dir_bytes: u64,
+    pub commit_count: usize,
+    pub contributors: usize,
+    /// Unix timestamps of the oldest and newest commits reachable from HEAD.
+    pub first_commit: Option<i64>,
+    pub last_commit: Option<i64>,
+    /// Largest tracked files (path, bytes), biggest first; at most 10.
+    pub largest_files: Vec<(String, u64)>,
+}
+
+impl RepoStats {
+    /// Render as a JSON value (timestamps as RFC 3339 strings).
+    pub fn to_json(&self) -> serde_json::Value {
+        let categories: serde_json::Map<String, serde_json::Value> = self
+            .categories
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.clone(),
+                    serde_json::json!({ "files": v.files, "lines": v.lines }),
+                )
+            })
+            .collect();
+        serde_json::json!({
+            "tracked_files": self.tracked_files,
+            "categories": categories,
+            "total_lines": self.total_lines,
+            "skipped_line_counts": self.skipped_line_counts,
+            "object_count": self.object_count,
+            "git_dir_bytes": self.git_dir_bytes,
+            "commit_count": self.commit_count,
+            "contributors": self.contributors,
+            "first_commit": self.first_commit.map(rfc3339),
+            "last_commit": self.last_commit.map(rfc3339),
+            "largest_files": self
+                .largest_files
+                .iter()
+                .map(|(p, s)| serde_json::json!({ "path": p, "bytes": s }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn rfc3339(secs: i64) -> String {
+    Utc.timestamp_opt(secs, 0)
+        .single()
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| "(invalid time)".to_string())
+}
+
+/// Gather repository statistics. Blobs larger than `max_blob_bytes` are sized but not
+/// line-counted.
+pub fn repo_stats(dir: &str, max_blob_bytes: u64) -> Result<RepoStats, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    let head = repo.head()?;
+    let tree = head.peel_to_tree()?;
+    let odb = repo.odb()?;
+    let mut stats = RepoStats::default();
+    let mut sizes: Vec<(String, u64)> = Vec::new();
+    let mut walk_err: Option<Box<dyn Error>> = None;
+
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+        let path = format!("{}{}", root, entry.name().unwrap_or("?"));
+        let (size, _) = match odb.read_header(entry.id()) {
+            Ok(h) => h,
+            Err(e) => {
+                walk_err = Some(e.into());
+                return TreeWalkResult::Abort;
+            }
+        };
+        let size = size as u64;
+        let category = detect_file_type(Path::new(&path)).unwrap_or("Other");
+        let cat = stats.categories.entry(category.to_string()).or_default();
+        cat.files += 1;
+        stats.tracked_files += 1;
+        if !BINARY_CATEGORIES.contains(&category) {
+            if size > max_blob_bytes {
+                stats.skipped_line_counts += 1;
+            } else if let Ok(blob) = repo.find_blob(entry.id()) {
+                if !blob.is_binary() {
+                    let content = blob.content();
+                    let mut lines = content.iter().filter(|b| **b == b'\n').count();
+                    if content.last().is_some_and(|b| *b != b'\n') {
+                        lines += 1;
+                    }
+                    cat.lines += lines;
+                    stats.total_lines += lines;
+                }
+            }
+        }
+        sizes.push((path, size));
+        TreeWalkResult::Ok
+    })?;
+    if let Some(e) = walk_err {
+        return Err(e);
+    }
+    stats.largest_files = top_by_size(sizes, 10);
+
+    odb.foreach(|_| {
+        stats.object_count += 1;
+        true
+    })?;
+    stats.git_dir_bytes = path_size(repo.path());
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TIME)?;
+    let mut authors = BTreeSet::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let t = commit.time().seconds();
+        stats.commit_count += 1;
+        stats.last_commit = Some(stats.last_commit.map_or(t, |v| v.max(t)));
+        stats.first_commit = Some(stats.first_commit.map_or(t, |v| v.min(t)));
+        let author = commit.author();
+        authors.insert(
+            author
+                .email()
+                .or(author.name())
+                .unwrap_or("(unknown)")
+                .to_lowercase(),
+        );
+    }
+    stats.contributors = authors.len();
+    Ok(stats)
+}
+
+/// Biggest first, ties by path, at most `n` entries.
+fn top_by_size(mut sizes: Vec<(String, u64)>, n: usize) -> Vec<(String, u64)> {
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sizes.truncate(n);
+    sizes
+}
+
+/// The `n` largest blobs in the HEAD tree as (path, bytes), biggest first.
+pub fn largest_files(dir: &str, n: usize) -> Result<Vec<(String, u64)>, Box<dyn Error>> {
+    let repo = open_repo_with_commits(dir)?;
+    let tree = repo.head()?.peel_to_tree()?;
+    let odb = repo.odb()?;
+    let mut sizes: Vec<(String, u64)> = Vec::new();
+    let mut walk_err: Option<git2::Error> = None;
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+        match odb.read_header(entry.id()) {
+            Ok((size, _)) => {
+                let path = format!("{}{}", root, entry.name().unwrap_or("?"));
+                sizes.push((path, size as u64));
+                TreeWalkResult::Ok
+            }
+            Err(e) => {
+                walk_err = Some(e);
+                TreeWalkResult::Abort
+            }
+        }
+    })?;
+    if let Some(e) = walk_err {
+        return Err(e.into());
+    }
+    Ok(top_by_size(sizes, n))
+}
+
+/// Print the `n` largest tracked files (`mdcode biggest`).
+pub fn biggest_command(dir: &str, n: usize) -> Result<Vec<(String, u64)>, Box<dyn Error>> {
+    let files = largest_files(dir, n)?;
+    #[cfg(not(coverage))]
+    {
+        use crate::{colorize, format_size, YELLOW};
+        if files.is_empty() {
+            log::info!("No tracked files in HEAD");
+        }
+        for (path, size) in &files {
+            log::info!("{:>10}  {}", colorize(YELLOW, format_size(*size)), path);
+        }
+    }
+    Ok(files)
+}
+
+/// Print `repo_stats` output as text (or pretty JSON when `json` is set).
+pub fn stats_command(dir: &str, json: bool, max_blob_kb: u64) -> Result<RepoStats, Box<dyn Error>> {
+    let stats = repo_stats(dir, max_blob_kb.saturating_mul(1024))?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats.to_json())?);
+        return Ok(stats);
+    }
+    #[cfg(not(coverage))]
+    {
+        use crate::{colorize, format_size, BLUE, YELLOW};
+        let now = Utc::now().timestamp();
+        let age = |t: Option<i64>| match t {
+            Some(t) => format!("{} ({} days ago)", rfc3339(t), (now - t).max(0) / 86_400),
+            None => "(none)".to_string(),
+        };
+        log::info!(
+            "{} {} files, {} lines",
+            colorize(BLUE, "Tracked:"),
+            stats.tracked_files,
+            stats.total_lines
+        );
+        for (name, cat) in &stats.categories {
+            log::info!(
+                "  {:<20} {:>6} files {:>9} lines",
+                name,
+                cat.files,
+                cat.lines
+            );
+        }
+        if stats.skipped_line_counts > 0 {
+            log::info!(
+                "  ({} blobs larger than {} KB were not line-counted)",
+                stats.skipped_line_counts,
+                max_blob_kb
+            );
+        }
+        log::info!(
+            "{} {} objects, .git is {}",
+            colorize(BLUE, "Storage:"),
+            stats.object_count,
+            format_size(stats.git_dir_bytes)
+        );
+        log::info!(
+            "{} {} commits by {} contributors",
+            colorize(BLUE, "History:"),
+            stats.commit_count,
+            stats.contributors
+        );
+        log::info!("  first: {}", age(stats.first_commit));
+        log::info!("  last:  {}", age(stats.last_commit));
+        log::info!("{}", colorize(BLUE, "Largest files:"));
+        for (path, size) in &stats.largest_files {
+            log::info!("  {:>10}  {}", colorize(YELLOW, format_size(*size)), path);
+        }
+    }
+    Ok(stats)
+}
@@ -0,0 +1,96 @@
+//! Time limit for the git commands that talk to a remote (`--timeout`).
+//!
+//! `gh_fetch`, `gh_push` and `gh_sync` take the limit as a parameter (or in `PushOptions`)
+//! and run `git fetch`/`pull`/`push`/`ls-remote` through `git_status`/`git_output` with it.
+//! Without a limit these simply wait for git; with one, a git still running at the deadline
+//! is killed and the command fails with `MdcodeError::TimedOut` instead of hanging on an
+//! unreachable host.
+
+use crate::MdcodeError;
+use std::error::Error;
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running git is checked against the deadline.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// `cmd.status()`, killed after `timeout` (`--timeout`); no limit when `None`.
+pub fn git_status(
+    cmd: &mut Command,
+    timeout: Option<Duration>,
+) -> Result<ExitStatus, Box<dyn Error>> {
+    let mut child = cmd.spawn()?;
+    wait_with_timeout(&mut child, cmd, timeout)
+}
+
+/// `cmd.output()`, killed after `timeout` (`--timeout`). Stdout is captured; stderr is
+/// captured only when the caller set it to `Stdio::piped()` (otherwise it goes where the
+/// caller sent it).
+pub fn git_output(cmd: &mut Command, timeout: Option<Duration>) -> Result<Output, Box<dyn Error>> {
+    let mut child = cmd.stdout(Stdio::piped()).spawn()?;
+    // Drain the pipes on their own threads so a chatty git cannot block on a full pipe.
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+    let status = wait_with_timeout(&mut child, cmd, timeout)?;
+    let collect = |h: Option<thread::JoinHandle<Vec<u8>>>| {
+        h.map(|h| h.join().unwrap_or_default()).unwrap_or_default()
+    };
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+fn drain(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// Wait for `child`, killing it once `timeout` has passed.
+fn wait_with_timeout(
+    child: &mut Child,
+    cmd: &Command,
+    timeout: Option<Duration>,
+) -> Result<ExitStatus, Box<dyn Error>> {
+    let Some(limit) = timeout else {
+        return Ok(child.wait()?);
+    };
+    let deadline = Instant::now() + limit;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(MdcodeError::TimedOut(format!(
+                "operation timed out: '{}' did not finish within {}s (--timeout)",
+                describe(cmd),
+                limit.as_secs()
+            ))
+            .into());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// `git <subcommand> ...` for messages, leaving out the `-C <dir>` prefix.
+fn describe(cmd: &Command) -> String {
+    let args: Vec<String> = cmd
+        .get_args()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    let rest = match args.first().map(String::as_str) {
+        Some("-C") => &args[2.min(args.len())..],
+        _ => &args[..],
+    };
+    let mut parts = vec![cmd.get_program().to_string_lossy().into_owned()];
+    parts.extend(rest.iter().cloned());
+    parts.join(" ")
+}
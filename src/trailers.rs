@@ -0,0 +1,73 @@
+//! Commit message trailers for `update --trailer` and `--signoff`.
+//!
+//! Trailers go into a block of `Key: Value` lines at the end of the message, separated from
+//! the body by a blank line as `git interpret-trailers` does. When the message already ends
+//! in such a block (an amended commit that was signed off, say), new trailers join it and
+//! lines already present are not repeated.
+
+use git2::Signature;
+use std::error::Error;
+
+/// Split `"Key: Value"` into its key and value. The key must be a single word (letters,
+/// digits and `-`) and the value non-empty.
+pub fn parse_trailer(trailer: &str) -> Result<(String, String), Box<dyn Error>> {
+    let invalid = || -> Box<dyn Error> {
+        format!(
+            "invalid trailer '{}': expected \"Key: Value\" (e.g. \"Reviewed-by: Name <email>\")",
+            trailer
+        )
+        .into()
+    };
+    let (key, value) = trailer.split_once(':').ok_or_else(invalid)?;
+    let (key, value) = (key.trim(), value.trim());
+    if key.is_empty()
+        || value.is_empty()
+        || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err(invalid());
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// The `Signed-off-by` trailer for `signature`.
+pub fn signoff_trailer(signature: &Signature) -> String {
+    format!(
+        "Signed-off-by: {} <{}>",
+        signature.name().unwrap_or(""),
+        signature.email().unwrap_or("")
+    )
+}
+
+/// True when every line of `paragraph` is a `Key: Value` trailer.
+fn is_trailer_block(paragraph: &str) -> bool {
+    paragraph.lines().all(|l| parse_trailer(l).is_ok())
+}
+
+/// `message` with `trailers` (already in `Key: Value` form) appended as its trailer block;
+/// trailers the message already ends with are skipped.
+pub fn append_trailers(message: &str, trailers: &[String]) -> String {
+    let body = message.trim_end();
+    let last_paragraph = body.rsplit("\n\n").next().unwrap_or("");
+    let has_block = body.contains("\n\n") && is_trailer_block(last_paragraph);
+    let existing: Vec<&str> = if has_block {
+        last_paragraph.lines().collect()
+    } else {
+        Vec::new()
+    };
+    let mut out = body.to_string();
+    let mut added: Vec<&str> = Vec::new();
+    for trailer in trailers {
+        let trailer = trailer.trim();
+        if existing.contains(&trailer) || added.contains(&trailer) {
+            continue;
+        }
+        if added.is_empty() && !has_block {
+            out.push_str("\n\n");
+        } else {
+            out.push('\n');
+        }
+        out.push_str(trailer);
+        added.push(trailer);
+    }
+    out
+}
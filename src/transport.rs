@@ -0,0 +1,139 @@
+//! Pre-flight checks on a remote's URL before `gh_push`, `gh_fetch` and `gh_sync` talk to it.
+//!
+//! Plain `http://` remotes send credentials and code unencrypted, so they are refused unless
+//! `--insecure` is passed. For SSH remotes mdcode checks that an agent or key looks
+//! available, turning the usual opaque `Permission denied (publickey)` into a clear error.
+
+use crate::open_repo;
+#[cfg(not(coverage))]
+use crate::{colorize, YELLOW};
+use std::error::Error;
+use std::path::Path;
+
+/// Transport of a remote URL, as far as mdcode's checks are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteUrlKind {
+    Https,
+    /// Plain `http://`: no TLS.
+    Http,
+    /// `ssh://` URLs and scp-like `user@host:path`.
+    Ssh,
+    /// `file://` URLs and plain filesystem paths.
+    Local,
+    /// Anything else (e.g. `git://`).
+    Other,
+}
+
+/// Classify a remote URL by its scheme (case-insensitive).
+pub fn classify_remote_url(url: &str) -> RemoteUrlKind {
+    let url = url.trim();
+    let lower = url.to_ascii_lowercase();
+    if let Some((scheme, _)) = lower.split_once("://") {
+        return match scheme {
+            "https" => RemoteUrlKind::Https,
+            "http" => RemoteUrlKind::Http,
+            "ssh" | "git+ssh" | "ssh+git" => RemoteUrlKind::Ssh,
+            "file" => RemoteUrlKind::Local,
+            _ => RemoteUrlKind::Other,
+        };
+    }
+    // scp-like `[user@]host:path`; a single letter before ':' is a Windows drive.
+    match url.split_once(':') {
+        Some((host, _)) if host.len() > 1 && !host.contains(['/', '\\']) => RemoteUrlKind::Ssh,
+        _ => RemoteUrlKind::Local,
+    }
+}
+
+/// Check the shape of a URL given for a new remote (`gh_create --remote-url`): it must not
+/// be empty or contain whitespace, scheme URLs need a host or path after `://`, and local
+/// paths (plain or `file://`) must exist.
+pub fn validate_remote_url(url: &str) -> Result<RemoteUrlKind, Box<dyn Error>> {
+    let url = url.trim();
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        return Err(format!("invalid remote URL '{}'", url).into());
+    }
+    let kind = classify_remote_url(url);
+    if let Some((_, rest)) = url.split_once("://") {
+        if rest.trim_start_matches('/').is_empty() {
+            return Err(format!("invalid remote URL '{}': nothing after '://'", url).into());
+        }
+    }
+    if kind == RemoteUrlKind::Local {
+        let path = url
+            .strip_prefix("file://")
+            .or_else(|| url.strip_prefix("FILE://"))
+            .unwrap_or(url);
+        if !Path::new(path).exists() {
+            return Err(format!(
+                "remote URL '{}' is not a URL and no such path exists (create it with `git init --bare`)",
+                url
+            )
+            .into());
+        }
+    }
+    Ok(kind)
+}
+
+/// True when SSH authentication looks possible: an agent socket (`SSH_AUTH_SOCK`), a custom
+/// `GIT_SSH_COMMAND`/`GIT_SSH`, or an `id_*` key or `config` file under `~/.ssh`.
+pub fn ssh_credentials_available() -> bool {
+    let set = |key: &str| std::env::var_os(key).is_some_and(|v| !v.is_empty());
+    if set("SSH_AUTH_SOCK") || set("GIT_SSH_COMMAND") || set("GIT_SSH") {
+        return true;
+    }
+    let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) else {
+        return false;
+    };
+    let Ok(entries) = std::fs::read_dir(Path::new(&home).join(".ssh")) else {
+        return false;
+    };
+    entries.filter_map(|e| e.ok()).any(|e| {
+        let name = e.file_name().to_string_lossy().to_string();
+        name == "config" || (name.starts_with("id_") && !name.ends_with(".pub"))
+    })
+}
+
+/// Check `remote`'s URL before fetching or pushing: plain http is refused unless
+/// `insecure` (and warned about when allowed); SSH without an agent or key fails early.
+/// A missing remote is left for the operation itself to report.
+pub fn check_remote_transport(
+    directory: &str,
+    remote: &str,
+    insecure: bool,
+) -> Result<(), Box<dyn Error>> {
+    let repo = open_repo(directory)?;
+    let Ok(found) = repo.find_remote(remote) else {
+        return Ok(());
+    };
+    let url = found
+        .pushurl()
+        .or(found.url())
+        .unwrap_or_default()
+        .to_string();
+    match classify_remote_url(&url) {
+        RemoteUrlKind::Http if !insecure => Err(format!(
+            "remote '{}' uses plain http ({}): credentials and code would be sent unencrypted; \
+             switch it to https or ssh (git remote set-url {} <url>), or pass --insecure",
+            remote, url, remote
+        )
+        .into()),
+        RemoteUrlKind::Http => {
+            #[cfg(not(coverage))]
+            log::warn!(
+                "{}",
+                colorize(
+                    YELLOW,
+                    format!("Using insecure http remote '{}' ({})", remote, url)
+                )
+            );
+            Ok(())
+        }
+        RemoteUrlKind::Ssh if !ssh_credentials_available() => Err(format!(
+            "remote '{}' uses SSH ({}) but no ssh-agent (SSH_AUTH_SOCK) or key in ~/.ssh was \
+             found; start ssh-agent and add a key (ssh-add), or switch the remote to https",
+            remote, url
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,338 @@
+//! Pre-release health checks for `mdcode verify`.
+//!
+//! Each check inspects one aspect of the repository and returns a `VerifyResult` (pass or
+//! fail, what was found, and how to fix a failure); a check that cannot run counts as
+//! failed. `verify_command` runs every check not skipped with `--skip <name>`, prints one
+//! line per check and exits 1 when any failed. Named `VerifyResult` because
+//! `CheckResult` already describes CI checks for `gh_status`.
+
+use crate::{
+    colorize, dirty_report, format_size, generate_gitignore_content, normalize_semver_tag,
+    open_repo, open_repo_with_commits, read_version_from_cargo_toml,
+    scan_source_files_with_options, Config, MdcodeError, ScanOptions, SizeCaps, GREEN, RED,
+};
+use git2::{BranchType, ObjectType, TreeWalkMode, TreeWalkResult};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Names accepted by `--skip`, in the order the checks run.
+pub const VERIFY_CHECKS: &[&str] = &[
+    "clean",
+    "untracked",
+    "upstream",
+    "version-tag",
+    "gitignore",
+    "large-files",
+];
+
+/// Outcome of one `verify` check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyResult {
+    /// Check name, as accepted by `--skip`.
+    pub name: &'static str,
+    pub passed: bool,
+    /// What the check found.
+    pub detail: String,
+    /// How to fix a failure.
+    pub hint: Option<String>,
+}
+
+impl VerifyResult {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        VerifyResult {
+            name,
+            passed: true,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        VerifyResult {
+            name,
+            passed: false,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn error(name: &'static str, err: Box<dyn Error>) -> Self {
+        VerifyResult::fail(
+            name,
+            format!("could not run check: {}", err),
+            "fix the error above and re-run 'mdcode verify'",
+        )
+    }
+}
+
+/// Working tree has no uncommitted changes in tracked files (`dirty_report`).
+pub fn check_clean(dir: &str) -> VerifyResult {
+    const NAME: &str = "clean";
+    match dirty_report(dir) {
+        Ok(report) if report.is_dirty() => VerifyResult::fail(
+            NAME,
+            format!("uncommitted changes:\n{}", report.summary(10)),
+            "commit them with 'mdcode update' (or stash them)",
+        ),
+        Ok(_) => VerifyResult::pass(NAME, "no uncommitted changes"),
+        Err(e) => VerifyResult::error(NAME, e),
+    }
+}
+
+/// No recognized source file (one `update` would stage) is missing from the index.
+pub fn check_untracked_sources(
+    dir: &str,
+    max_file_mb: u64,
+    config: Option<&Config>,
+) -> VerifyResult {
+    const NAME: &str = "untracked";
+    let run = || -> Result<Vec<String>, Box<dyn Error>> {
+        let repo = open_repo(dir)?;
+        let index = repo.index()?;
+        let scan = ScanOptions {
+            config: config.cloned(),
+            ..ScanOptions::default()
+        };
+        let (files, _) = scan_source_files_with_options(dir, max_file_mb, &scan)?;
+        let mut untracked: Vec<String> = files
+            .iter()
+            .filter_map(|f| f.strip_prefix(dir).ok())
+            .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+            .filter(|rel| index.get_path(Path::new(rel), 0).is_none())
+            .collect();
+        untracked.sort();
+        Ok(untracked)
+    };
+    match run() {
+        Ok(untracked) if untracked.is_empty() => {
+            VerifyResult::pass(NAME, "all recognized source files are tracked")
+        }
+        Ok(untracked) => VerifyResult::fail(
+            NAME,
+            format!("untracked source files: {}", list(&untracked)),
+            "commit them with 'mdcode update', or add them to .gitignore",
+        ),
+        Err(e) => VerifyResult::error(NAME, e),
+    }
+}
+
+/// The current branch tracks a remote branch and has not diverged from it (as of the
+/// last fetch).
+pub fn check_upstream(dir: &str) -> VerifyResult {
+    const NAME: &str = "upstream";
+    let run = || -> Result<VerifyResult, Box<dyn Error>> {
+        let repo = open_repo_with_commits(dir)?;
+        let head = repo.head()?;
+        let Some(name) = head.shorthand().filter(|_| head.is_branch()) else {
+            return Ok(VerifyResult::fail(
+                NAME,
+                "HEAD is detached",
+                "check out the branch you are releasing",
+            ));
+        };
+        let branch = repo.find_branch(name, BranchType::Local)?;
+        let Ok(upstream) = branch.upstream() else {
+            return Ok(VerifyResult::fail(
+                NAME,
+                format!("branch '{}' has no upstream", name),
+                "push it with 'mdcode gh_push' (which sets the upstream)",
+            ));
+        };
+        let upstream_name = upstream.name()?.unwrap_or("(upstream)").to_string();
+        let local = branch.get().peel_to_commit()?.id();
+        let remote = upstream.get().peel_to_commit()?.id();
+        let (ahead, behind) = repo.graph_ahead_behind(local, remote)?;
+        let detail = format!(
+            "'{}' tracks '{}' ({} ahead, {} behind)",
+            name, upstream_name, ahead, behind
+        );
+        Ok(if ahead > 0 && behind > 0 {
+            VerifyResult::fail(
+                NAME,
+                format!("{}: diverged", detail),
+                "run 'mdcode gh_sync', resolve the merge, then 'mdcode gh_push'",
+            )
+        } else {
+            VerifyResult::pass(NAME, detail)
+        })
+    };
+    run().unwrap_or_else(|e| VerifyResult::error(NAME, e))
+}
+
+/// The `Cargo.toml` package version has no release tag yet. Passes when there is no
+/// `Cargo.toml` version to check.
+pub fn check_version_tag(dir: &str) -> VerifyResult {
+    const NAME: &str = "version-tag";
+    let run = || -> Result<VerifyResult, Box<dyn Error>> {
+        let Some(version) = read_version_from_cargo_toml(dir)? else {
+            return Ok(VerifyResult::pass(NAME, "no Cargo.toml version to check"));
+        };
+        let (_, tag) = normalize_semver_tag(&version)?;
+        let tagged = open_repo(dir)?
+            .find_reference(&format!("refs/tags/{}", tag))
+            .is_ok();
+        Ok(if tagged {
+            VerifyResult::fail(
+                NAME,
+                format!("Cargo.toml version {} is already tagged ({})", version, tag),
+                "bump the version in Cargo.toml before releasing",
+            )
+        } else {
+            VerifyResult::pass(NAME, format!("{} is not tagged yet", tag))
+        })
+    };
+    run().unwrap_or_else(|e| VerifyResult::error(NAME, e))
+}
+
+/// `.gitignore` contains every pattern `mdcode new` would write for this directory
+/// (`generate_gitignore_content`).
+pub fn check_gitignore(dir: &str) -> VerifyResult {
+    const NAME: &str = "gitignore";
+    let run = || -> Result<VerifyResult, Box<dyn Error>> {
+        let path = Path::new(dir).join(".gitignore");
+        let Ok(existing) = fs::read_to_string(&path) else {
+            return Ok(VerifyResult::fail(
+                NAME,
+                ".gitignore is missing",
+                "create one; 'mdcode new' writes the baseline patterns",
+            ));
+        };
+        let present: Vec<&str> = existing.lines().map(str::trim).collect();
+        let missing: Vec<String> = generate_gitignore_content(dir)?
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter(|l| !present.contains(l))
+            .map(str::to_string)
+            .collect();
+        Ok(if missing.is_empty() {
+            VerifyResult::pass(NAME, ".gitignore has the mdcode baseline patterns")
+        } else {
+            VerifyResult::fail(
+                NAME,
+                format!(".gitignore is missing {}", list(&missing)),
+                "add the missing patterns to .gitignore",
+            )
+        })
+    };
+    run().unwrap_or_else(|e| VerifyResult::error(NAME, e))
+}
+
+/// No file tracked at HEAD is larger than its size cap (`--max-file-mb`, or its category's
+/// entry in `[size_caps]`).
+pub fn check_large_files(dir: &str, max_file_mb: u64, config: Option<&Config>) -> VerifyResult {
+    const NAME: &str = "large-files";
+    let run = || -> Result<Vec<String>, Box<dyn Error>> {
+        let caps = SizeCaps::load(dir, config)?;
+        let repo = open_repo_with_commits(dir)?;
+        let tree = repo.head()?.peel_to_tree()?;
+        let odb = repo.odb()?;
+        let mut large = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                let path = format!("{}{}", root, entry.name().unwrap_or("?"));
+                if let Ok((size, _)) = odb.read_header(entry.id()) {
+                    if caps
+                        .cap_for(Path::new(&path), max_file_mb)
+                        .exceeded_by(size as u64)
+                    {
+                        large.push(format!("{} ({})", path, format_size(size as u64)));
+                    }
+                }
+            }
+            TreeWalkResult::Ok
+        })?;
+        Ok(large)
+    };
+    match run() {
+        Ok(large) if large.is_empty() => {
+            VerifyResult::pass(NAME, "no tracked file over its size cap")
+        }
+        Ok(large) => VerifyResult::fail(
+            NAME,
+            format!("tracked files over their size cap: {}", list(&large)),
+            "untrack them with 'git rm --cached <path>' and add them to .gitignore",
+        ),
+        Err(e) => VerifyResult::error(NAME, e),
+    }
+}
+
+/// Run every check whose name is not in `skip`. Unknown names in `skip` are an error.
+pub fn run_verify_checks(
+    dir: &str,
+    max_file_mb: u64,
+    skip: &[String],
+    config: Option<&Config>,
+) -> Result<Vec<VerifyResult>, Box<dyn Error>> {
+    if let Some(unknown) = skip.iter().find(|s| !VERIFY_CHECKS.contains(&s.as_str())) {
+        return Err(format!(
+            "unknown check '{}' for --skip (expected one of: {})",
+            unknown,
+            VERIFY_CHECKS.join(", ")
+        )
+        .into());
+    }
+    open_repo(dir)?;
+    let mut results = Vec::new();
+    for &name in VERIFY_CHECKS {
+        if skip.iter().any(|s| s == name) {
+            continue;
+        }
+        results.push(match name {
+            "clean" => check_clean(dir),
+            "untracked" => check_untracked_sources(dir, max_file_mb, config),
+            "upstream" => check_upstream(dir),
+            "version-tag" => check_version_tag(dir),
+            "gitignore" => check_gitignore(dir),
+            _ => check_large_files(dir, max_file_mb, config),
+        });
+    }
+    Ok(results)
+}
+
+/// `PASS name: detail` or `FAIL name: detail` plus an indented `hint:` line.
+pub fn format_verify_result(result: &VerifyResult) -> String {
+    let status = if result.passed {
+        colorize(GREEN, "PASS")
+    } else {
+        colorize(RED, "FAIL")
+    };
+    let mut line = format!("{} {}: {}", status, result.name, result.detail);
+    if let Some(hint) = &result.hint {
+        line.push_str(&format!("\n     hint: {}", hint));
+    }
+    line
+}
+
+/// `mdcode verify`: run and print the checks; any failure becomes exit status 1.
+pub fn verify_command(
+    dir: &str,
+    max_file_mb: u64,
+    skip: &[String],
+    config: Option<&Config>,
+) -> Result<Vec<VerifyResult>, Box<dyn Error>> {
+    let results = run_verify_checks(dir, max_file_mb, skip, config)?;
+    #[cfg(not(coverage))]
+    for result in &results {
+        log::info!("{}", format_verify_result(result));
+    }
+    let failed = results.iter().filter(|r| !r.passed).count();
+    if failed > 0 {
+        return Err(MdcodeError::ExitCode(
+            1,
+            format!("{} of {} verify checks failed", failed, results.len()),
+        )
+        .into());
+    }
+    Ok(results)
+}
+
+/// Up to five items, comma-separated, noting how many more there are.
+fn list(items: &[String]) -> String {
+    let mut out = items.iter().take(5).cloned().collect::<Vec<_>>().join(", ");
+    if items.len() > 5 {
+        out.push_str(&format!(" and {} more", items.len() - 5));
+    }
+    out
+}
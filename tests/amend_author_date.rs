@@ -0,0 +1,129 @@
+use git2::{Repository, Signature, Time};
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// Commit `file` with `body` as "Old Author", authored at `when` (Unix seconds).
+fn commit_at(dir: &Path, file: &str, body: &str, message: &str, when: i64) -> git2::Oid {
+    std::fs::write(dir.join(file), body).unwrap();
+    let repo = Repository::open(dir).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file)).unwrap();
+    index.write().unwrap();
+    let author = Signature::new("Old Author", "old@example.com", &Time::new(when, 60)).unwrap();
+    commit_current_index(&repo, message, &author, &author, false).unwrap()
+}
+
+fn head(dir: &Path) -> (git2::Oid, i64, i64, String, String) {
+    let repo = Repository::open(dir).unwrap();
+    let c = repo.head().unwrap().peel_to_commit().unwrap();
+    let out = (
+        c.id(),
+        c.author().when().seconds(),
+        c.committer().when().seconds(),
+        c.author().name().unwrap().to_string(),
+        c.message().unwrap().to_string(),
+    );
+    out
+}
+
+#[test]
+fn test_update_amend_keeps_author_date() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let original = commit_at(&dir, "b.txt", "b\n", "Add b", 1_000_000_000);
+    let parent = Repository::open(&dir)
+        .unwrap()
+        .find_commit(original)
+        .unwrap()
+        .parent_id(0)
+        .unwrap();
+
+    std::fs::write(dir.join("b.txt"), "b fixed\n").unwrap();
+    let opts = UpdateOptions {
+        amend: true,
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, None, 50, &opts).unwrap();
+
+    let (id, author_time, committer_time, author, message) = head(&dir);
+    assert_ne!(id, original);
+    assert_eq!(author_time, 1_000_000_000);
+    assert!(committer_time > 1_000_000_000);
+    assert_eq!(author, "Old Author");
+    assert_eq!(message.trim_end(), "Add b");
+    let repo = Repository::open(&dir).unwrap();
+    let amended = repo.find_commit(id).unwrap();
+    assert_eq!(amended.parent_ids().collect::<Vec<_>>(), vec![parent]);
+    let blob = amended
+        .tree()
+        .unwrap()
+        .get_path(Path::new("b.txt"))
+        .unwrap();
+    assert_eq!(repo.find_blob(blob.id()).unwrap().content(), b"b fixed\n");
+
+    // --author replaces the author but still keeps the date; -m replaces the message.
+    let opts = UpdateOptions {
+        amend: true,
+        author: Some("New Author <new@example.com>".into()),
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, Some("Add b (reworded)"), 50, &opts).unwrap();
+    let (_, author_time, _, author, message) = head(&dir);
+    assert_eq!(author_time, 1_000_000_000);
+    assert_eq!(author, "New Author");
+    assert_eq!(message, "Add b (reworded)");
+}
+
+#[test]
+fn test_squash_keeps_oldest_author_date() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let (base, ..) = head(&dir);
+    commit_at(&dir, "b.txt", "b\n", "Add b", 1_000_000_000);
+    commit_at(&dir, "c.txt", "c\n", "Add c", 1_100_000_000);
+    let (newest, ..) = head(&dir);
+
+    let identity = SignatureOptions {
+        allow_fallback_identity: true,
+        interactive: false,
+        sign: false,
+    };
+    assert_eq!(
+        squash_commits(s, 2, None, None, &identity, true).unwrap(),
+        newest
+    );
+    assert_eq!(head(&dir).0, newest);
+
+    let err = squash_commits(s, 9, None, None, &identity, false).unwrap_err();
+    assert!(err.to_string().contains("cannot squash 9"), "{}", err);
+
+    let squashed = squash_commits(s, 2, None, None, &identity, false).unwrap();
+    let (id, author_time, _, author, message) = head(&dir);
+    assert_eq!(id, squashed);
+    assert_eq!(author_time, 1_000_000_000);
+    assert_eq!(author, "Old Author");
+    assert_eq!(message, "Add b\n\nAdd c");
+    let repo = Repository::open(&dir).unwrap();
+    let commit = repo.find_commit(squashed).unwrap();
+    assert_eq!(commit.parent_ids().collect::<Vec<_>>(), vec![base]);
+    let tree = commit.tree().unwrap();
+    assert!(tree.get_path(Path::new("b.txt")).is_ok());
+    assert!(tree.get_path(Path::new("c.txt")).is_ok());
+}
@@ -0,0 +1,75 @@
+use clap::Parser;
+use mdcode::*;
+#[cfg(not(coverage))]
+use tempfile::tempdir;
+
+#[test]
+fn test_yes_flag_parses_short_and_long() {
+    for flag in ["-y", "--yes"] {
+        let cli = Cli::try_parse_from(["mdcode", flag, "update", "."]).unwrap();
+        assert!(cli.yes);
+    }
+    assert!(!Cli::try_parse_from(["mdcode", "update", "."]).unwrap().yes);
+}
+
+// The binary is a no-op under coverage builds.
+#[cfg(not(coverage))]
+#[test]
+fn test_yes_skips_commit_message_prompt() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    let s = repo.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::write(repo.join("one.rs"), "fn one() {}\n").unwrap();
+
+    // A message waiting on stdin would be used if update still prompted.
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mdcode"))
+        .args(["--yes", "update", s])
+        .env("GIT_AUTHOR_NAME", "t")
+        .env("GIT_AUTHOR_EMAIL", "t@example.com")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"typed message\n")
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("Enter commit message"));
+
+    let repo = git2::Repository::open(&repo).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message(), Some("Updated files"));
+}
+
+#[cfg(not(coverage))]
+#[test]
+fn test_yes_tag_without_version_errors_instead_of_prompting() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    let s = repo.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let cli = Cli::try_parse_from(["mdcode", "-y", "tag", s, "--no-push"]).unwrap();
+    let err = execute_cli(cli).unwrap_err();
+    assert!(err.to_string().contains("pass --version"), "{}", err);
+}
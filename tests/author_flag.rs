@@ -0,0 +1,172 @@
+use clap::Parser;
+use git2::Repository;
+use mdcode::*;
+use tempfile::tempdir;
+
+fn head_author(dir: &std::path::Path) -> (String, String) {
+    let repo = Repository::open(dir).unwrap();
+    let commit = repo.head().unwrap().peel_to_commit().unwrap();
+    let author = commit.author();
+    (
+        author.name().unwrap().to_string(),
+        author.email().unwrap().to_string(),
+    )
+}
+
+fn pair(name: &str, email: &str) -> (String, String) {
+    (name.to_string(), email.to_string())
+}
+
+#[test]
+fn test_parse_author() {
+    assert_eq!(
+        parse_author("Ada Lovelace <ada@example.com>").unwrap(),
+        pair("Ada Lovelace", "ada@example.com")
+    );
+    assert_eq!(
+        parse_author("  Ada <ada@example.com> ").unwrap(),
+        pair("Ada", "ada@example.com")
+    );
+    for bad in ["Ada", "<ada@example.com>", "Ada <>", "Ada ada@example.com>"] {
+        let err = parse_author(bad).unwrap_err();
+        assert!(
+            err.to_string().contains("expected \"Name <email>\""),
+            "{}",
+            bad
+        );
+    }
+}
+
+#[test]
+#[serial_test::serial]
+fn test_author_flag_takes_precedence_over_env() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    std::env::set_var("GIT_AUTHOR_NAME", "Env User");
+    std::env::set_var("GIT_AUTHOR_EMAIL", "env@example.com");
+
+    new_repository_with_options(
+        s,
+        false,
+        50,
+        Some("Flag User <flag@example.com>"),
+        &SignatureOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(head_author(&dir), pair("Flag User", "flag@example.com"));
+
+    std::fs::write(dir.join("a.txt"), "a").unwrap();
+    let opts = UpdateOptions {
+        author: Some("Other Flag <other@example.com>".into()),
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, Some("a"), 50, &opts).unwrap();
+    assert_eq!(head_author(&dir), pair("Other Flag", "other@example.com"));
+
+    // Without the flag the env vars win again.
+    std::fs::write(dir.join("b.txt"), "b").unwrap();
+    update_repository(s, false, Some("b"), 50).unwrap();
+    assert_eq!(head_author(&dir), pair("Env User", "env@example.com"));
+
+    // The tagger follows --author as well.
+    let cli = Cli::try_parse_from([
+        "mdcode",
+        "tag",
+        s,
+        "--version",
+        "1.0.0",
+        "--no-push",
+        "--author",
+        "Release Bot <bot@example.com>",
+    ])
+    .unwrap();
+    execute_cli(cli).unwrap();
+    let repo = Repository::open(&dir).unwrap();
+    let tag = repo
+        .find_reference("refs/tags/v1.0.0")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    let tagger = tag.tagger().unwrap();
+    assert_eq!(tagger.name(), Some("Release Bot"));
+    assert_eq!(tagger.email(), Some("bot@example.com"));
+
+    std::env::remove_var("GIT_AUTHOR_NAME");
+    std::env::remove_var("GIT_AUTHOR_EMAIL");
+}
+
+#[test]
+#[serial_test::serial]
+fn test_set_author_persists_repo_local_identity() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    let cli = Cli::try_parse_from([
+        "mdcode",
+        "config",
+        s,
+        "--set-author",
+        "Repo Owner <owner@example.com>",
+    ])
+    .unwrap();
+    execute_cli(cli).unwrap();
+    let repo = Repository::open(&dir).unwrap();
+    let local = repo
+        .config()
+        .unwrap()
+        .open_level(git2::ConfigLevel::Local)
+        .unwrap();
+    assert_eq!(local.get_string("user.name").unwrap(), "Repo Owner");
+    assert_eq!(local.get_string("user.email").unwrap(), "owner@example.com");
+
+    for k in [
+        "GIT_AUTHOR_NAME",
+        "GIT_AUTHOR_EMAIL",
+        "GIT_COMMITTER_NAME",
+        "GIT_COMMITTER_EMAIL",
+    ] {
+        std::env::remove_var(k);
+    }
+    std::fs::write(dir.join("a.txt"), "a").unwrap();
+    update_repository(s, false, Some("a"), 50).unwrap();
+    assert_eq!(head_author(&dir), pair("Repo Owner", "owner@example.com"));
+
+    assert!(set_repo_author(s, "not an author", false).is_err());
+}
+
+#[test]
+fn test_fallback_warning_only_for_fallback_identity() {
+    let warning = fallback_identity_warning(FALLBACK_SIGNATURE_SOURCE).unwrap();
+    assert!(warning.contains("mdcode <mdcode@example.com>"));
+    assert!(warning.contains("--set-author"));
+    for source in [
+        "--author",
+        "env:GIT_AUTHOR_NAME/GIT_AUTHOR_EMAIL",
+        "git config (repo/global)",
+        "git config (global)",
+    ] {
+        assert_eq!(fallback_identity_warning(source), None, "{}", source);
+    }
+
+    let t = tempdir().unwrap();
+    let repo = Repository::init(t.path()).unwrap();
+    let (sig, source) = resolve_commit_signature(
+        &repo,
+        Some("Ada <ada@example.com>"),
+        &SignatureOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(sig.name(), Some("Ada"));
+    assert_eq!(fallback_identity_warning(&source), None);
+}
@@ -0,0 +1,76 @@
+use clap::Parser;
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let ok = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()
+        .unwrap()
+        .success();
+    assert!(ok, "git {:?} failed", args);
+}
+
+#[test]
+fn test_biggest_lists_largest_blobs_first() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let d = tmp.path();
+    git(d, &["init", "-q"]);
+    std::fs::create_dir(d.join("assets")).unwrap();
+    std::fs::write(d.join("small.txt"), vec![b'a'; 10]).unwrap();
+    std::fs::write(d.join("assets/huge.bin"), vec![0u8; 5000]).unwrap();
+    std::fs::write(d.join("medium.rs"), vec![b'b'; 300]).unwrap();
+    std::fs::write(d.join("also_medium.rs"), vec![b'c'; 300]).unwrap();
+    git(d, &["add", "."]);
+    git(d, &["commit", "-q", "-m", "sizes"]);
+    // Working-tree changes do not count; only HEAD does.
+    std::fs::write(d.join("small.txt"), vec![b'a'; 90_000]).unwrap();
+
+    let dir = d.to_str().unwrap();
+    let all = largest_files(dir, 10).unwrap();
+    assert_eq!(
+        all,
+        vec![
+            ("assets/huge.bin".to_string(), 5000),
+            ("also_medium.rs".to_string(), 300),
+            ("medium.rs".to_string(), 300),
+            ("small.txt".to_string(), 10),
+        ]
+    );
+    let top = biggest_command(dir, 2).unwrap();
+    assert_eq!(top, all[..2].to_vec());
+}
+
+#[test]
+fn test_biggest_cli_default_count() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let d = tmp.path();
+    git(d, &["init", "-q"]);
+    for i in 0..12 {
+        std::fs::write(d.join(format!("f{:02}.txt", i)), vec![b'x'; 100 + i]).unwrap();
+    }
+    git(d, &["add", "."]);
+    git(d, &["commit", "-q", "-m", "files"]);
+    let cli = Cli::try_parse_from(["mdcode", "biggest", d.to_str().unwrap()]).unwrap();
+    match &cli.command {
+        Commands::Biggest { count, .. } => assert_eq!(*count, 10),
+        _ => panic!("expected biggest"),
+    }
+    execute_cli(cli).unwrap();
+    assert_eq!(
+        largest_files(d.to_str().unwrap(), 10).unwrap()[0].0,
+        "f11.txt"
+    );
+}
@@ -0,0 +1,135 @@
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+/// Commit everything as `name`, dated `day` January 2024 so commit indices are unambiguous.
+fn commit_as(dir: &str, name: &str, message: &str, day: u32) {
+    let email = format!("{}@example.com", name.to_lowercase());
+    let date = format!("2024-01-{:02}T12:00:00+00:00", day);
+    for args in [vec!["add", "-A"], vec!["commit", "-q", "-m", message]] {
+        assert!(Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", &format!("user.name={}", name)])
+            .args(["-c", &format!("user.email={}", email)])
+            .args(&args)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .status()
+            .unwrap()
+            .success());
+    }
+}
+
+fn regions(hunks: &[BlameHunk]) -> Vec<(String, usize, usize)> {
+    hunks
+        .iter()
+        .map(|h| {
+            let name = h.author.split(' ').next().unwrap().to_string();
+            (name, h.start_line, h.end_line)
+        })
+        .collect()
+}
+
+#[test]
+fn test_blame_attributes_regions_to_three_authors() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path();
+    let s = dir.to_str().unwrap();
+    git2::Repository::init(dir).unwrap();
+
+    std::fs::write(dir.join("app.rs"), "a1\na2\na3\n").unwrap();
+    commit_as(s, "Alice", "Start app", 1);
+    std::fs::write(dir.join("app.rs"), "a1\na2\na3\nb4\nb5\n").unwrap();
+    commit_as(s, "Bob", "Extend app", 2);
+    std::fs::write(dir.join("app.rs"), "a1\nc2\na3\nb4\nb5\n").unwrap();
+    commit_as(s, "Carol", "Fix line 2", 3);
+
+    let hunks = blame_hunks(s, "app.rs", None).unwrap();
+    assert_eq!(
+        regions(&hunks),
+        vec![
+            ("Alice".into(), 1, 1),
+            ("Carol".into(), 2, 2),
+            ("Alice".into(), 3, 3),
+            ("Bob".into(), 4, 5),
+        ]
+    );
+    assert_eq!(hunks[3].author, "Bob <bob@example.com>");
+    assert_eq!(hunks[3].lines(), 2);
+
+    let summary = blame_summary(&hunks);
+    let shares: Vec<(&str, usize, f64)> = summary
+        .iter()
+        .map(|a| (a.author.as_str(), a.lines, a.percent))
+        .collect();
+    assert_eq!(
+        shares,
+        vec![
+            ("Alice <alice@example.com>", 2, 40.0),
+            ("Bob <bob@example.com>", 2, 40.0),
+            ("Carol <carol@example.com>", 1, 20.0),
+        ]
+    );
+
+    // --rev uses the diff selector semantics: index 1 is the commit before HEAD.
+    let older = blame_hunks(s, "./app.rs", Some("1")).unwrap();
+    assert_eq!(
+        regions(&older),
+        vec![("Alice".into(), 1, 3), ("Bob".into(), 4, 5)]
+    );
+    let oldest = blame_hunks(s, "app.rs", Some("HEAD~2")).unwrap();
+    assert_eq!(regions(&oldest), vec![("Alice".into(), 1, 3)]);
+
+    let cli = Cli {
+        command: Commands::Blame {
+            directory: s.to_string(),
+            path: "app.rs".into(),
+            summary: true,
+            rev: None,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
+
+#[test]
+fn test_blame_errors_for_missing_binary_and_workdir() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path();
+    let s = dir.to_str().unwrap();
+    git2::Repository::init(dir).unwrap();
+    std::fs::create_dir_all(dir.join("assets")).unwrap();
+    std::fs::write(dir.join("assets/logo.bin"), [0u8, 159, 146, 150, 0, 1]).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    commit_as(s, "Alice", "Add files", 1);
+    std::fs::write(dir.join("new.txt"), "uncommitted\n").unwrap();
+
+    let err = |path: &str, rev: Option<&str>| blame_hunks(s, path, rev).unwrap_err().to_string();
+    assert_eq!(err("new.txt", None), "'new.txt' does not exist in HEAD");
+    assert_eq!(err("a.txt", Some("5")), "invalid repo index '5'");
+    assert!(err("assets/logo.bin", None).contains("is a binary file"));
+    assert_eq!(err("assets", None), "'assets' is not a file in HEAD");
+    assert!(err("a.txt", Some("L")).contains("not supported"));
+}
@@ -0,0 +1,94 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+fn setup_repo() -> (tempfile::TempDir, std::path::PathBuf) {
+    let tmp = tempdir().unwrap();
+    let repo = tmp.path().join("r");
+    std::fs::create_dir_all(repo.join("src")).unwrap();
+    std::fs::write(repo.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+    new_repository(repo.to_str().unwrap(), false, 50).unwrap();
+    std::fs::create_dir_all(repo.join("target").join("debug")).unwrap();
+    std::fs::write(
+        repo.join("target").join("debug").join("app"),
+        vec![0u8; 2048],
+    )
+    .unwrap();
+    std::fs::write(repo.join("build.log"), "log output\n").unwrap();
+    (tmp, repo)
+}
+
+#[test]
+fn test_clean_lists_without_force() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let (_tmp, repo) = setup_repo();
+    let entries = clean_repository(repo.to_str().unwrap(), false, &[]).unwrap();
+    let paths: Vec<_> = entries
+        .iter()
+        .map(|e| e.path.to_string_lossy().to_string())
+        .collect();
+    assert_eq!(paths, vec!["build.log".to_string(), "target".to_string()]);
+    assert!(entries.iter().find(|e| e.is_dir).unwrap().size >= 2048);
+    assert!(repo.join("target").exists());
+    assert!(repo.join("build.log").exists());
+}
+
+#[test]
+fn test_clean_force_removes_only_ignored() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let (_tmp, repo) = setup_repo();
+    clean_repository(repo.to_str().unwrap(), true, &[]).unwrap();
+    assert!(!repo.join("target").exists());
+    assert!(!repo.join("build.log").exists());
+    assert!(repo.join("src").join("main.rs").exists());
+    assert!(repo.join(".gitignore").exists());
+}
+
+#[test]
+fn test_clean_keep_protects_matching_paths() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let (_tmp, repo) = setup_repo();
+    clean_repository(repo.to_str().unwrap(), true, &["*.log".to_string()]).unwrap();
+    assert!(!repo.join("target").exists());
+    assert!(repo.join("build.log").exists());
+}
+
+#[test]
+fn test_clean_keep_protects_paths_inside_ignored_dirs() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let (_tmp, repo) = setup_repo();
+    std::fs::create_dir_all(repo.join("target").join("keepme")).unwrap();
+    std::fs::write(repo.join("target").join("keepme").join("x"), "x").unwrap();
+    let s = repo.to_str().unwrap();
+    let keep = ["target/keepme".to_string()];
+
+    let entries = clean_repository(s, false, &keep).unwrap();
+    let paths: Vec<_> = entries
+        .iter()
+        .map(|e| e.path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    assert_eq!(paths, vec!["build.log", "target/debug"]);
+
+    clean_repository(s, true, &keep).unwrap();
+    assert!(repo.join("target").join("keepme").join("x").exists());
+    assert!(!repo.join("target").join("debug").exists());
+    assert!(!repo.join("build.log").exists());
+}
+
+#[test]
+fn test_format_size_units() {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1536), "1.5 KB");
+    assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+}
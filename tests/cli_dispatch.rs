@@ -15,9 +15,27 @@ fn test_execute_cli_dispatches_core_commands() {
     let cli_new = Cli {
         command: Commands::New {
             directory: repo_str.clone(),
+            gc: false,
+            author: None,
+            allow_default_author: true,
+            adopt: false,
+            keep_empty_dirs: true,
+            lfs: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli_new).unwrap();
     assert!(repo_path.join(".git").exists());
@@ -26,20 +44,75 @@ fn test_execute_cli_dispatches_core_commands() {
     std::fs::write(repo_path.join("cli_dispatch.txt"), "pending change").unwrap();
     let cli_update = Cli {
         command: Commands::Update {
-            directory: repo_str.clone(),
+            directory: Some(repo_str.clone()),
+            allow_merge_commit: false,
+            interactive: false,
+            rename_threshold: DEFAULT_RENAME_THRESHOLD,
+            gc: false,
+            author: None,
+            allow_default_author: false,
+            no_secret_scan: false,
+            only: vec![],
+            message: None,
+            auto_message: false,
+            allow_empty: false,
+            keep_empty_dirs: false,
+            amend: false,
+            max_new_files: None,
+            signoff: false,
+            trailer: Vec::new(),
+            detached_ok: false,
+            branch: None,
+            recursive: false,
         },
         dry_run: true,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli_update).unwrap();
 
     // info
     let cli_info = Cli {
         command: Commands::Info {
-            directory: repo_str.clone(),
+            directory: Some(repo_str.clone()),
+            rename_threshold: DEFAULT_RENAME_THRESHOLD,
+            since_tag: None,
+            limit: None,
+            oldest_first: false,
+            utc: false,
+            local: false,
+            relative: false,
+            files: FileListMode::Summary,
+            paginate: false,
+            graph: false,
+            remote: None,
+            recursive: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli_info).unwrap();
 
@@ -48,9 +121,28 @@ fn test_execute_cli_dispatches_core_commands() {
         command: Commands::Diff {
             directory: repo_str.clone(),
             versions: Vec::new(),
+            name_only: false,
+            in_place: false,
+            no_fetch: false,
+            against: None,
+            against_version: None,
+            path: None,
+            export_ignore: false,
         },
         dry_run: true,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli_diff).unwrap();
 
@@ -71,49 +163,114 @@ fn test_execute_cli_dispatches_core_commands() {
     // push
     let cli_push = Cli {
         command: Commands::GhPush {
-            directory: repo_str.clone(),
+            directory: Some(repo_str.clone()),
             remote: "origin".to_string(),
+            no_set_upstream: false,
+            all_branches: false,
+            tags: false,
+            follow_tags: false,
+            insecure: false,
+            set_default_branch: false,
+            recursive: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli_push).unwrap();
 
     // fetch
     let cli_fetch = Cli {
         command: Commands::GhFetch {
-            directory: repo_str.clone(),
+            directory: Some(repo_str.clone()),
             remote: "origin".to_string(),
+            prune: false,
+            insecure: false,
+            recursive: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli_fetch).unwrap();
 
     // sync
     let cli_sync = Cli {
         command: Commands::GhSync {
-            directory: repo_str.clone(),
+            directory: Some(repo_str.clone()),
             remote: "origin".to_string(),
+            all: false,
+            ff_only: false,
+            insecure: false,
+            recursive: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli_sync).unwrap();
 
     // tag (dry-run/no-push) so it doesn't need network
     let cli_tag = Cli {
         command: Commands::Tag {
-            directory: repo_str.clone(),
+            directory: Some(repo_str.clone()),
             version: Some("1.2.3".to_string()),
             message: None,
+            message_file: None,
             no_push: true,
+            strict_push: false,
             remote: "origin".to_string(),
             force: false,
             allow_dirty: true,
+            author: None,
         },
         dry_run: true,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli_tag).unwrap();
 }
@@ -38,9 +38,28 @@ fn test_execute_cli_diff_numeric_and_two_index() {
         command: Commands::Diff {
             directory: s.clone(),
             versions: vec!["1".into()],
+            name_only: false,
+            in_place: false,
+            no_fetch: false,
+            against: None,
+            against_version: None,
+            path: None,
+            export_ignore: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli1).unwrap();
     // two indices
@@ -48,9 +67,28 @@ fn test_execute_cli_diff_numeric_and_two_index() {
         command: Commands::Diff {
             directory: s.clone(),
             versions: vec!["2".into(), "1".into()],
+            name_only: false,
+            in_place: false,
+            no_fetch: false,
+            against: None,
+            against_version: None,
+            path: None,
+            export_ignore: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli2).unwrap();
     std::env::remove_var("MDCODE_DIFF_TOOL");
@@ -86,9 +124,28 @@ fn test_execute_cli_diff_l_current_launches_tool() {
         command: Commands::Diff {
             directory: s.clone(),
             versions: vec!["L".into()],
+            name_only: false,
+            in_place: false,
+            no_fetch: false,
+            against: None,
+            against_version: None,
+            path: None,
+            export_ignore: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli).unwrap();
     std::env::remove_var("MDCODE_DIFF_TOOL");
@@ -124,9 +181,28 @@ fn test_execute_cli_diff_h_vs_index_launches_tool() {
         command: Commands::Diff {
             directory: s.clone(),
             versions: vec!["H".into(), "0".into()],
+            name_only: false,
+            in_place: false,
+            no_fetch: false,
+            against: None,
+            against_version: None,
+            path: None,
+            export_ignore: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli).unwrap();
     std::env::remove_var("MDCODE_DIFF_TOOL");
@@ -154,11 +230,27 @@ fn test_execute_cli_sync_missing_remote_branch() {
         .unwrap();
     let cli = Cli {
         command: Commands::GhSync {
-            directory: s.clone(),
+            directory: Some(s.clone()),
             remote: "origin".into(),
+            all: false,
+            ff_only: false,
+            insecure: false,
+            recursive: false,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli).unwrap();
 }
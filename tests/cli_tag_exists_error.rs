@@ -29,31 +29,61 @@ fn test_execute_cli_tag_exists_error_no_force() {
     // First tag creation succeeds (no push)
     let cli1 = Cli {
         command: Commands::Tag {
-            directory: s.clone(),
+            directory: Some(s.clone()),
             version: Some("1.2.3".into()),
             message: None,
+            message_file: None,
             no_push: true,
+            strict_push: false,
             remote: "origin".into(),
             force: false,
             allow_dirty: true,
+            author: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli1).unwrap();
     // Second creation without --force should error
     let cli2 = Cli {
         command: Commands::Tag {
-            directory: s.clone(),
+            directory: Some(s.clone()),
             version: Some("1.2.3".into()),
             message: None,
+            message_file: None,
             no_push: true,
+            strict_push: false,
             remote: "origin".into(),
             force: false,
             allow_dirty: true,
+            author: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     let e = execute_cli(cli2).unwrap_err();
     assert!(e.to_string().contains("already exists"));
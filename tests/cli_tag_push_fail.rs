@@ -58,16 +58,31 @@ fn test_execute_cli_tag_push_failure_with_shim() {
 
     let cli = Cli {
         command: Commands::Tag {
-            directory: s.clone(),
+            directory: Some(s.clone()),
             version: Some("3.4.5".into()),
             message: None,
+            message_file: None,
             no_push: false,
+            strict_push: false,
             remote: "origin".into(),
             force: false,
             allow_dirty: true,
+            author: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     let err = execute_cli(cli).unwrap_err();
     assert!(err.to_string().contains("failed to push tag"));
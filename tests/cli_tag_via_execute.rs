@@ -11,19 +11,34 @@ fn test_execute_cli_tag_missing_remote_errors() {
     let repo = tmp.path().join("r");
     let s = repo.to_str().unwrap().to_string();
     new_repository(&s, false, 50).unwrap();
-    // No remote configured: push should error
+    // No remote configured: with --strict-push the push should error
     let cli = Cli {
         command: Commands::Tag {
-            directory: s.clone(),
+            directory: Some(s.clone()),
             version: Some("0.1.0".into()),
             message: None,
+            message_file: None,
             no_push: false,
+            strict_push: true,
             remote: "origin".into(),
             force: false,
             allow_dirty: true,
+            author: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     let err = execute_cli(cli).unwrap_err();
     assert!(
@@ -45,31 +60,61 @@ fn test_execute_cli_tag_force_overwrite_no_push_success() {
     // First create tag via CLI with no_push
     let cli1 = Cli {
         command: Commands::Tag {
-            directory: s.clone(),
+            directory: Some(s.clone()),
             version: Some("1.0.0".into()),
             message: None,
+            message_file: None,
             no_push: true,
+            strict_push: false,
             remote: "origin".into(),
             force: false,
             allow_dirty: true,
+            author: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli1).unwrap();
     // Force overwrite should succeed (still no push)
     let cli2 = Cli {
         command: Commands::Tag {
-            directory: s.clone(),
+            directory: Some(s.clone()),
             version: Some("1.0.0".into()),
             message: None,
+            message_file: None,
             no_push: true,
+            strict_push: false,
             remote: "origin".into(),
             force: true,
             allow_dirty: true,
+            author: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli2).unwrap();
 }
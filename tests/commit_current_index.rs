@@ -0,0 +1,40 @@
+use git2::{Repository, Signature};
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn test_commit_current_index_commits_staged_files() {
+    let tmp = tempdir().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let author = Signature::now("Author", "author@example.com").unwrap();
+    let committer = Signature::now("Committer", "committer@example.com").unwrap();
+
+    std::fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("a.txt")).unwrap();
+    index.write().unwrap();
+
+    // Unborn branch: root commit without parents.
+    let first = commit_current_index(&repo, "first", &author, &committer, false).unwrap();
+    let commit = repo.find_commit(first).unwrap();
+    assert_eq!(repo.head().unwrap().target(), Some(first));
+    assert_eq!(commit.parent_count(), 0);
+    assert_eq!(commit.message(), Some("first"));
+    assert_eq!(commit.author().name(), Some("Author"));
+    assert_eq!(commit.committer().email(), Some("committer@example.com"));
+    assert!(commit.tree().unwrap().get_name("a.txt").is_some());
+
+    // Next commit is parented on HEAD and only includes what is staged.
+    std::fs::write(tmp.path().join("b.txt"), "b\n").unwrap();
+    std::fs::write(tmp.path().join("unstaged.txt"), "u\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("b.txt")).unwrap();
+    index.write().unwrap();
+    let second = commit_current_index(&repo, "second", &author, &author, false).unwrap();
+    let commit = repo.find_commit(second).unwrap();
+    assert_eq!(commit.parent_ids().collect::<Vec<_>>(), vec![first]);
+    let tree = commit.tree().unwrap();
+    assert!(tree.get_name("b.txt").is_some());
+    assert!(tree.get_name("unstaged.txt").is_none());
+}
@@ -0,0 +1,77 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+fn loose_object_dirs(repo: &std::path::Path) -> usize {
+    std::fs::read_dir(repo.join(".git/objects"))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().len() == 2)
+        .count()
+}
+
+#[test]
+fn test_new_with_gc_packs_fresh_import() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("import");
+    std::fs::create_dir_all(repo.join("src")).unwrap();
+    for i in 0..40 {
+        std::fs::write(
+            repo.join("src").join(format!("m{}.rs", i)),
+            format!("pub fn f{}() -> u32 {{ {} }}\n", i, i).repeat(50),
+        )
+        .unwrap();
+    }
+    let s = repo.to_str().unwrap();
+    execute_cli(Cli {
+        command: Commands::New {
+            directory: s.to_string(),
+            gc: true,
+            author: None,
+            allow_default_author: true,
+            adopt: false,
+            keep_empty_dirs: true,
+            lfs: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: true,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    })
+    .unwrap();
+    assert_eq!(loose_object_dirs(&repo), 0);
+    assert!(std::fs::read_dir(repo.join(".git/objects/pack"))
+        .unwrap()
+        .any(|e| e.unwrap().path().extension().is_some_and(|x| x == "pack")));
+    // The history is intact after packing.
+    assert!(git2::Repository::open(&repo).unwrap().head().is_ok());
+
+    // Packing an already packed store is a no-op size-wise.
+    let (before, after) = compress_objects(s, false).unwrap().unwrap();
+    assert!(after > 0 && after <= before, "{} -> {}", before, after);
+}
+
+#[test]
+fn test_compress_objects_dry_run_and_missing_repo() {
+    let t = tempdir().unwrap();
+    let missing = t.path().join("nope");
+    let m = missing.to_str().unwrap();
+    assert_eq!(compress_objects(m, true).unwrap(), None);
+    assert!(compress_objects(m, false)
+        .unwrap_err()
+        .to_string()
+        .contains("No git repository"));
+}
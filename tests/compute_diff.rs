@@ -0,0 +1,130 @@
+use mdcode::*;
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::tempdir;
+
+// Stage everything, deletions included, and commit via the git CLI.
+fn commit_all(dir: &str, msg: &str) {
+    assert!(Command::new("git")
+        .args(["-C", dir, "add", "-A"])
+        .status()
+        .unwrap()
+        .success());
+    assert!(Command::new("git")
+        .args(["-C", dir, "commit", "-q", "-m", msg])
+        .status()
+        .unwrap()
+        .success());
+}
+
+fn find<'a>(changes: &'a [FileChange], path: &str) -> &'a FileChange {
+    changes
+        .iter()
+        .find(|c| c.path == std::path::Path::new(path))
+        .unwrap_or_else(|| panic!("no change for {}: {:?}", path, changes))
+}
+
+#[test]
+fn test_compute_diff_add_modify_delete_rename_between_commits() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    let s = repo.to_str().unwrap();
+    std::fs::create_dir_all(&repo).unwrap();
+    let body: String = (0..20).map(|i| format!("line {}\n", i)).collect();
+    std::fs::write(repo.join("keep.rs"), "fn a() {}\n").unwrap();
+    std::fs::write(repo.join("gone.txt"), "bye\n").unwrap();
+    std::fs::write(repo.join("old_name.py"), &body).unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    std::fs::write(repo.join("keep.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+    std::fs::remove_file(repo.join("gone.txt")).unwrap();
+    std::fs::rename(repo.join("old_name.py"), repo.join("new_name.py")).unwrap();
+    std::fs::write(repo.join("fresh.md"), "# hi\nthere\n").unwrap();
+    commit_all(s, "changes");
+
+    let changes = compute_diff(s, "1", "0").unwrap();
+    let keep = find(&changes, "keep.rs");
+    assert_eq!(keep.status, ChangeKind::Modified);
+    assert_eq!((keep.insertions, keep.deletions), (1, 0));
+    assert_eq!(keep.category, Some("Rust"));
+    let gone = find(&changes, "gone.txt");
+    assert_eq!(gone.status, ChangeKind::Deleted);
+    assert_eq!(gone.deletions, 1);
+    let fresh = find(&changes, "fresh.md");
+    assert_eq!(fresh.status, ChangeKind::Added);
+    assert_eq!(fresh.insertions, 2);
+    let renamed = find(&changes, "new_name.py");
+    assert_eq!(renamed.status, ChangeKind::Renamed);
+    assert_eq!(renamed.old_path, Some(PathBuf::from("old_name.py")));
+    // Whether .gitignore shows up depends on new_repository having tracked it.
+    let others: Vec<_> = changes
+        .iter()
+        .filter(|c| c.path != std::path::Path::new(".gitignore"))
+        .collect();
+    assert_eq!(others.len(), 4, "{:?}", changes);
+
+    // Refs and revspecs work as selectors too.
+    assert_eq!(compute_diff(s, "HEAD~1", "HEAD").unwrap(), changes);
+    // A 100% threshold still pairs an identical rename.
+    let strict = DiffOptions {
+        rename_threshold: 100,
+        ..DiffOptions::default()
+    };
+    let strict_changes = compute_diff_with_options(s, "1", "0", &strict).unwrap();
+    assert_eq!(
+        find(&strict_changes, "new_name.py").status,
+        ChangeKind::Renamed
+    );
+}
+
+#[test]
+fn test_compute_diff_rename_threshold_and_working_tree() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    let s = repo.to_str().unwrap();
+    std::fs::create_dir_all(&repo).unwrap();
+    let body: String = (0..10).map(|i| format!("line {}\n", i)).collect();
+    std::fs::write(repo.join("a.txt"), &body).unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    // Move and edit 3 of 10 lines: similar enough for the default, not for 95%.
+    std::fs::remove_file(repo.join("a.txt")).unwrap();
+    let edited = body
+        .replacen("line 1\n", "one\n", 1)
+        .replacen("line 2\n", "two\n", 1);
+    let edited = edited.replacen("line 3\n", "three\n", 1);
+    std::fs::write(repo.join("b.txt"), edited).unwrap();
+    commit_all(s, "move");
+
+    let loose = compute_diff(s, "1", "0").unwrap();
+    assert_eq!(find(&loose, "b.txt").status, ChangeKind::Renamed);
+    let strict = DiffOptions {
+        rename_threshold: 95,
+        ..DiffOptions::default()
+    };
+    let strict = compute_diff_with_options(s, "1", "0", &strict).unwrap();
+    assert_eq!(find(&strict, "b.txt").status, ChangeKind::Added);
+    assert_eq!(find(&strict, "a.txt").status, ChangeKind::Deleted);
+
+    // Working tree ('L') as the after side includes untracked files.
+    std::fs::write(repo.join("c.txt"), "c\n").unwrap();
+    let wt = compute_diff(s, "0", "L").unwrap();
+    assert_eq!(wt.len(), 1);
+    assert_eq!(find(&wt, "c.txt").status, ChangeKind::Added);
+
+    assert!(compute_diff(s, "L", "0").is_err());
+    assert!(compute_diff(s, "no-such-ref", "0").is_err());
+    assert_eq!(
+        diff_selectors(&["L".to_string()]),
+        ("H".to_string(), "L".to_string())
+    );
+    assert_eq!(diff_selectors(&[]), ("0".to_string(), "L".to_string()));
+}
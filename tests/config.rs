@@ -0,0 +1,68 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_load_config_reads_each_table() {
+    let t = tempdir().unwrap();
+    let s = t.path().to_str().unwrap();
+    let empty = load_config(s).unwrap();
+    assert_eq!(empty.path, t.path().join(MDCODE_CONFIG_FILE));
+    assert!(empty.get("size_caps", "default").is_none());
+
+    std::fs::write(
+        t.path().join(MDCODE_CONFIG_FILE),
+        "[size_caps]\ndefault = 5\n\n[update]\nmax_new_files = 9\n",
+    )
+    .unwrap();
+    let config = load_config(s).unwrap();
+    assert_eq!(
+        config
+            .get("size_caps", "default")
+            .and_then(|v| v.as_integer()),
+        Some(5)
+    );
+    assert_eq!(
+        config
+            .get("update", "max_new_files")
+            .and_then(|v| v.as_integer()),
+        Some(9)
+    );
+    assert!(config.get("lfs", "patterns").is_none());
+    let err = config.invalid("x must be y").to_string();
+    assert!(err.starts_with("invalid "), "{}", err);
+    assert!(err.ends_with(".mdcode.toml: x must be y"), "{}", err);
+
+    std::fs::write(t.path().join(MDCODE_CONFIG_FILE), "[size_caps\n").unwrap();
+    let err = load_config(s).unwrap_err().to_string();
+    assert!(err.contains(".mdcode.toml"), "{}", err);
+}
+
+#[test]
+fn test_glob_list_and_anchored_glob() {
+    let t = tempdir().unwrap();
+    let s = t.path().to_str().unwrap();
+    std::fs::write(
+        t.path().join(MDCODE_CONFIG_FILE),
+        "[scan]\ninclude_hidden = [\".vscode\"]\n\n[lfs]\npatterns = \"*.psd\"\n",
+    )
+    .unwrap();
+    let config = load_config(s).unwrap();
+    assert_eq!(
+        config.glob_list("scan", "include_hidden").unwrap(),
+        vec![".vscode".to_string()]
+    );
+    assert!(config.glob_list("scan", "missing").unwrap().is_empty());
+    let err = config.glob_list("lfs", "patterns").unwrap_err().to_string();
+    assert!(
+        err.ends_with("lfs.patterns must be a list of globs"),
+        "{}",
+        err
+    );
+
+    let name = anchored_glob("*.psd").unwrap().compile_matcher();
+    assert!(name.is_match("a.psd") && name.is_match("art/raw/a.psd"));
+    let rooted = anchored_glob("/assets/*").unwrap().compile_matcher();
+    assert!(rooted.is_match("assets/a.psd"));
+    assert!(!rooted.is_match("assets/raw/a.psd") && !rooted.is_match("x/assets/a.psd"));
+    assert!(anchored_glob("[").is_err());
+}
@@ -0,0 +1,80 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+fn new_cli(dir: &str, config: Option<String>) -> Cli {
+    Cli {
+        command: Commands::New {
+            directory: dir.to_string(),
+            gc: false,
+            author: None,
+            allow_default_author: true,
+            adopt: false,
+            keep_empty_dirs: true,
+            lfs: false,
+        },
+        dry_run: false,
+        max_file_mb: None,
+        no_color: false,
+        yes: true,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    }
+}
+
+#[test]
+#[serial_test::serial]
+fn test_config_flag_reads_caps_from_another_path() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let repo = tmp.path().join("repo");
+    std::fs::create_dir_all(&repo).unwrap();
+    std::fs::write(repo.join("small.rs"), "fn a() {}\n").unwrap();
+    std::fs::write(repo.join("big.rs"), vec![b'a'; 2 * 1024 * 1024]).unwrap();
+    let shared = tmp.path().join("ci").join("alt.toml");
+    std::fs::create_dir_all(shared.parent().unwrap()).unwrap();
+    std::fs::write(&shared, "[size_caps]\nImage = 3\ndefault = 1\n").unwrap();
+    let s = repo.to_str().unwrap();
+
+    execute_cli(new_cli(s, Some(shared.to_str().unwrap().to_string()))).unwrap();
+    let r = git2::Repository::open(&repo).unwrap();
+    let tree = r.head().unwrap().peel_to_tree().unwrap();
+    assert!(tree.get_name("small.rs").is_some());
+    assert!(tree.get_name("big.rs").is_none());
+
+    let config = Config::load_file(&shared).unwrap();
+    assert_eq!(config.path, shared);
+    assert_eq!(effective_max_file_mb(None, s, Some(&config)), 1);
+    let caps = SizeCaps::load(s, Some(&config)).unwrap();
+    let cap = caps.cap_for(std::path::Path::new("logo.png"), 1);
+    assert_eq!(
+        caps.source(&cap),
+        format!("size_caps.Image in {}", shared.display())
+    );
+    assert_eq!(effective_max_file_mb(None, s, None), DEFAULT_MAX_FILE_MB);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_config_flag_missing_file_is_an_error() {
+    let tmp = tempdir().unwrap();
+    let missing = tmp.path().join("nope.toml");
+    let err = execute_cli(new_cli(
+        tmp.path().to_str().unwrap(),
+        Some(missing.to_str().unwrap().to_string()),
+    ))
+    .unwrap_err();
+    assert!(err.to_string().contains("does not exist"), "{}", err);
+    let err = Config::load_file(&missing).unwrap_err();
+    assert!(err.to_string().contains("nope.toml"), "{}", err);
+}
@@ -0,0 +1,93 @@
+use clap::Parser;
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_detects_remote_default_branch_mismatch() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("work");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let branch = git2::Repository::open(&dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .shorthand()
+        .unwrap()
+        .to_string();
+
+    // The server's HEAD points at 'trunk', as if the repository was created with a README.
+    let bare = tmp.path().join("server.git");
+    let server = git2::Repository::init_bare(&bare).unwrap();
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(s)
+        .args([
+            "push",
+            "-q",
+            bare.to_str().unwrap(),
+            "HEAD:refs/heads/trunk",
+        ])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    server.set_head("refs/heads/trunk").unwrap();
+    add_remote(s, "origin", bare.to_str().unwrap()).unwrap();
+
+    assert_eq!(
+        remote_default_branch(s, "origin", None).unwrap().as_deref(),
+        Some("trunk")
+    );
+    assert_eq!(
+        default_branch_mismatch(s, "origin", &branch, None)
+            .unwrap()
+            .as_deref(),
+        Some("trunk")
+    );
+    assert_eq!(
+        default_branch_mismatch(s, "origin", "trunk", None).unwrap(),
+        None
+    );
+
+    // The push itself still succeeds; the mismatch is only reported.
+    gh_push(s, "origin").unwrap();
+    assert!(server
+        .find_reference(&format!("refs/heads/{}", branch))
+        .is_ok());
+
+    // A bare path is not a GitHub repository whose default could be changed.
+    let err = set_remote_default_branch(s, "origin", &branch).unwrap_err();
+    assert!(err.to_string().contains("needs a GitHub remote"), "{}", err);
+}
+
+#[test]
+fn test_empty_remote_has_no_default_branch() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("work");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let bare = tmp.path().join("empty.git");
+    git2::Repository::init_bare(&bare).unwrap();
+    add_remote(s, "origin", bare.to_str().unwrap()).unwrap();
+    assert_eq!(remote_default_branch(s, "origin", None).unwrap(), None);
+
+    assert!(Cli::try_parse_from([
+        "mdcode",
+        "gh_push",
+        ".",
+        "--set-default-branch",
+        "--all-branches"
+    ])
+    .is_err());
+}
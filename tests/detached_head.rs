@@ -0,0 +1,97 @@
+use git2::Repository;
+use mdcode::*;
+use tempfile::tempdir;
+
+/// A repository with two commits, HEAD detached at the first one (as after checking out
+/// a tag), and an uncommitted change.
+fn detached_repo(dir: &std::path::Path) -> (Repository, git2::Oid) {
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let repo = Repository::open(dir).unwrap();
+    let first = repo.head().unwrap().peel_to_commit().unwrap().id();
+    std::fs::write(dir.join("lib.rs"), "pub fn a() {}\n").unwrap();
+    update_repository(s, false, Some("Add lib"), 50).unwrap();
+    repo.set_head_detached(first).unwrap();
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    std::fs::write(dir.join("util.rs"), "pub fn u() {}\n").unwrap();
+    (repo, first)
+}
+
+#[test]
+fn test_update_on_detached_head_needs_a_branch() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("r");
+    let s = dir.to_str().unwrap();
+    let (repo, first) = detached_repo(&dir);
+
+    let err =
+        update_repository_with_options(s, false, Some("Add util"), 50, &UpdateOptions::default())
+            .unwrap_err()
+            .to_string();
+    assert!(err.contains("HEAD is detached"), "{}", err);
+    assert!(err.contains("--branch"), "{}", err);
+    assert_eq!(repo.head().unwrap().peel_to_commit().unwrap().id(), first);
+
+    // A branch HEAD is not ahead of is left alone.
+    let taken = UpdateOptions {
+        branch: Some("master".to_string()),
+        ..UpdateOptions::default()
+    };
+    let err = update_repository_with_options(s, false, Some("Add util"), 50, &taken)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("has commits that HEAD does not"), "{}", err);
+
+    let opts = UpdateOptions {
+        branch: Some("temp".to_string()),
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, Some("Add util"), 50, &opts).unwrap();
+    assert!(!repo.head_detached().unwrap());
+    assert_eq!(repo.head().unwrap().shorthand(), Some("temp"));
+    let tip = repo
+        .find_branch("temp", git2::BranchType::Local)
+        .unwrap()
+        .get()
+        .peel_to_commit()
+        .unwrap();
+    assert_eq!(tip.summary(), Some("Add util"));
+    assert_eq!(tip.parent_id(0).unwrap(), first);
+    assert!(tip.tree().unwrap().get_name("util.rs").is_some());
+}
+
+#[test]
+fn test_update_detached_ok_and_push_refusal() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("r");
+    let s = dir.to_str().unwrap();
+    let (repo, first) = detached_repo(&dir);
+    repo.remote("origin", t.path().join("none.git").to_str().unwrap())
+        .unwrap();
+
+    let err = gh_push(s, "origin").unwrap_err().to_string();
+    assert!(err.contains("HEAD is detached"), "{}", err);
+
+    let opts = UpdateOptions {
+        detached_ok: true,
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, Some("Add util"), 50, &opts).unwrap();
+    assert!(repo.head_detached().unwrap());
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.parent_id(0).unwrap(), first);
+
+    use clap::Parser;
+    assert!(
+        Cli::try_parse_from(["mdcode", "update", ".", "--detached-ok", "--branch", "x"]).is_err()
+    );
+}
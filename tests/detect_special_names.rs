@@ -13,4 +13,8 @@ fn test_detect_file_type_special_names() {
         Some("Build Script")
     );
     assert_eq!(detect_file_type(Path::new("CMakeLists.txt")), Some("CMake"));
+    assert_eq!(
+        detect_file_type(Path::new("assets/.gitkeep")),
+        Some("Placeholder")
+    );
 }
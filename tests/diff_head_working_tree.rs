@@ -0,0 +1,72 @@
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let ok = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap()
+        .success();
+    assert!(ok, "git {:?} failed", args);
+}
+
+fn versions(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_diff_h_w_compares_remote_head_with_working_tree() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let bare = tmp.path().join("remote.git");
+    let ok = Command::new("git")
+        .args(["init", "--quiet", "--bare"])
+        .arg(&bare)
+        .status()
+        .unwrap()
+        .success();
+    assert!(ok);
+
+    let work = tmp.path().join("work");
+    let s = work.to_str().unwrap();
+    std::fs::create_dir_all(&work).unwrap();
+    std::fs::write(work.join("a.txt"), "a\n").unwrap();
+    new_repository(s, false, 50).unwrap();
+    add_remote(s, "origin", bare.to_str().unwrap()).unwrap();
+    git(&work, &["push", "--quiet", "-u", "origin", "HEAD"]);
+    git(&work, &["remote", "set-head", "origin", "--auto"]);
+
+    // A local commit origin does not have, plus uncommitted changes on top.
+    std::fs::write(work.join("b.txt"), "b\n").unwrap();
+    update_repository(s, false, Some("Add b"), 50).unwrap();
+    std::fs::write(work.join("a.txt"), "changed\n").unwrap();
+    std::fs::write(work.join("c.txt"), "new\n").unwrap();
+
+    let explicit = diff_name_only(s, &versions(&["H", "W"])).unwrap();
+    // Untracked files are listed too; .gitignore, written by `new`, is one of them.
+    assert_eq!(explicit, vec![".gitignore", "a.txt", "b.txt", "c.txt"]);
+    assert_eq!(diff_name_only(s, &versions(&["L"])).unwrap(), explicit);
+    // `H 0` leaves the uncommitted changes out.
+    assert_eq!(
+        diff_name_only(s, &versions(&["H", "0"])).unwrap(),
+        vec!["b.txt"]
+    );
+    let changes: Vec<String> = compute_diff(s, "H", "W")
+        .unwrap()
+        .iter()
+        .map(|c| c.path.to_string_lossy().into_owned())
+        .collect();
+    // The change listing names the same files.
+    assert_eq!(changes, explicit);
+    // Snapshots only: no diff tool is launched on a dry run.
+    diff_command(s, &versions(&["h", "w"]), true).unwrap();
+
+    let err = compute_diff(s, "W", "H").unwrap_err().to_string();
+    assert!(err.contains("after side"), "{}", err);
+}
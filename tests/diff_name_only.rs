@@ -0,0 +1,79 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_diff_name_only_between_commits_and_worktree() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    std::fs::write(dir.join("b.txt"), "b\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    std::fs::write(dir.join("a.txt"), "a2\n").unwrap();
+    std::fs::write(dir.join("src").join("c.rs"), "fn c() {}\n").unwrap();
+    update_repository(s, false, Some("second"), 50).unwrap();
+
+    let changed = diff_name_only(s, &["1".to_string(), "0".to_string()]).unwrap();
+    assert_eq!(changed, vec!["a.txt".to_string(), "src/c.rs".to_string()]);
+
+    // Working tree vs most recent commit.
+    std::fs::write(dir.join("b.txt"), "b2\n").unwrap();
+    // .gitignore, written by `new`, is untracked and listed like in the full diff.
+    assert_eq!(
+        diff_name_only(s, &[]).unwrap(),
+        vec![".gitignore".to_string(), "b.txt".to_string()]
+    );
+
+    // Untracked files in new directories too.
+    std::fs::write(dir.join("new.rs"), "fn n() {}\n").unwrap();
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    std::fs::write(dir.join("lib").join("d.rs"), "fn d() {}\n").unwrap();
+    assert_eq!(
+        diff_name_only(s, &[]).unwrap(),
+        vec![
+            ".gitignore".to_string(),
+            "b.txt".to_string(),
+            "lib/d.rs".to_string(),
+            "new.rs".to_string()
+        ]
+    );
+
+    // Through the CLI entry point (prints; no tool launch).
+    let cli = Cli {
+        command: Commands::Diff {
+            directory: s.to_string(),
+            versions: vec!["1".into(), "0".into()],
+            name_only: true,
+            in_place: false,
+            no_fetch: false,
+            against: None,
+            against_version: None,
+            path: None,
+            export_ignore: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+
+    let err = diff_name_only(s, &["9".to_string()]).unwrap_err();
+    assert!(err.to_string().contains("invalid repo indexes"));
+}
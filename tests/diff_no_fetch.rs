@@ -0,0 +1,143 @@
+#![cfg(unix)]
+use git2::Repository;
+use mdcode::*;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn which_git() -> String {
+    let out = Command::new("which").arg("git").output().unwrap();
+    assert!(out.status.success());
+    String::from_utf8_lossy(&out.stdout).trim().to_string()
+}
+
+/// `git` shim that fails every network-touching call (`fetch`, `remote show`).
+fn offline_git_shim(bin: &Path, real_git: &str) {
+    std::fs::create_dir_all(bin).unwrap();
+    let shim = bin.join("git");
+    std::fs::write(
+        &shim,
+        format!(
+            "#!/bin/sh\ncase \"$*\" in *fetch*|*\"remote show\"*) echo 'network disabled' >&2; exit 128 ;; esac\nexec {} \"$@\"\n",
+            real_git
+        ),
+    )
+    .unwrap();
+    let mut p = std::fs::metadata(&shim).unwrap().permissions();
+    p.set_mode(0o755);
+    std::fs::set_permissions(&shim, p).unwrap();
+}
+
+fn no_fetch() -> DiffOptions {
+    DiffOptions {
+        no_fetch: true,
+        ..DiffOptions::default()
+    }
+}
+
+fn h_mode() -> Vec<String> {
+    vec!["H".to_string(), "0".to_string()]
+}
+
+#[test]
+#[serial_test::serial]
+fn test_h_mode_resolves_default_branch_locally() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let bare = tmp.path().join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    let seed = tmp.path().join("seed");
+    let seed_s = seed.to_str().unwrap();
+    std::fs::create_dir_all(&seed).unwrap();
+    std::fs::write(seed.join("a.txt"), "a\n").unwrap();
+    new_repository(seed_s, false, 50).unwrap();
+    add_remote(seed_s, "origin", bare.to_str().unwrap()).unwrap();
+    gh_push(seed_s, "origin").unwrap();
+
+    let work = tmp.path().join("work");
+    let work_s = work.to_str().unwrap();
+    let status = Command::new("git")
+        .arg("clone")
+        .arg("--quiet")
+        .arg(&bare)
+        .arg(&work)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    std::fs::write(work.join("local.txt"), "local\n").unwrap();
+    update_repository(work_s, false, Some("local"), 50).unwrap();
+    let branch = Repository::open(&work)
+        .unwrap()
+        .head()
+        .unwrap()
+        .shorthand()
+        .unwrap()
+        .to_string();
+
+    let orig_path = std::env::var("PATH").unwrap();
+    let bin = tmp.path().join("bin");
+    offline_git_shim(&bin, &which_git());
+    std::env::set_var("PATH", format!("{}:{}", bin.display(), orig_path));
+
+    // The network path is really disabled.
+    assert!(diff_name_only(work_s, &h_mode()).is_err());
+
+    // 1. refs/remotes/origin/HEAD (set by clone).
+    let expected = vec!["local.txt".to_string()];
+    assert_eq!(
+        diff_name_only_with_options(work_s, &h_mode(), &no_fetch()).unwrap(),
+        expected
+    );
+
+    // 2. init.defaultBranch once origin/HEAD is gone.
+    let repo = Repository::open(&work).unwrap();
+    repo.find_reference("refs/remotes/origin/HEAD")
+        .unwrap()
+        .delete()
+        .unwrap();
+    let mut cfg = repo.config().unwrap();
+    cfg.set_str("init.defaultBranch", &branch).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.reference("refs/remotes/origin/other", head.id(), false, "test")
+        .unwrap();
+    assert_eq!(
+        diff_name_only_with_options(work_s, &h_mode(), &no_fetch()).unwrap(),
+        expected
+    );
+
+    // 3. The checked-out branch on origin.
+    cfg.set_str("init.defaultBranch", "does-not-exist").unwrap();
+    assert_eq!(
+        diff_name_only_with_options(work_s, &h_mode(), &no_fetch()).unwrap(),
+        expected
+    );
+
+    // 4. The only remote-tracking branch; ambiguity is an error.
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(&work)
+        .args(["checkout", "--quiet", "-b", "topic"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let err = diff_name_only_with_options(work_s, &h_mode(), &no_fetch()).unwrap_err();
+    assert!(err.to_string().contains("default branch"), "{}", err);
+    repo.find_reference("refs/remotes/origin/other")
+        .unwrap()
+        .delete()
+        .unwrap();
+    assert_eq!(
+        diff_name_only_with_options(work_s, &h_mode(), &no_fetch()).unwrap(),
+        expected
+    );
+
+    // The config key enables the same behavior without the flag.
+    cfg.set_bool("mdcode.noFetch", true).unwrap();
+    assert_eq!(diff_name_only(work_s, &h_mode()).unwrap(), expected);
+
+    std::env::set_var("PATH", orig_path);
+}
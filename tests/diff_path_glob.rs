@@ -0,0 +1,100 @@
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn opts(pattern: &str) -> DiffOptions {
+    DiffOptions {
+        path: Some(pattern.to_string()),
+        ..DiffOptions::default()
+    }
+}
+
+#[test]
+fn test_path_filter_glob_file_and_directory() {
+    let f = PathFilter::new("src/**/*.rs").unwrap();
+    assert!(f.matches(Path::new("src/lib.rs")));
+    assert!(f.matches(Path::new("src/a/b/mod.rs")));
+    assert!(!f.matches(Path::new("tests/t.rs")));
+    assert!(!f.matches(Path::new("src/notes.md")));
+    // `*` stays within one directory.
+    let f = PathFilter::new("src/*.rs").unwrap();
+    assert!(f.matches(Path::new("src/lib.rs")));
+    assert!(!f.matches(Path::new("src/a/mod.rs")));
+    // A plain directory selects everything under it; a file only itself.
+    let f = PathFilter::new("./docs/").unwrap();
+    assert_eq!(f.pattern(), "docs");
+    assert!(f.matches(Path::new("docs/guide/intro.md")));
+    assert!(!f.matches(Path::new("docsite/index.md")));
+    assert!(PathFilter::new("README.md")
+        .unwrap()
+        .matches(Path::new("README.md")));
+    assert!(PathFilter::new("src/[").is_err());
+}
+
+#[test]
+fn test_diff_path_glob_restricts_changes_and_snapshots() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(dir.join("src/net")).unwrap();
+    std::fs::create_dir_all(dir.join("tests")).unwrap();
+    for f in [
+        "src/lib.rs",
+        "src/net/http.rs",
+        "src/notes.md",
+        "tests/t.rs",
+    ] {
+        std::fs::write(dir.join(f), "one\n").unwrap();
+    }
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    for f in [
+        "src/lib.rs",
+        "src/net/http.rs",
+        "src/notes.md",
+        "tests/t.rs",
+    ] {
+        std::fs::write(dir.join(f), "one\ntwo\n").unwrap();
+    }
+    update_repository(s, false, Some("second"), 50).unwrap();
+
+    let glob = opts("src/**/*.rs");
+    let mut paths: Vec<String> = compute_diff_with_options(s, "1", "0", &glob)
+        .unwrap()
+        .iter()
+        .map(|c| c.path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    paths.sort();
+    assert_eq!(paths, vec!["src/lib.rs", "src/net/http.rs"]);
+    assert_eq!(
+        diff_name_only_with_options(s, &["1".into(), "0".into()], &glob).unwrap(),
+        vec!["src/lib.rs", "src/net/http.rs"]
+    );
+
+    // Only matching files are checked out for the diff tool.
+    let repo = git2::Repository::open(&dir).unwrap();
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let out = tmp.path().join("snapshot");
+    let filter = PathFilter::new("src/**/*.rs").unwrap();
+    checkout_matching_to_dir(&repo, &tree, &out, Some(&filter)).unwrap();
+    assert!(out.join("src/lib.rs").exists());
+    assert!(out.join("src/net/http.rs").exists());
+    assert!(!out.join("src/notes.md").exists());
+    assert!(!out.join("tests").exists());
+
+    // Nothing matching is a clear error, before any tool is launched.
+    let none = opts("docs/**");
+    let err = diff_name_only_with_options(s, &["1".into(), "0".into()], &none).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("no files matched --path 'docs/**'"),
+        "{}",
+        err
+    );
+    let err = diff_command_with_options(s, &["1".into(), "0".into()], true, &none).unwrap_err();
+    assert!(err.to_string().contains("no files matched"), "{}", err);
+    diff_command_with_options(s, &["1".into(), "0".into()], true, &glob).unwrap();
+}
@@ -0,0 +1,162 @@
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn repo_with(root: &Path, name: &str, files: &[(&str, &str)]) -> String {
+    let dir = root.join(name);
+    for (path, body) in files {
+        let p = dir.join(path);
+        std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+        std::fs::write(p, body).unwrap();
+    }
+    let s = dir.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    s
+}
+
+#[test]
+fn test_compute_repo_diff_lists_changes_across_repositories() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let fork = repo_with(
+        tmp.path(),
+        "fork",
+        &[
+            ("shared.txt", "same\n"),
+            ("src/lib.rs", "fn a() {}\n"),
+            ("fork_only.md", "# fork\n"),
+        ],
+    );
+    let upstream = repo_with(
+        tmp.path(),
+        "upstream",
+        &[
+            ("shared.txt", "same\n"),
+            ("src/lib.rs", "fn a() {}\nfn b() {}\n"),
+            ("upstream_only.py", "print(1)\n"),
+        ],
+    );
+
+    let opts = DiffOptions::default();
+    let changes = compute_repo_diff(&fork, "0", &upstream, "0", &opts).unwrap();
+    let mut listed: Vec<(char, String)> = changes
+        .iter()
+        .map(|c| (c.status.code(), c.path.to_string_lossy().replace('\\', "/")))
+        .collect();
+    listed.sort();
+    assert_eq!(
+        listed,
+        vec![
+            ('A', "upstream_only.py".to_string()),
+            ('D', "fork_only.md".to_string()),
+            ('M', "src/lib.rs".to_string()),
+        ]
+    );
+    let lib = changes
+        .iter()
+        .find(|c| c.path == Path::new("src/lib.rs"))
+        .unwrap();
+    assert_eq!((lib.insertions, lib.deletions), (1, 0));
+
+    // Selectors apply to each repository independently.
+    std::fs::write(Path::new(&upstream).join("shared.txt"), "changed\n").unwrap();
+    update_repository(&upstream, false, Some("second"), 50).unwrap();
+    let newest = compute_repo_diff(&fork, "0", &upstream, "0", &opts).unwrap();
+    assert!(newest.iter().any(|c| c.path == Path::new("shared.txt")));
+    assert_eq!(
+        compute_repo_diff(&fork, "0", &upstream, "1", &opts).unwrap(),
+        changes
+    );
+    assert!(compute_repo_diff(&fork, "0", &fork, "0", &opts)
+        .unwrap()
+        .is_empty());
+
+    // Dry run through the CLI: no snapshots are filled and no tool is launched.
+    let cli = Cli {
+        command: Commands::Diff {
+            directory: fork.clone(),
+            versions: vec!["0".into()],
+            name_only: false,
+            in_place: false,
+            no_fetch: false,
+            against: Some(upstream.clone()),
+            against_version: Some("1".into()),
+            path: None,
+            export_ignore: false,
+        },
+        dry_run: true,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
+
+#[test]
+fn test_compute_repo_diff_rejects_non_repositories_and_workdir() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let repo = repo_with(tmp.path(), "r", &[("a.txt", "a\n")]);
+    let plain = tmp.path().join("plain");
+    std::fs::create_dir_all(&plain).unwrap();
+    let opts = DiffOptions::default();
+
+    let err = compute_repo_diff(&repo, "0", plain.to_str().unwrap(), "0", &opts).unwrap_err();
+    assert!(
+        err.to_string().contains("No git repository in directory"),
+        "{}",
+        err
+    );
+    let err = compute_repo_diff(plain.to_str().unwrap(), "0", &repo, "0", &opts).unwrap_err();
+    assert!(err.to_string().contains("No git repository"), "{}", err);
+
+    let err = compute_repo_diff(&repo, "0", &repo, "L", &opts).unwrap_err();
+    assert!(err.to_string().contains("not 'L'"), "{}", err);
+
+    // More than one version for <directory> is refused with --against.
+    let cli = Cli {
+        command: Commands::Diff {
+            directory: repo.clone(),
+            versions: vec!["1".into(), "0".into()],
+            name_only: true,
+            in_place: false,
+            no_fetch: false,
+            against: Some(repo.clone()),
+            against_version: None,
+            path: None,
+            export_ignore: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    let err = execute_cli(cli).unwrap_err();
+    assert!(err.to_string().contains("--against-version"), "{}", err);
+}
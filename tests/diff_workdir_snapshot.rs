@@ -0,0 +1,57 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_snapshot_workdir_skips_vcs_build_and_large_files() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    let s = repo.to_str().unwrap();
+    std::fs::create_dir_all(repo.join("src")).unwrap();
+    std::fs::write(repo.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::create_dir_all(repo.join("target").join("debug")).unwrap();
+    std::fs::write(repo.join("target").join("debug").join("out.o"), "obj").unwrap();
+    std::fs::write(repo.join("debug.log"), "ignored by .gitignore").unwrap();
+    std::fs::write(repo.join("notes.md"), "untracked but not ignored").unwrap();
+    std::fs::write(repo.join("big.rs"), vec![b'x'; 2 * 1024 * 1024]).unwrap();
+
+    let out = t.path().join("snap");
+    let (copied, skipped) = snapshot_workdir(s, &out, 1).unwrap();
+
+    assert!(out.join("src").join("main.rs").is_file());
+    assert!(out.join(".gitignore").is_file());
+    assert!(out.join("notes.md").is_file());
+    assert!(!out.join(".git").exists());
+    assert!(!out.join("target").exists());
+    assert!(!out.join("debug.log").exists());
+    assert!(!out.join("big.rs").exists());
+    assert_eq!(skipped, vec![std::path::PathBuf::from("big.rs")]);
+    assert_eq!(copied, 3);
+}
+
+#[test]
+fn test_diff_with_options_in_place_and_snapshot_dry_run() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    let s = repo.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::write(repo.join("a.txt"), "a").unwrap();
+    update_repository(s, false, Some("a"), 50).unwrap();
+
+    let snap = DiffOptions::default();
+    assert!(!snap.in_place);
+    diff_command_with_options(s, &[], true, &snap).unwrap();
+    let in_place = DiffOptions {
+        in_place: true,
+        ..DiffOptions::default()
+    };
+    diff_command_with_options(s, &["0".into()], true, &in_place).unwrap();
+}
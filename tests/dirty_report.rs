@@ -0,0 +1,150 @@
+use mdcode::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &Path, args: &[&str]) {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap();
+}
+
+fn committed_repo(files: &[(&str, &str)]) -> tempfile::TempDir {
+    let tmp = tempdir().unwrap();
+    let d = tmp.path();
+    git(d, &["init"]);
+    git(d, &["config", "user.name", "mdcode"]);
+    git(d, &["config", "user.email", "md@code.local"]);
+    for (name, content) in files {
+        std::fs::write(d.join(name), content).unwrap();
+    }
+    git(d, &["add", "."]);
+    git(d, &["commit", "-m", "init"]);
+    tmp
+}
+
+fn kinds(dir: &Path) -> Vec<(String, DirtyKind, bool)> {
+    dirty_report(dir.to_str().unwrap())
+        .unwrap()
+        .entries
+        .into_iter()
+        .map(|e| (e.path, e.kind, e.eol_only))
+        .collect()
+}
+
+#[test]
+fn test_dirty_report_categories() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    // staged-new
+    let tmp = committed_repo(&[("a.txt", "a\n")]);
+    std::fs::write(tmp.path().join("new.txt"), "n\n").unwrap();
+    git(tmp.path(), &["add", "new.txt"]);
+    assert_eq!(
+        kinds(tmp.path()),
+        vec![("new.txt".into(), DirtyKind::StagedNew, false)]
+    );
+
+    // modified
+    let tmp = committed_repo(&[("a.txt", "a\n")]);
+    std::fs::write(tmp.path().join("a.txt"), "b\n").unwrap();
+    assert_eq!(
+        kinds(tmp.path()),
+        vec![("a.txt".into(), DirtyKind::Modified, false)]
+    );
+
+    // deleted
+    let tmp = committed_repo(&[("gone.txt", "g\n")]);
+    std::fs::remove_file(tmp.path().join("gone.txt")).unwrap();
+    assert_eq!(
+        kinds(tmp.path()),
+        vec![("gone.txt".into(), DirtyKind::Deleted, false)]
+    );
+
+    // renamed (staged)
+    let tmp = committed_repo(&[("old.txt", "same content here\n")]);
+    git(tmp.path(), &["mv", "old.txt", "new.txt"]);
+    assert_eq!(
+        kinds(tmp.path()),
+        vec![("old.txt -> new.txt".into(), DirtyKind::Renamed, false)]
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_dirty_report_typechange() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = committed_repo(&[("x.sh", "echo hi\n"), ("t.txt", "t\n")]);
+    let f = tmp.path().join("x.sh");
+    std::fs::remove_file(&f).unwrap();
+    std::os::unix::fs::symlink(tmp.path().join("t.txt"), &f).unwrap();
+    assert_eq!(
+        kinds(tmp.path()),
+        vec![("x.sh".into(), DirtyKind::TypeChange, false)]
+    );
+}
+
+#[test]
+fn test_dirty_report_marks_eol_only_changes() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = committed_repo(&[("eol.txt", "one\ntwo\n")]);
+    std::fs::write(tmp.path().join("eol.txt"), "one\r\ntwo\r\n").unwrap();
+    let report = dirty_report(tmp.path().to_str().unwrap()).unwrap();
+    assert!(!report.is_dirty());
+    assert_eq!(report.entries.len(), 1);
+    assert!(report.entries[0].eol_only);
+    assert!(report.summary(20).is_empty());
+}
+
+#[test]
+fn test_tag_release_error_lists_dirty_paths() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = committed_repo(&[("a.txt", "a\n"), ("b.txt", "b\n")]);
+    std::fs::write(tmp.path().join("a.txt"), "changed\n").unwrap();
+    std::fs::remove_file(tmp.path().join("b.txt")).unwrap();
+    let err = tag_release(
+        tmp.path().to_str().unwrap(),
+        Some("1.0.0".into()),
+        None,
+        false,
+        "origin",
+        false,
+        false,
+        true,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("uncommitted changes"), "{}", err);
+    assert!(err.contains("modified: a.txt"), "{}", err);
+    assert!(err.contains("deleted: b.txt"), "{}", err);
+}
+
+#[test]
+fn test_dirty_report_summary_truncates() {
+    let report = DirtyReport {
+        entries: (0..25)
+            .map(|i| DirtyEntry {
+                path: format!("f{}.txt", i),
+                kind: DirtyKind::Modified,
+                eol_only: false,
+            })
+            .collect(),
+    };
+    let summary = report.summary(20);
+    assert_eq!(summary.lines().count(), 21);
+    assert!(summary.ends_with("... and 5 more"));
+}
@@ -0,0 +1,92 @@
+use clap::Parser;
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// Run `f` with the process working directory set to `dir`, restoring it afterwards.
+fn in_dir<T>(dir: &Path, f: impl FnOnce() -> T) -> T {
+    let saved = std::env::current_dir().unwrap();
+    std::env::set_current_dir(dir).unwrap();
+    let out = f();
+    std::env::set_current_dir(saved).unwrap();
+    out
+}
+
+#[test]
+fn test_directory_is_optional_except_before_other_positionals() {
+    let cli = Cli::try_parse_from(["mdcode", "info"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::Info {
+            directory: None,
+            ..
+        }
+    ));
+    let cli = Cli::try_parse_from(["mdcode", "gh_push", "--tags"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::GhPush {
+            directory: None,
+            ..
+        }
+    ));
+    assert!(Cli::try_parse_from(["mdcode", "new"]).is_err());
+    assert_eq!(resolve_directory(Some("some/dir")).unwrap(), "some/dir");
+}
+
+#[test]
+#[serial_test::serial]
+fn test_info_without_directory_discovers_repo_from_subdirectory() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    std::fs::create_dir_all(repo.join("src/nested")).unwrap();
+    std::fs::write(repo.join("src/main.rs"), "fn main() {}\n").unwrap();
+    new_repository(repo.to_str().unwrap(), false, 50).unwrap();
+
+    let sub = repo.join("src/nested");
+    let root = in_dir(&sub, || resolve_directory(None)).unwrap();
+    assert_eq!(
+        Path::new(&root).canonicalize().unwrap(),
+        repo.canonicalize().unwrap()
+    );
+
+    let cli = Cli::try_parse_from(["mdcode", "info"]).unwrap();
+    in_dir(&sub, || execute_cli(cli)).unwrap();
+
+    // Outside any repository the usual "no repository" error (exit code 3) is reported.
+    let outside = t.path().join("plain");
+    std::fs::create_dir_all(&outside).unwrap();
+    let cli = Cli::try_parse_from(["mdcode", "info"]).unwrap();
+    let err = in_dir(&outside, || execute_cli(cli)).unwrap_err();
+    assert_eq!(exit_code_for(err.as_ref()), 3, "{}", err);
+}
+
+// The binary is a no-op under coverage builds.
+#[cfg(not(coverage))]
+#[test]
+fn test_binary_info_without_directory_from_subdirectory() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    std::fs::create_dir_all(repo.join("docs")).unwrap();
+    std::fs::write(repo.join("docs/readme.md"), "# hi\n").unwrap();
+    new_repository(repo.to_str().unwrap(), false, 50).unwrap();
+
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_mdcode"))
+        .arg("info")
+        .current_dir(repo.join("docs"))
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
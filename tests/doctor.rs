@@ -0,0 +1,68 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_doctor_reports_git_and_identity() {
+    let tmp = tempdir().unwrap();
+    let s = tmp.path().to_str().unwrap();
+    let checks = run_doctor_checks(s);
+    let names: Vec<&str> = checks.iter().map(|c| c.name).collect();
+    assert_eq!(names, vec!["git", "identity", "github", "diff-tool"]);
+
+    let git = &checks[0];
+    if check_git_installed() {
+        assert_eq!(git.status, DoctorStatus::Ok);
+        assert!(git.detail.starts_with("git version"), "{}", git.detail);
+        // Only a FAIL makes the command itself fail.
+        doctor_command(s).unwrap();
+    } else {
+        assert_eq!(git.status, DoctorStatus::Fail);
+        assert!(git.hint.is_some());
+    }
+    assert!(format_doctor_check(git).contains("git: "));
+
+    // Inside a repository the repository's own author is reported with its source.
+    if check_git_installed() {
+        let dir = tmp.path().join("r");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+        let r = dir.to_str().unwrap();
+        new_repository(r, false, 50).unwrap();
+        set_repo_author(r, "Doc Tor <doc@example.com>", false).unwrap();
+        let identity = check_identity(r);
+        if std::env::var("GIT_AUTHOR_NAME").is_err() && std::env::var("GIT_COMMITTER_NAME").is_err()
+        {
+            assert_eq!(identity.status, DoctorStatus::Ok);
+            assert!(
+                identity
+                    .detail
+                    .starts_with("Doc Tor <doc@example.com> (source: git config"),
+                "{}",
+                identity.detail
+            );
+        }
+    }
+}
+
+#[test]
+#[serial_test::serial]
+fn test_doctor_warns_about_missing_diff_tool() {
+    let prev = std::env::var("MDCODE_DIFF_TOOL").ok();
+    std::env::set_var("MDCODE_DIFF_TOOL", "/nonexistent/difftool");
+    let check = check_diff_tool();
+    assert_eq!(check.status, DoctorStatus::Warn);
+    assert!(
+        check.detail.contains("/nonexistent/difftool"),
+        "{}",
+        check.detail
+    );
+    assert!(format_doctor_check(&check).contains("hint:"));
+
+    let exe = std::env::current_exe().unwrap();
+    std::env::set_var("MDCODE_DIFF_TOOL", &exe);
+    assert_eq!(check_diff_tool().status, DoctorStatus::Ok);
+    match prev {
+        Some(v) => std::env::set_var("MDCODE_DIFF_TOOL", v),
+        None => std::env::remove_var("MDCODE_DIFF_TOOL"),
+    }
+}
@@ -0,0 +1,111 @@
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn committed_paths(dir: &Path) -> Vec<String> {
+    let repo = git2::Repository::open(dir).unwrap();
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let mut out = Vec::new();
+    tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(git2::ObjectType::Blob) {
+            out.push(format!("{}{}", root, entry.name().unwrap()));
+        }
+        git2::TreeWalkResult::Ok
+    })
+    .unwrap();
+    out.sort();
+    out
+}
+
+#[test]
+fn test_find_empty_dirs_lists_leaves_and_skips_excluded() {
+    let tmp = tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("assets/img")).unwrap();
+    std::fs::create_dir_all(root.join("db/migrations")).unwrap();
+    std::fs::create_dir_all(root.join("target/debug")).unwrap();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+    let found: Vec<String> = find_empty_dirs(root.to_str().unwrap())
+        .iter()
+        .map(|p| {
+            p.strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect();
+    assert_eq!(found, vec!["assets/img", "db/migrations"]);
+
+    let scan = scan_source_tree(root.to_str().unwrap(), 50).unwrap();
+    assert_eq!(scan.count, 1);
+    assert_eq!(scan.empty_dirs.len(), 2);
+}
+
+#[test]
+fn test_new_commits_gitkeep_in_nested_empty_dirs() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(dir.join("assets/img/icons")).unwrap();
+    std::fs::create_dir_all(dir.join("logs")).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    let s = dir.to_str().unwrap();
+
+    let plan = plan_new_repository(s, &NewOptions::default()).unwrap();
+    assert!(
+        plan.render()
+            .contains("Would add .gitkeep to 2 empty directories: assets/img/icons, logs"),
+        "{}",
+        plan.render()
+    );
+    new_repository(s, false, 50).unwrap();
+    assert_eq!(
+        committed_paths(&dir),
+        vec!["assets/img/icons/.gitkeep", "logs/.gitkeep", "main.rs"]
+    );
+}
+
+#[test]
+fn test_keep_empty_dirs_off_for_new_and_opt_in_for_update() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(dir.join("empty")).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    let s = dir.to_str().unwrap();
+    let opts = NewOptions {
+        keep_empty_dirs: false,
+        ..NewOptions::default()
+    };
+    new_repository_from_options(s, false, &opts).unwrap();
+    assert!(!dir.join("empty/.gitkeep").exists());
+    assert_eq!(committed_paths(&dir), vec!["main.rs"]);
+
+    // update leaves empty directories alone unless asked.
+    std::fs::create_dir_all(dir.join("later/nested")).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() { }\n").unwrap();
+    update_repository(s, false, Some("second"), 50).unwrap();
+    assert!(!dir.join("later/nested/.gitkeep").exists());
+
+    // A dry run reports but writes nothing.
+    let keep = UpdateOptions {
+        keep_empty_dirs: true,
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, true, Some("preview"), 50, &keep).unwrap();
+    assert!(!dir.join("later/nested/.gitkeep").exists());
+
+    update_repository_with_options(s, false, Some("keep dirs"), 50, &keep).unwrap();
+    assert_eq!(
+        committed_paths(&dir),
+        vec!["empty/.gitkeep", "later/nested/.gitkeep", "main.rs"]
+    );
+}
@@ -0,0 +1,169 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_parse_env_file() {
+    let pairs = parse_env_file(
+        "# local secrets\n\
+         \n\
+         GITHUB_TOKEN=ghp_abc123\n\
+         export GIT_AUTHOR_NAME=\"Jane Doe\"\n\
+         GIT_AUTHOR_EMAIL='jane@example.com'\n\
+         NOTE=two words # trailing comment\n\
+         MULTI=\"a\\nb \\\"q\\\"\"\n\
+         EMPTY=\n",
+    )
+    .unwrap();
+    let t = |k: &str, v: &str| (k.to_string(), v.to_string());
+    assert_eq!(
+        pairs,
+        vec![
+            t("GITHUB_TOKEN", "ghp_abc123"),
+            t("GIT_AUTHOR_NAME", "Jane Doe"),
+            t("GIT_AUTHOR_EMAIL", "jane@example.com"),
+            t("NOTE", "two words"),
+            t("MULTI", "a\nb \"q\""),
+            t("EMPTY", ""),
+        ]
+    );
+
+    let err = parse_env_file("A=1\njust text\n").unwrap_err().to_string();
+    assert!(err.contains("line 2"), "{}", err);
+    let err = parse_env_file("1BAD=x\n").unwrap_err().to_string();
+    assert!(err.contains("invalid variable name '1BAD'"), "{}", err);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_load_env_file_keeps_variables_already_set() {
+    let t = tempdir().unwrap();
+    let path = t.path().join(".env");
+    std::fs::write(
+        &path,
+        "MDCODE_TEST_ENV_NEW=from-file\nMDCODE_TEST_ENV_SET=from-file\n",
+    )
+    .unwrap();
+    std::env::remove_var("MDCODE_TEST_ENV_NEW");
+    std::env::set_var("MDCODE_TEST_ENV_SET", "from-shell");
+
+    let set = load_env_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(set, vec!["MDCODE_TEST_ENV_NEW".to_string()]);
+    assert_eq!(std::env::var("MDCODE_TEST_ENV_NEW").unwrap(), "from-file");
+    assert_eq!(std::env::var("MDCODE_TEST_ENV_SET").unwrap(), "from-shell");
+    std::env::remove_var("MDCODE_TEST_ENV_NEW");
+    std::env::remove_var("MDCODE_TEST_ENV_SET");
+
+    let err = load_env_file(t.path().join("missing.env").to_str().unwrap())
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("cannot read env file"), "{}", err);
+
+    use clap::Parser;
+    let cli = Cli::try_parse_from(["mdcode", "--env-file", ".env", "info", "."]).unwrap();
+    assert_eq!(cli.env_file.as_deref(), Some(".env"));
+}
+
+/// `gh_create` with a logged-out `gh` needs an API token; here it only exists in the
+/// `.env` passed with `--env-file`.
+#[cfg(all(unix, feature = "offline_gh"))]
+#[test]
+#[serial_test::serial]
+fn test_gh_create_reads_token_from_env_file() {
+    use std::os::unix::fs::PermissionsExt;
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let bin = t.path().join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let gh = bin.join("gh");
+    std::fs::write(
+        &gh,
+        "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo gh version; exit 0; fi\nexit 1\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&gh, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let bare = t.path().join("remote.git");
+    git2::Repository::init_bare(&bare).unwrap();
+    let proj = t.path().join("proj");
+    let proj_str = proj.to_str().unwrap();
+    new_repository(proj_str, false, 50).unwrap();
+    let env_path = t.path().join(".env");
+    std::fs::write(
+        &env_path,
+        format!(
+            "GITHUB_TOKEN=ghp_from_env_file\nMDCODE_TEST_BARE_REMOTE=file://{}\n",
+            bare.display()
+        ),
+    )
+    .unwrap();
+
+    let saved: Vec<_> = [
+        "PATH",
+        "GITHUB_TOKEN",
+        "GH_TOKEN",
+        "GITHUB_TOKEN_FILE",
+        "MDCODE_TEST_BARE_REMOTE",
+    ]
+    .iter()
+    .map(|k| (*k, std::env::var_os(k)))
+    .collect();
+    std::env::set_var(
+        "PATH",
+        format!("{}:{}", bin.display(), std::env::var("PATH").unwrap()),
+    );
+    for k in [
+        "GITHUB_TOKEN",
+        "GH_TOKEN",
+        "GITHUB_TOKEN_FILE",
+        "MDCODE_TEST_BARE_REMOTE",
+    ] {
+        std::env::remove_var(k);
+    }
+    let cli = |env_file: Option<String>| Cli {
+        command: Commands::GhCreate {
+            directory: Some(proj_str.to_string()),
+            description: None,
+            public: false,
+            private: true,
+            internal: false,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: None,
+            set_default_branch: false,
+            remote_url: None,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    let without = execute_cli(cli(None)).map_err(|e| e.to_string());
+    let with =
+        execute_cli(cli(Some(env_path.to_str().unwrap().to_string()))).map_err(|e| e.to_string());
+    let token = resolve_github_token(None).map(|(token, _)| token).ok();
+    for (k, v) in saved {
+        match v {
+            Some(v) => std::env::set_var(k, v),
+            None => std::env::remove_var(k),
+        }
+    }
+
+    assert!(without.unwrap_err().contains("not authenticated"));
+    with.unwrap();
+    assert_eq!(token.as_deref(), Some("ghp_from_env_file"));
+    let remote = git2::Repository::open_bare(&bare).unwrap();
+    assert!(remote.find_reference("refs/heads/master").is_ok());
+}
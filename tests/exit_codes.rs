@@ -0,0 +1,115 @@
+use mdcode::*;
+use std::error::Error;
+use tempfile::tempdir;
+
+fn cli(command: Commands) -> Cli {
+    Cli {
+        command,
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    }
+}
+
+fn exit_code(command: Commands) -> i32 {
+    let err = execute_cli(cli(command)).unwrap_err();
+    exit_code_for(err.as_ref())
+}
+
+fn info(directory: &str) -> Commands {
+    Commands::Info {
+        directory: Some(directory.to_string()),
+        rename_threshold: DEFAULT_RENAME_THRESHOLD,
+        since_tag: None,
+        limit: None,
+        oldest_first: false,
+        utc: false,
+        local: false,
+        relative: false,
+        files: FileListMode::Summary,
+        paginate: false,
+        graph: false,
+        remote: None,
+        recursive: false,
+    }
+}
+
+#[test]
+fn test_exit_codes_per_error_kind() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+
+    // 3: no repository.
+    let missing = tmp.path().join("missing");
+    assert_eq!(exit_code(info(missing.to_str().unwrap())), 3);
+
+    // 4: repository without commits.
+    let empty = tmp.path().join("empty");
+    git2::Repository::init(&empty).unwrap();
+    assert_eq!(exit_code(info(empty.to_str().unwrap())), 4);
+
+    // 5: tagging a dirty working tree.
+    let work = tmp.path().join("work");
+    let s = work.to_str().unwrap();
+    std::fs::create_dir_all(&work).unwrap();
+    std::fs::write(work.join("a.txt"), "a\n").unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::write(work.join("a.txt"), "changed\n").unwrap();
+    let tag = Commands::Tag {
+        directory: Some(s.to_string()),
+        version: Some("0.1.0".into()),
+        message: None,
+        message_file: None,
+        no_push: true,
+        strict_push: false,
+        remote: "origin".into(),
+        force: false,
+        allow_dirty: false,
+        author: None,
+    };
+    assert_eq!(exit_code(tag), 5);
+
+    // 6: push to a remote that does not exist.
+    add_remote(
+        s,
+        "origin",
+        tmp.path().join("nowhere.git").to_str().unwrap(),
+    )
+    .unwrap();
+    let push = Commands::GhPush {
+        directory: Some(s.to_string()),
+        remote: "origin".into(),
+        no_set_upstream: false,
+        all_branches: false,
+        tags: false,
+        follow_tags: false,
+        insecure: false,
+        set_default_branch: false,
+        recursive: false,
+    };
+    assert_eq!(exit_code(push), 6);
+}
+
+#[test]
+fn test_exit_code_fallback_is_one() {
+    let plain: Box<dyn Error> = "something else went wrong".into();
+    assert_eq!(exit_code_for(plain.as_ref()), 1);
+    let git: Box<dyn Error> = MdcodeError::Git(git2::Error::from_str("boom")).into();
+    assert_eq!(exit_code_for(git.as_ref()), 1);
+    let status: Box<dyn Error> = MdcodeError::ExitCode(2, "CI checks still running".into()).into();
+    assert_eq!(exit_code_for(status.as_ref()), 2);
+}
@@ -0,0 +1,64 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn test_export_ignore_patterns() {
+    let rules = ExportIgnore::parse(
+        "# test data\n\
+         fixtures export-ignore\n\
+         /ci/*.yml export-ignore\n\
+         *.png binary\n\
+         ci/keep.yml -export-ignore\n",
+    );
+    assert!(rules.is_ignored(Path::new("fixtures")));
+    assert!(rules.is_ignored(Path::new("fixtures/data.json")));
+    assert!(rules.is_ignored(Path::new("tests/fixtures/big/data.json")));
+    assert!(rules.is_ignored(Path::new("ci/build.yml")));
+    assert!(!rules.is_ignored(Path::new("ci/keep.yml")));
+    assert!(!rules.is_ignored(Path::new("sub/ci/build.yml")));
+    assert!(!rules.is_ignored(Path::new("logo.png")));
+    assert!(!rules.is_ignored(Path::new("src/main.rs")));
+    assert!(ExportIgnore::parse("*.png binary\n").is_empty());
+}
+
+#[test]
+fn test_export_checkout_leaves_out_export_ignore_dirs() {
+    let t = tempdir().unwrap();
+    let repo_dir = t.path().join("r");
+    std::fs::create_dir_all(repo_dir.join("tests/fixtures")).unwrap();
+    std::fs::write(repo_dir.join("main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(repo_dir.join("tests/it.rs"), "#[test] fn t() {}\n").unwrap();
+    std::fs::write(repo_dir.join("tests/fixtures/input.json"), "{}\n").unwrap();
+    std::fs::write(
+        repo_dir.join(".gitattributes"),
+        "tests/fixtures export-ignore\n",
+    )
+    .unwrap();
+    let s = repo_dir.to_str().unwrap();
+    let repo = Repository::init(&repo_dir).unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let sig = git2::Signature::now("t", "t@example.com").unwrap();
+    commit_current_index(&repo, "Initial commit", &sig, &sig, false).unwrap();
+    // The working tree no longer says so; the committed attributes still apply.
+    std::fs::write(repo_dir.join(".gitattributes"), "").unwrap();
+
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let export = t.path().join("export");
+    checkout_export_to_dir(&repo, &tree, &export, None).unwrap();
+    let full = t.path().join("full");
+    checkout_tree_to_dir(&repo, &tree, &full).unwrap();
+
+    assert!(export.join("main.rs").is_file());
+    assert!(export.join("tests/it.rs").is_file());
+    assert!(!export.join("tests/fixtures").exists());
+    assert!(full.join("tests/fixtures/input.json").is_file());
+
+    // Working tree snapshots use the file on disk.
+    assert!(ExportIgnore::load(s).is_empty());
+}
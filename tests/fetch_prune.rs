@@ -0,0 +1,67 @@
+use git2::Repository;
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &std::path::Path, args: &[&str]) {
+    assert!(Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap()
+        .success());
+}
+
+#[test]
+fn test_gh_fetch_prune_removes_and_reports_deleted_branch() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let bare = tmp.path().join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    let bare_s = bare.to_str().unwrap();
+
+    // Clone A publishes master and a second branch.
+    let a = tmp.path().join("A");
+    new_repository(a.to_str().unwrap(), false, 50).unwrap();
+    git(&a, &["remote", "add", "origin", bare_s]);
+    git(&a, &["push", "-q", "origin", "master"]);
+    git(&a, &["branch", "feature"]);
+    git(&a, &["push", "-q", "origin", "feature"]);
+
+    // Clone B sees both branches and tracks feature locally.
+    let b = tmp.path().join("B");
+    git(tmp.path(), &["clone", "-q", bare_s, b.to_str().unwrap()]);
+    git(&b, &["branch", "--track", "feature", "origin/feature"]);
+    let b_s = b.to_str().unwrap();
+    let repo_b = Repository::open(&b).unwrap();
+    assert!(remote_tracking_refs(&repo_b, "origin")
+        .unwrap()
+        .contains(&"refs/remotes/origin/feature".to_string()));
+    assert!(gone_upstream_branches(b_s).unwrap().is_empty());
+
+    // Delete the branch on the remote, then fetch without and with --prune.
+    git(&bare, &["branch", "-D", "feature"]);
+    assert!(gh_fetch_with_prune(b_s, "origin", false, None)
+        .unwrap()
+        .is_empty());
+    assert!(repo_b.find_reference("refs/remotes/origin/feature").is_ok());
+
+    let pruned = gh_fetch_with_prune(b_s, "origin", true, None).unwrap();
+    assert_eq!(pruned, vec!["refs/remotes/origin/feature".to_string()]);
+    assert!(repo_b
+        .find_reference("refs/remotes/origin/feature")
+        .is_err());
+    assert_eq!(
+        gone_upstream_branches(b_s).unwrap(),
+        vec![("feature".to_string(), "origin/feature".to_string())]
+    );
+
+    // A second prune has nothing left to remove.
+    assert!(gh_fetch_with_prune(b_s, "origin", true, None)
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,96 @@
+use mdcode::*;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn change(path: &str, status: ChangeKind, old: Option<&str>) -> FileChange {
+    FileChange {
+        path: PathBuf::from(path),
+        old_path: old.map(PathBuf::from),
+        status,
+        insertions: 1,
+        deletions: 0,
+        category: None,
+    }
+}
+
+/// 200 changes: a rename, a deletion, then added files.
+fn delta() -> Vec<FileChange> {
+    let mut changes = vec![
+        change("src/new.rs", ChangeKind::Renamed, Some("src/old.rs")),
+        change("gone.rs", ChangeKind::Deleted, None),
+    ];
+    changes.extend((0..198).map(|i| change(&format!("f{:03}.rs", i), ChangeKind::Added, None)));
+    changes
+}
+
+#[test]
+#[serial_test::serial]
+fn test_file_list_summary_truncates_after_five_entries() {
+    let changes = delta();
+    std::env::set_var("NO_COLOR", "1");
+    let summary = render_file_list(&changes, FileListMode::Summary);
+    let full = render_file_list(&changes, FileListMode::Full);
+    let none = render_file_list(&changes, FileListMode::None);
+    std::env::remove_var("NO_COLOR");
+
+    assert_eq!(
+        summary,
+        "src/old.rs -> src/new.rs, gone.rs, f000.rs, f001.rs, f002.rs … and 195 more"
+    );
+    assert_eq!(full.split(", ").count(), 200);
+    assert!(full.ends_with("f197.rs"));
+    assert_eq!(none, "");
+    // Short lists are not cut.
+    assert_eq!(
+        render_file_list(&changes[..FILE_LIST_SUMMARY], FileListMode::Summary),
+        render_file_list(&changes[..FILE_LIST_SUMMARY], FileListMode::Full)
+    );
+}
+
+#[test]
+#[serial_test::serial]
+fn test_file_list_summary_keeps_colored_markers() {
+    if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return;
+    }
+    let summary = render_file_list(&delta(), FileListMode::Summary);
+    assert!(summary.starts_with(&format!("{}src/old.rs -> src/new.rs{}", YELLOW, RESET)));
+    assert!(summary.contains(&format!("{}gone.rs{}", RED, RESET)));
+    assert!(summary.contains(&format!("{}f000.rs{}", GREEN, RESET)));
+    assert!(summary.ends_with(" … and 195 more"));
+}
+
+#[test]
+fn test_files_none_skips_diffs() {
+    let t = tempdir().unwrap();
+    std::fs::write(t.path().join("main.rs"), "fn main() {}").unwrap();
+    let s = t.path().to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    let changes = |files| {
+        let opts = HistoryOptions {
+            files,
+            ..HistoryOptions::default()
+        };
+        let mut out = Vec::new();
+        for_each_commit(s, DEFAULT_RENAME_THRESHOLD, &opts, |info| {
+            out.push(info.changes.len());
+            Ok(())
+        })
+        .unwrap();
+        out
+    };
+    assert_eq!(changes(FileListMode::None), vec![0]);
+    assert_eq!(changes(FileListMode::Summary), vec![1]);
+
+    use clap::Parser;
+    let cli = Cli::try_parse_from(["mdcode", "info", ".", "--files", "none"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::Info {
+            files: FileListMode::None,
+            ..
+        }
+    ));
+    assert!(Cli::try_parse_from(["mdcode", "info", ".", "--files", "some"]).is_err());
+}
@@ -0,0 +1,112 @@
+use mdcode::*;
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &str, args: &[&str]) {
+    assert!(Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap()
+        .success());
+}
+
+fn commit_all(dir: &str, msg: &str) {
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", msg]);
+}
+
+#[test]
+fn test_file_history_follows_two_renames_across_five_commits() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    let s = repo.to_str().unwrap();
+    std::fs::create_dir_all(&repo).unwrap();
+    git(s, &["init", "-q"]);
+    let body: String = (0..20).map(|i| format!("line {}\n", i)).collect();
+
+    std::fs::write(repo.join("a.txt"), &body).unwrap();
+    commit_all(s, "c1 add a");
+    std::fs::write(repo.join("a.txt"), format!("{}line 20\n", body)).unwrap();
+    commit_all(s, "c2 edit a");
+    git(s, &["mv", "a.txt", "b.txt"]);
+    commit_all(s, "c3 a to b");
+    std::fs::write(repo.join("other.txt"), "unrelated\n").unwrap();
+    commit_all(s, "c4 unrelated");
+    std::fs::write(repo.join("b.txt"), format!("{}line 20\nline 21\n", body)).unwrap();
+    commit_all(s, "c5 edit b");
+    git(s, &["mv", "b.txt", "sub_c.txt"]);
+    commit_all(s, "c6 b to c");
+
+    let log = file_history(s, "sub_c.txt", DEFAULT_RENAME_THRESHOLD).unwrap();
+    let summaries: Vec<&str> = log.iter().map(|e| e.summary.as_str()).collect();
+    assert_eq!(
+        summaries,
+        vec![
+            "c6 b to c",
+            "c5 edit b",
+            "c3 a to b",
+            "c2 edit a",
+            "c1 add a"
+        ]
+    );
+    let paths: Vec<PathBuf> = log.iter().map(|e| e.path.clone()).collect();
+    assert_eq!(
+        paths,
+        ["sub_c.txt", "b.txt", "b.txt", "a.txt", "a.txt"]
+            .iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>()
+    );
+    let kinds: Vec<ChangeKind> = log.iter().map(|e| e.status).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ChangeKind::Renamed,
+            ChangeKind::Modified,
+            ChangeKind::Renamed,
+            ChangeKind::Modified,
+            ChangeKind::Added
+        ]
+    );
+    assert_eq!(log[0].old_path, Some(PathBuf::from("b.txt")));
+    assert_eq!(log[2].old_path, Some(PathBuf::from("a.txt")));
+    // Indexes match `info`: the unrelated commit (index 2) is skipped.
+    let indexes: Vec<usize> = log.iter().map(|e| e.index).collect();
+    assert_eq!(indexes, vec![0, 1, 3, 4, 5]);
+
+    // Exact renames still pair at 100% similarity.
+    let strict = file_history(s, "sub_c.txt", 100).unwrap();
+    assert_eq!(strict.len(), 5);
+    assert!(file_history(s, "missing.txt", DEFAULT_RENAME_THRESHOLD).is_err());
+
+    let cli = Cli {
+        command: Commands::Log {
+            directory: s.to_string(),
+            path: "sub_c.txt".to_string(),
+            rename_threshold: DEFAULT_RENAME_THRESHOLD,
+            since_tag: None,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
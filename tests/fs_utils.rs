@@ -41,6 +41,29 @@ fn test_create_gitignore_writes_expected_content() {
     assert!(content.contains(".venv/"));
 }
 
+#[test]
+fn test_gitignore_adds_blocks_for_detected_tooling() {
+    let tmp = tempdir().unwrap();
+    let d = tmp.path();
+    std::fs::write(d.join("package.json"), b"{}").unwrap();
+    let content = generate_gitignore_content(d.to_str().unwrap()).unwrap();
+    assert!(content.starts_with("target/\ntarget_ci/"));
+    assert!(content.contains("\n\n# package.json\nnode_modules/\ndist/\n"));
+    assert!(!content.contains("CMakeFiles/"));
+
+    // Patterns already in the base are not repeated; an all-duplicate block is dropped.
+    std::fs::write(d.join("Cargo.toml"), b"").unwrap();
+    std::fs::write(d.join("CMakeLists.txt"), b"").unwrap();
+    let content = generate_gitignore_content(d.to_str().unwrap()).unwrap();
+    assert_eq!(content.lines().filter(|l| *l == "target/").count(), 1);
+    assert!(content.contains("# Cargo.toml\n**/*.rs.bk"));
+    assert!(content.contains("# CMakeLists.txt\nbuild/\nCMakeFiles/"));
+
+    create_gitignore(d.to_str().unwrap(), false).unwrap();
+    let written = std::fs::read_to_string(d.join(".gitignore")).unwrap();
+    assert!(written.contains("node_modules/"));
+}
+
 #[test]
 fn test_add_files_to_git_counts_files() {
     if !check_git_installed() {
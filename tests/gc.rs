@@ -0,0 +1,115 @@
+use clap::Parser;
+use mdcode::*;
+use serde_json::json;
+use tempfile::tempdir;
+
+fn entry(time: i64) -> JournalEntry {
+    JournalEntry {
+        time,
+        event: "update".to_string(),
+        args: json!({}),
+        commit: None,
+        tag: None,
+        error: None,
+    }
+}
+
+#[test]
+fn test_gc_prunes_diff_snapshots_of_this_repo_only() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    let before = create_temp_dir(&format!("before.{}.2024-05-01_120000", s)).unwrap();
+    std::fs::write(before.join("main.rs"), "fn main() {}\n").unwrap();
+    let after = create_temp_dir(&format!("after.{}.current", s)).unwrap();
+    // Same prefix, but not a snapshot name, and a snapshot of another directory.
+    let unrelated = create_temp_dir(&format!("before.{}.notes", s)).unwrap();
+    let other_repo = format!("{}-other", s);
+    let other = create_temp_dir(&format!("before.{}.2024-05-01_120000", other_repo)).unwrap();
+
+    let mut expected = vec![before.clone(), after.clone()];
+    expected.sort();
+    assert_eq!(diff_snapshot_dirs(s), expected);
+
+    let dry = gc_command(s, false, DEFAULT_JOURNAL_KEEP, true).unwrap();
+    assert_eq!(dry.removed_snapshots, expected);
+    assert!(dry.snapshot_bytes >= 13);
+    assert!(!dry.ran_git_gc);
+    assert!(before.exists() && after.exists());
+
+    let report = gc_command(s, false, DEFAULT_JOURNAL_KEEP, false).unwrap();
+    assert_eq!(report.removed_snapshots, expected);
+    assert!(report.ran_git_gc);
+    assert!(report.before.objects > 0);
+    assert!(!before.exists() && !after.exists());
+    assert!(unrelated.exists() && other.exists());
+    assert!(diff_snapshot_dirs(s).is_empty());
+    assert_eq!(diff_snapshot_dirs(&other_repo), vec![other.clone()]);
+
+    std::fs::remove_dir_all(unrelated).unwrap();
+    std::fs::remove_dir_all(other).unwrap();
+}
+
+#[test]
+fn test_gc_trims_journal_to_newest_entries() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let _ = std::fs::remove_file(journal_path(s).unwrap());
+    for time in 1..=5 {
+        append_journal(s, &entry(time)).unwrap();
+    }
+
+    assert_eq!(trim_journal(s, 2, true).unwrap(), 3);
+    assert_eq!(read_journal(s).unwrap().len(), 5);
+
+    let cli = Cli::try_parse_from(["mdcode", "gc", s, "--keep-journal", "2"]).unwrap();
+    execute_cli(cli).unwrap();
+    let times: Vec<i64> = read_journal(s).unwrap().iter().map(|e| e.time).collect();
+    assert_eq!(times, vec![4, 5]);
+    assert_eq!(trim_journal(s, 2, false).unwrap(), 0);
+
+    // `gc` is this command; gh_clone's short alias is `gcl`.
+    let clone = Cli::try_parse_from(["mdcode", "gcl", "owner/name"]).unwrap();
+    assert!(matches!(clone.command, Commands::GhClone { .. }));
+}
+
+#[test]
+fn test_object_store_stats_counts_loose_objects() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let repo = git2::Repository::open(&dir).unwrap();
+    let loose = object_store_stats(&repo).unwrap();
+    assert!(loose.loose_objects > 0);
+    assert_eq!(loose.objects, loose.loose_objects);
+    assert_eq!(loose.packs, 0);
+
+    let ok = std::process::Command::new("git")
+        .args(["-C", s, "repack", "-a", "-d", "-q"])
+        .status()
+        .unwrap()
+        .success();
+    assert!(ok);
+    let _ = std::process::Command::new("git")
+        .args(["-C", s, "prune-packed"])
+        .status();
+    let packed = object_store_stats(&repo).unwrap();
+    assert_eq!(packed.packs, 1);
+    assert_eq!(packed.objects, loose.objects);
+    assert_eq!(packed.loose_objects, 0);
+    assert!(packed.pack_bytes > 0);
+}
@@ -50,14 +50,32 @@ fn test_gh_create_api_fallback_offline_pushes_to_local_bare() {
 
     let cli = Cli {
         command: Commands::GhCreate {
-            directory: work_str.to_string(),
+            directory: Some(work_str.to_string()),
             description: Some("offline".into()),
             public: false,
             private: false,
             internal: false,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: None,
+            set_default_branch: false,
+            remote_url: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     // Should add origin pointing to our local bare and push successfully
     execute_cli(cli).unwrap();
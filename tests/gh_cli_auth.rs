@@ -0,0 +1,167 @@
+#![cfg(unix)]
+use mdcode::*;
+use std::io::Write as _;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+/// Write a `gh` shim whose `auth` subcommand runs `auth_body`; every other call is logged.
+fn gh_shim(dir: &Path, auth_body: &str, log_path: &Path) -> PathBuf {
+    std::fs::create_dir_all(dir).unwrap();
+    let gh = dir.join("gh");
+    let mut f = std::fs::File::create(&gh).unwrap();
+    writeln!(f, "#!/bin/sh").unwrap();
+    writeln!(
+        f,
+        "if [ \"$1\" = \"--version\" ]; then echo gh version; exit 0; fi"
+    )
+    .unwrap();
+    writeln!(f, "if [ \"$1\" = \"auth\" ]; then {}; fi", auth_body).unwrap();
+    writeln!(f, "echo \"$@\" >> {}", log_path.display()).unwrap();
+    writeln!(f, "exit 0").unwrap();
+    drop(f);
+    let mut p = std::fs::metadata(&gh).unwrap().permissions();
+    p.set_mode(0o755);
+    std::fs::set_permissions(&gh, p).unwrap();
+    gh
+}
+
+const LOGGED_IN: &str = "echo 'Logged in to github.com' >&2; exit 0";
+const LOGGED_OUT: &str =
+    "echo 'You are not logged into any GitHub hosts. Run gh auth login to authenticate.' >&2; exit 1";
+const TOO_OLD: &str = "echo 'unknown command \"auth\" for \"gh\"' >&2; exit 1";
+
+#[test]
+fn test_gh_cli_authenticated_cases() {
+    let t = tempdir().unwrap();
+    let log = t.path().join("log.txt");
+    assert!(gh_cli_authenticated(&gh_shim(
+        &t.path().join("ok"),
+        LOGGED_IN,
+        &log
+    )));
+    assert!(!gh_cli_authenticated(&gh_shim(
+        &t.path().join("out"),
+        LOGGED_OUT,
+        &log
+    )));
+    assert!(!gh_cli_authenticated(&gh_shim(
+        &t.path().join("old"),
+        TOO_OLD,
+        &log
+    )));
+    assert!(!gh_cli_authenticated(&t.path().join("missing-gh")));
+    assert!(!log.exists(), "only `auth status` should have run");
+}
+
+fn create_cli(dir: &str) -> Cli {
+    Cli {
+        command: Commands::GhCreate {
+            directory: Some(dir.to_string()),
+            description: None,
+            public: false,
+            private: true,
+            internal: false,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: None,
+            set_default_branch: false,
+            remote_url: None,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    }
+}
+
+/// Run `gh_create` with a logged-out `gh` first on PATH and the given token env.
+fn run_logged_out(
+    t: &Path,
+    token: Option<&str>,
+    bare_remote: Option<&str>,
+) -> (Result<(), String>, PathBuf) {
+    let log = t.join("gh_args.txt");
+    let bin = t.join("bin");
+    gh_shim(&bin, LOGGED_OUT, &log);
+    let proj = t.join("proj");
+    new_repository(proj.to_str().unwrap(), false, 50).unwrap();
+
+    let saved: Vec<_> = [
+        "PATH",
+        "GITHUB_TOKEN",
+        "GH_TOKEN",
+        "GITHUB_TOKEN_FILE",
+        "MDCODE_TEST_BARE_REMOTE",
+    ]
+    .iter()
+    .map(|k| (*k, std::env::var_os(k)))
+    .collect();
+    std::env::set_var(
+        "PATH",
+        format!("{}:{}", bin.display(), std::env::var("PATH").unwrap()),
+    );
+    std::env::remove_var("GH_TOKEN");
+    std::env::remove_var("GITHUB_TOKEN_FILE");
+    match token {
+        Some(tok) => std::env::set_var("GITHUB_TOKEN", tok),
+        None => std::env::remove_var("GITHUB_TOKEN"),
+    }
+    if let Some(url) = bare_remote {
+        std::env::set_var("MDCODE_TEST_BARE_REMOTE", url);
+    }
+    let res = execute_cli(create_cli(proj.to_str().unwrap())).map_err(|e| e.to_string());
+    for (k, v) in saved {
+        match v {
+            Some(v) => std::env::set_var(k, v),
+            None => std::env::remove_var(k),
+        }
+    }
+    (res, log)
+}
+
+#[test]
+#[serial_test::serial]
+fn test_gh_create_unauthenticated_cli_without_token_explains_options() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (res, log) = run_logged_out(t.path(), None, None);
+    let msg = res.unwrap_err();
+    assert!(msg.contains("not authenticated"), "{}", msg);
+    assert!(msg.contains("gh auth login"), "{}", msg);
+    assert!(msg.contains("GITHUB_TOKEN"), "{}", msg);
+    assert!(!log.exists(), "gh repo create must not run");
+}
+
+#[cfg(feature = "offline_gh")]
+#[test]
+#[serial_test::serial]
+fn test_gh_create_unauthenticated_cli_falls_back_to_api_token() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let bare = t.path().join("remote.git");
+    git2::Repository::init_bare(&bare).unwrap();
+    let url = format!("file://{}", bare.display());
+    let (res, log) = run_logged_out(t.path(), Some("ghp_test"), Some(&url));
+    res.unwrap();
+    assert!(!log.exists(), "gh repo create must not run");
+    let remote = git2::Repository::open_bare(&bare).unwrap();
+    assert!(remote.find_reference("refs/heads/master").is_ok());
+}
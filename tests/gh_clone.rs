@@ -0,0 +1,77 @@
+use git2::Repository;
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_gh_clone_depth_one_from_local_bare() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let work = t.path().join("work");
+    let work_s = work.to_str().unwrap();
+    new_repository(work_s, false, 50).unwrap();
+    for i in 0..3 {
+        std::fs::write(work.join(format!("f{}.txt", i)), "x").unwrap();
+        update_repository(work_s, false, Some("more"), 50).unwrap();
+    }
+    let bare = t.path().join("remote.git");
+    assert!(Command::new("git")
+        .args(["clone", "--bare", "-q", work_s, bare.to_str().unwrap()])
+        .status()
+        .unwrap()
+        .success());
+
+    // file:// is required for git to honor --depth on a local source.
+    let url = format!("file://{}", bare.to_str().unwrap());
+    let dest = t.path().join("shallow");
+    let out = gh_clone(&url, Some(dest.to_str().unwrap()), Some(1), false).unwrap();
+    assert_eq!(out, dest);
+
+    let repo = Repository::open(&dest).unwrap();
+    assert!(repo.is_shallow());
+    let mut walk = repo.revwalk().unwrap();
+    walk.push_head().unwrap();
+    assert_eq!(walk.count(), 1);
+    assert!(dest.join("f2.txt").is_file());
+
+    // A plain path gets the same shallow history instead of a full local copy.
+    let plain = t.path().join("plain");
+    gh_clone(
+        bare.to_str().unwrap(),
+        Some(plain.to_str().unwrap()),
+        Some(1),
+        false,
+    )
+    .unwrap();
+    let repo = Repository::open(&plain).unwrap();
+    assert!(repo.is_shallow());
+    let mut walk = repo.revwalk().unwrap();
+    walk.push_head().unwrap();
+    assert_eq!(walk.count(), 1);
+
+    // Refuses a non-empty destination and a zero depth.
+    assert!(gh_clone(&url, Some(dest.to_str().unwrap()), None, false).is_err());
+    let err = gh_clone(&url, Some("unused"), Some(0), false).unwrap_err();
+    assert!(err.to_string().contains("--depth"));
+}
+
+#[test]
+fn test_resolve_clone_source_shorthand_and_passthrough() {
+    assert_eq!(
+        resolve_clone_source("octo/project"),
+        "https://github.com/octo/project.git"
+    );
+    assert_eq!(
+        resolve_clone_source("git@github.com:octo/project.git"),
+        "git@github.com:octo/project.git"
+    );
+    assert_eq!(
+        resolve_clone_source("https://example.com/a/b.git"),
+        "https://example.com/a/b.git"
+    );
+    let out = gh_clone("octo/project", None, Some(1), true).unwrap();
+    assert_eq!(out, std::path::PathBuf::from("project"));
+}
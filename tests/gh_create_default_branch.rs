@@ -0,0 +1,158 @@
+use mdcode::*;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+// Write an executable `gh` shim that appends its arguments to `log` and exits with `code`.
+#[cfg(unix)]
+fn write_gh_shim(bin_dir: &Path, log: &Path, code: i32) -> PathBuf {
+    std::fs::create_dir_all(bin_dir).unwrap();
+    let gh = bin_dir.join("gh");
+    let mut f = std::fs::File::create(&gh).unwrap();
+    writeln!(f, "#!/bin/sh").unwrap();
+    writeln!(f, "echo \"$@\" >> '{}'", log.display()).unwrap();
+    writeln!(f, "exit {}", code).unwrap();
+    use std::os::unix::fs::PermissionsExt;
+    let mut p = std::fs::metadata(&gh).unwrap().permissions();
+    p.set_mode(0o755);
+    std::fs::set_permissions(&gh, p).unwrap();
+    gh
+}
+
+fn calls(log: &Path) -> Vec<String> {
+    std::fs::read_to_string(log)
+        .unwrap_or_default()
+        .lines()
+        .map(|l| l.to_string())
+        .collect()
+}
+
+#[test]
+fn test_default_branch_api_request() {
+    let (path, body) = github_default_branch_request("me/proj", "main");
+    assert_eq!(path, "/repos/me/proj");
+    assert_eq!(body, serde_json::json!({ "default_branch": "main" }));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_gh_set_default_branch_via_cli_runs_repo_edit() {
+    let t = tempdir().unwrap();
+    let log = t.path().join("gh.log");
+    let dir = t.path().to_str().unwrap();
+    let gh = write_gh_shim(&t.path().join("bin"), &log, 0);
+    gh_set_default_branch_via_cli(&gh, dir, "main").unwrap();
+    assert_eq!(calls(&log), vec!["repo edit --default-branch main"]);
+
+    let gh = write_gh_shim(&t.path().join("bin2"), &log, 1);
+    let err = gh_set_default_branch_via_cli(&gh, dir, "main").unwrap_err();
+    assert!(err.to_string().contains("--default-branch main"));
+}
+
+#[test]
+fn test_set_default_branch_needs_github_and_a_branch() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("proj");
+    let s = repo.to_str().unwrap();
+    std::fs::create_dir_all(&repo).unwrap();
+    std::fs::write(repo.join("main.rs"), "fn main() {}\n").unwrap();
+    new_repository(s, false, 50).unwrap();
+    let branch = current_branch_name(s).unwrap();
+    assert!(!branch.is_empty());
+
+    let cli = Cli {
+        command: Commands::GhCreate {
+            directory: Some(s.to_string()),
+            description: None,
+            public: false,
+            private: true,
+            internal: false,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Gitlab,
+            org: None,
+            set_default_branch: true,
+            remote_url: None,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    let err = execute_cli(cli).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "--set-default-branch is only supported with --provider github"
+    );
+
+    let git = git2::Repository::open(&repo).unwrap();
+    let head = git.head().unwrap().target().unwrap();
+    git.set_head_detached(head).unwrap();
+    assert!(current_branch_name(s)
+        .unwrap_err()
+        .to_string()
+        .contains("detached"));
+}
+
+#[cfg(all(unix, not(coverage)))]
+#[test]
+fn test_gh_create_cli_path_sets_default_branch_after_push() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let log = t.path().join("gh.log");
+    let bin = t.path().join("bin");
+    write_gh_shim(&bin, &log, 0);
+    let repo = t.path().join("proj");
+    std::fs::create_dir_all(&repo).unwrap();
+    std::fs::write(repo.join("main.rs"), "fn main() {}\n").unwrap();
+    new_repository(repo.to_str().unwrap(), false, 50).unwrap();
+    let branch = current_branch_name(repo.to_str().unwrap()).unwrap();
+
+    let path = format!(
+        "{}:{}",
+        bin.to_str().unwrap(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_mdcode"))
+        .env("PATH", path)
+        .args([
+            "gh_create",
+            repo.to_str().unwrap(),
+            "--private",
+            "--set-default-branch",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let calls = calls(&log);
+    let create = calls
+        .iter()
+        .position(|c| c.starts_with("repo create proj"))
+        .expect("gh repo create not called");
+    let edit = calls
+        .iter()
+        .position(|c| *c == format!("repo edit --default-branch {}", branch))
+        .expect("gh repo edit not called");
+    assert!(create < edit, "{:?}", calls);
+}
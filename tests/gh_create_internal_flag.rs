@@ -32,25 +32,81 @@ fn test_gh_create_internal_flag_via_execute_cli() {
     );
     std::env::set_var("PATH", new_path);
 
-    // Create a dummy working directory; we don't need a real git repo for the GhCreate CLI path
+    // Create a working repository with an initial commit (gh_create refuses empty dirs)
     let t = tempdir().unwrap();
     let dir = t.path().join("project_internal");
     std::fs::create_dir_all(&dir).unwrap();
     let dir_str = dir.to_str().unwrap().to_string();
+    new_repository(&dir_str, false, 50).unwrap();
 
     let cli = Cli {
         command: Commands::GhCreate {
-            directory: dir_str,
+            directory: Some(dir_str),
             description: Some("d".to_string()),
             public: false,
             private: false,
             internal: true, // the path we want to cover
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: Some("acme".to_string()),
+            set_default_branch: false,
+            remote_url: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     execute_cli(cli).unwrap();
 
+    // Without --org the request is rejected before gh is ever invoked.
+    let cli = Cli {
+        command: Commands::GhCreate {
+            directory: Some(dir.to_str().unwrap().to_string()),
+            description: None,
+            public: false,
+            private: false,
+            internal: true,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: None,
+            set_default_branch: false,
+            remote_url: None,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    let err = execute_cli(cli).unwrap_err();
+    assert!(
+        err.to_string().contains("--internal requires --org"),
+        "{}",
+        err
+    );
+
     // Restore PATH
     if let Some(p) = orig_path {
         std::env::set_var("PATH", p);
@@ -12,14 +12,32 @@ fn test_gh_create_conflicting_flags_error_via_execute_cli() {
 
     let cli = Cli {
         command: Commands::GhCreate {
-            directory: repo_str,
+            directory: Some(repo_str),
             description: None,
             public: true,
             private: true, // conflicting with public
             internal: false,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: None,
+            set_default_branch: false,
+            remote_url: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     let err = execute_cli(cli).expect_err("conflicting flags should error");
     assert!(err.to_string().contains("Provide only one of"));
@@ -45,7 +63,7 @@ fn test_gh_create_resolves_dot_directory_name_and_invokes_cli() {
             "if [ \"$1\" = \"--version\" ]; then echo gh version; exit 0; fi"
         )
         .unwrap();
-        writeln!(f, "echo \"$@\" > {}", log_path.to_string_lossy()).unwrap();
+        writeln!(f, "echo \"$@\" >> {}", log_path.to_string_lossy()).unwrap();
         writeln!(f, "exit 0").unwrap();
         use std::os::unix::fs::PermissionsExt;
         let mut p = std::fs::metadata(&gh_path).unwrap().permissions();
@@ -66,6 +84,7 @@ fn test_gh_create_resolves_dot_directory_name_and_invokes_cli() {
     let work = tempdir().unwrap();
     let proj = work.path().join("project_dot_name");
     std::fs::create_dir_all(&proj).unwrap();
+    new_repository(proj.to_str().unwrap(), false, 50).unwrap();
 
     // Change current_dir for this test so "." resolves to `project_dot_name`.
     let orig_cwd = std::env::current_dir().unwrap();
@@ -73,14 +92,32 @@ fn test_gh_create_resolves_dot_directory_name_and_invokes_cli() {
 
     let cli = Cli {
         command: Commands::GhCreate {
-            directory: ".".to_string(),
+            directory: Some(".".to_string()),
             description: Some("desc".to_string()),
             public: false,
             private: false,
             internal: false,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: None,
+            set_default_branch: false,
+            remote_url: None,
         },
         dry_run: false,
-        max_file_mb: 50,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
     };
     // This should go down the CLI path and invoke our shim.
     execute_cli(cli).unwrap();
@@ -91,8 +128,16 @@ fn test_gh_create_resolves_dot_directory_name_and_invokes_cli() {
     }
     std::env::set_current_dir(&orig_cwd).unwrap();
 
-    // Verify the first non-`--version` invocation captured the repo name argument.
-    let args = std::fs::read_to_string(&log_path).unwrap();
+    // `gh auth status` runs first; then the `repo create` invocation carries the repo name.
+    let log = std::fs::read_to_string(&log_path).unwrap();
+    assert_eq!(
+        log.lines().next(),
+        Some("auth status --hostname github.com")
+    );
+    let args = log
+        .lines()
+        .find(|l| l.starts_with("repo "))
+        .unwrap_or_default();
     // Expect pattern: repo create <name> --source . --remote origin --push ...
     let parts: Vec<&str> = args.split_whitespace().collect();
     assert!(parts.len() >= 4, "logged args too short: {}", args);
@@ -0,0 +1,127 @@
+use mdcode::*;
+use std::io::Write as _;
+use tempfile::tempdir;
+
+fn gh_shim(bin_dir: &std::path::Path, log_path: &std::path::Path) {
+    std::fs::create_dir_all(bin_dir).unwrap();
+    let gh_path = bin_dir.join("gh");
+    let mut f = std::fs::File::create(&gh_path).unwrap();
+    writeln!(f, "#!/bin/sh").unwrap();
+    writeln!(
+        f,
+        "if [ \"$1\" = \"--version\" ]; then echo gh version; exit 0; fi"
+    )
+    .unwrap();
+    writeln!(f, "echo \"$@\" >> {}", log_path.to_string_lossy()).unwrap();
+    writeln!(f, "exit 0").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut p = std::fs::metadata(&gh_path).unwrap().permissions();
+        p.set_mode(0o755);
+        std::fs::set_permissions(&gh_path, p).unwrap();
+    }
+}
+
+fn create_cli(dir: &str, init_if_missing: bool) -> Cli {
+    Cli {
+        command: Commands::GhCreate {
+            directory: Some(dir.to_string()),
+            description: None,
+            public: false,
+            private: true,
+            internal: false,
+            init_if_missing,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: None,
+            set_default_branch: false,
+            remote_url: None,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    }
+}
+
+#[test]
+#[serial_test::serial]
+#[cfg(unix)]
+fn test_gh_create_refuses_repo_without_commits() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let temp = tempdir().unwrap();
+    let log_path = temp.path().join("gh_args.txt");
+    gh_shim(&temp.path().join("bin"), &log_path);
+    let orig_path = std::env::var_os("PATH");
+    std::env::set_var(
+        "PATH",
+        format!(
+            "{}:{}",
+            temp.path().join("bin").display(),
+            std::env::var("PATH").unwrap()
+        ),
+    );
+
+    let proj = temp.path().join("empty_proj");
+    git2::Repository::init(&proj).unwrap();
+    let res = execute_cli(create_cli(proj.to_str().unwrap(), false));
+
+    if let Some(p) = orig_path {
+        std::env::set_var("PATH", p);
+    }
+    let msg = res.unwrap_err().to_string();
+    assert!(msg.contains("has no commits"), "{}", msg);
+    assert!(msg.contains("--init-if-missing"), "{}", msg);
+    assert!(!log_path.exists(), "gh must not be invoked");
+}
+
+#[test]
+#[serial_test::serial]
+#[cfg(unix)]
+fn test_gh_create_init_if_missing_creates_initial_commit() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let temp = tempdir().unwrap();
+    let log_path = temp.path().join("gh_args.txt");
+    gh_shim(&temp.path().join("bin"), &log_path);
+    let orig_path = std::env::var_os("PATH");
+    std::env::set_var(
+        "PATH",
+        format!(
+            "{}:{}",
+            temp.path().join("bin").display(),
+            std::env::var("PATH").unwrap()
+        ),
+    );
+
+    let proj = temp.path().join("fresh_proj");
+    std::fs::create_dir_all(&proj).unwrap();
+    std::fs::write(proj.join("main.rs"), "fn main() {}\n").unwrap();
+    let res = execute_cli(create_cli(proj.to_str().unwrap(), true));
+
+    if let Some(p) = orig_path {
+        std::env::set_var("PATH", p);
+    }
+    res.unwrap();
+    let repo = git2::Repository::open(&proj).unwrap();
+    assert!(repo.head().is_ok());
+    assert!(std::fs::read_to_string(&log_path)
+        .unwrap()
+        .contains("repo create fresh_proj"));
+}
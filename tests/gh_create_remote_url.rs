@@ -0,0 +1,164 @@
+use clap::Parser;
+use mdcode::*;
+use tempfile::tempdir;
+
+fn create_cli(dir: &str, url: &str) -> Cli {
+    Cli {
+        command: Commands::GhCreate {
+            directory: Some(dir.to_string()),
+            description: None,
+            public: false,
+            private: false,
+            internal: false,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Github,
+            org: None,
+            set_default_branch: false,
+            remote_url: Some(url.to_string()),
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    }
+}
+
+fn remote_head(bare: &std::path::Path, branch: &str) -> git2::Oid {
+    git2::Repository::open_bare(bare)
+        .unwrap()
+        .find_reference(&format!("refs/heads/{}", branch))
+        .unwrap()
+        .target()
+        .unwrap()
+}
+
+fn local_repo(root: &std::path::Path) -> (String, git2::Oid, String) {
+    let dir = root.join("work");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    let s = dir.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    let repo = git2::Repository::open(&dir).unwrap();
+    let head = repo.head().unwrap();
+    let branch = head.shorthand().unwrap().to_string();
+    (s, head.target().unwrap(), branch)
+}
+
+#[test]
+fn test_gh_create_remote_url_pushes_to_bare_repository_path() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let (dir, head, branch) = local_repo(tmp.path());
+    let bare = tmp.path().join("server.git");
+    git2::Repository::init_bare(&bare).unwrap();
+
+    // A dry run adds nothing.
+    let mut cli = create_cli(&dir, bare.to_str().unwrap());
+    cli.dry_run = true;
+    execute_cli(cli).unwrap();
+    assert!(git2::Repository::open(&dir)
+        .unwrap()
+        .find_remote("origin")
+        .is_err());
+
+    execute_cli(create_cli(&dir, bare.to_str().unwrap())).unwrap();
+    let repo = git2::Repository::open(&dir).unwrap();
+    assert_eq!(repo.find_remote("origin").unwrap().url(), bare.to_str());
+    assert_eq!(remote_head(&bare, &branch), head);
+
+    // Re-running with the same URL is fine; a different one is refused.
+    execute_cli(create_cli(&dir, bare.to_str().unwrap())).unwrap();
+    let other = tmp.path().join("other.git");
+    git2::Repository::init_bare(&other).unwrap();
+    let err = execute_cli(create_cli(&dir, other.to_str().unwrap())).unwrap_err();
+    assert!(err.to_string().contains("already points to"), "{}", err);
+}
+
+#[test]
+fn test_gh_create_remote_url_accepts_file_url() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let (dir, head, branch) = local_repo(tmp.path());
+    let bare = tmp.path().join("server.git");
+    git2::Repository::init_bare(&bare).unwrap();
+    let url = format!("file://{}", bare.to_str().unwrap());
+
+    gh_create_with_remote_url(&dir, &url, false).unwrap();
+    assert_eq!(remote_head(&bare, &branch), head);
+}
+
+#[test]
+fn test_gh_create_remote_url_validation_and_conflicts() {
+    let tmp = tempdir().unwrap();
+    let missing = tmp.path().join("nope.git");
+    assert!(validate_remote_url(missing.to_str().unwrap())
+        .unwrap_err()
+        .to_string()
+        .contains("no such path"));
+    assert!(validate_remote_url("https://").is_err());
+    assert!(validate_remote_url("").is_err());
+    assert_eq!(
+        validate_remote_url("https://git.example.com/me/proj.git").unwrap(),
+        RemoteUrlKind::Https
+    );
+    assert_eq!(
+        validate_remote_url("git@git.example.com:me/proj.git").unwrap(),
+        RemoteUrlKind::Ssh
+    );
+
+    for flag in [
+        "--public",
+        "--private",
+        "--internal",
+        "--set-default-branch",
+    ] {
+        let parsed = Cli::try_parse_from([
+            "mdcode",
+            "gh_create",
+            ".",
+            "--remote-url",
+            "https://git.example.com/me/proj.git",
+            flag,
+        ]);
+        assert!(
+            parsed.is_err(),
+            "{} should conflict with --remote-url",
+            flag
+        );
+    }
+    let parsed = Cli::try_parse_from([
+        "mdcode",
+        "gh_create",
+        ".",
+        "--remote-url",
+        "https://git.example.com/me/proj.git",
+        "--description",
+        "x",
+    ]);
+    assert!(parsed.is_err());
+    assert!(Cli::try_parse_from([
+        "mdcode",
+        "gh_create",
+        ".",
+        "--remote-url",
+        "https://git.example.com/me/proj.git",
+    ])
+    .is_ok());
+}
@@ -0,0 +1,88 @@
+use mdcode::*;
+use std::io::Write as _;
+use tempfile::tempdir;
+
+// Write an executable `gh` shim that answers `repo view` with JSON and succeeds otherwise.
+#[cfg(unix)]
+fn write_gh_shim(bin_dir: &std::path::Path, view_json: Option<&str>) -> std::path::PathBuf {
+    std::fs::create_dir_all(bin_dir).unwrap();
+    let gh = bin_dir.join("gh");
+    let mut f = std::fs::File::create(&gh).unwrap();
+    writeln!(f, "#!/bin/sh").unwrap();
+    match view_json {
+        Some(json) => writeln!(
+            f,
+            "if [ \"$1\" = \"repo\" ] && [ \"$2\" = \"view\" ]; then echo '{}'; exit 0; fi",
+            json
+        )
+        .unwrap(),
+        None => writeln!(
+            f,
+            "if [ \"$1\" = \"repo\" ] && [ \"$2\" = \"view\" ]; then exit 1; fi"
+        )
+        .unwrap(),
+    }
+    writeln!(f, "exit 0").unwrap();
+    use std::os::unix::fs::PermissionsExt;
+    let mut p = std::fs::metadata(&gh).unwrap().permissions();
+    p.set_mode(0o755);
+    std::fs::set_permissions(&gh, p).unwrap();
+    gh
+}
+
+#[cfg(unix)]
+#[test]
+fn test_gh_repo_summary_uses_repo_view_and_falls_back() {
+    let t = tempdir().unwrap();
+    let gh = write_gh_shim(
+        &t.path().join("bin"),
+        Some(r#"{"name":"proj","url":"https://github.com/me/proj","visibility":"PUBLIC"}"#),
+    );
+    let dir = t.path().to_str().unwrap();
+    assert_eq!(
+        gh_repo_summary(&gh, dir, "proj", RepoVisibility::Private),
+        "Created GitHub repository: proj (public) https://github.com/me/proj"
+    );
+
+    let gh = write_gh_shim(&t.path().join("bin2"), None);
+    assert_eq!(
+        gh_repo_summary(&gh, dir, "proj", RepoVisibility::Internal),
+        "Created GitHub repository: proj (internal) (url unknown)"
+    );
+}
+
+#[cfg(all(unix, not(coverage)))]
+#[test]
+fn test_gh_create_cli_path_prints_summary_line() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let bin = t.path().join("bin");
+    write_gh_shim(
+        &bin,
+        Some(r#"{"name":"proj","url":"https://github.com/me/proj","visibility":"PRIVATE"}"#),
+    );
+    let repo = t.path().join("proj");
+    std::fs::create_dir_all(&repo).unwrap();
+    new_repository(repo.to_str().unwrap(), false, 50).unwrap();
+
+    let path = format!(
+        "{}:{}",
+        bin.to_str().unwrap(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_mdcode"))
+        .env("PATH", path)
+        .args(["gh_create", repo.to_str().unwrap(), "--private"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains("Created GitHub repository: proj (private) https://github.com/me/proj"),
+        "missing summary in: {}",
+        stdout
+    );
+}
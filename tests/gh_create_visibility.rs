@@ -0,0 +1,66 @@
+use mdcode::*;
+
+#[test]
+fn test_github_create_payload_carries_visibility() {
+    let public = github_create_payload("n", Some("d"), RepoVisibility::Public);
+    assert_eq!(public["private"], false);
+    assert_eq!(public["visibility"], "public");
+    assert_eq!(public["description"], "d");
+
+    let private = github_create_payload("n", None, RepoVisibility::Private);
+    assert_eq!(private["private"], true);
+    assert_eq!(private["visibility"], "private");
+    assert_eq!(private["description"], "");
+
+    let internal = github_create_payload("n", None, RepoVisibility::Internal);
+    assert_eq!(internal["private"], true);
+    assert_eq!(internal["visibility"], "internal");
+    assert_eq!(internal["name"], "n");
+}
+
+#[test]
+fn test_github_create_path_and_org_validation() {
+    assert_eq!(github_create_path(None), "/user/repos");
+    assert_eq!(github_create_path(Some("acme")), "/orgs/acme/repos");
+
+    assert!(check_create_visibility(RepoVisibility::Internal, Some("acme")).is_ok());
+    assert!(check_create_visibility(RepoVisibility::Private, None).is_ok());
+    let err = check_create_visibility(RepoVisibility::Internal, None).unwrap_err();
+    assert!(err.to_string().contains("--internal requires --org"));
+}
+
+#[test]
+fn test_visibility_mismatch_warning() {
+    assert_eq!(
+        visibility_mismatch_warning(RepoVisibility::Internal, "internal"),
+        None
+    );
+    assert_eq!(
+        visibility_mismatch_warning(RepoVisibility::Internal, "PRIVATE").as_deref(),
+        Some("Requested internal visibility but GitHub created the repository as private")
+    );
+}
+
+#[cfg(feature = "offline_gh")]
+#[test]
+#[serial_test::serial]
+fn test_offline_github_provider_echoes_requested_visibility() {
+    std::env::set_var("MDCODE_TEST_BARE_REMOTE", "/tmp/mdcode-visibility.git");
+    let org = ProviderKind::Github.remote_provider(None, Some("acme".into()));
+    let created = org
+        .create_repo("n", None, RepoVisibility::Internal)
+        .unwrap();
+    assert_eq!(created.visibility, "internal");
+    let personal = ProviderKind::Github.remote_provider(None, None);
+    let created = personal
+        .create_repo("n", None, RepoVisibility::Private)
+        .unwrap();
+    assert_eq!(created.visibility, "private");
+    std::env::remove_var("MDCODE_TEST_BARE_REMOTE");
+
+    // Rejected before anything is created, even with the stub remote unset.
+    let err = personal
+        .create_repo("n", None, RepoVisibility::Internal)
+        .unwrap_err();
+    assert!(err.to_string().contains("--internal requires --org"));
+}
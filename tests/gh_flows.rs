@@ -107,7 +107,7 @@ fn test_resolve_signature_precedence() {
     // Only set author; committer unset should still use author values
     std::env::set_var("GIT_AUTHOR_NAME", "Author Name");
     std::env::set_var("GIT_AUTHOR_EMAIL", "author@example.com");
-    let (sig, src) = resolve_signature_with_source(&repo).unwrap();
+    let (sig, src) = resolve_signature_with_source(&repo, &SignatureOptions::default()).unwrap();
     assert_eq!(sig.name(), Some("Author Name"));
     assert_eq!(sig.email(), Some("author@example.com"));
     assert_eq!(src, "env:GIT_AUTHOR_NAME/GIT_AUTHOR_EMAIL");
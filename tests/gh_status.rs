@@ -0,0 +1,168 @@
+use git2::Repository;
+use mdcode::*;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use tempfile::tempdir;
+
+/// Answers API paths from canned JSON and records what was requested.
+struct CannedApi {
+    responses: HashMap<String, Value>,
+    requested: RefCell<Vec<String>>,
+}
+
+impl ChecksApi for CannedApi {
+    fn get_json(&self, path: &str) -> Result<Value, Box<dyn Error>> {
+        self.requested.borrow_mut().push(path.to_string());
+        let key = path.split('?').next().unwrap().rsplit('/').next().unwrap();
+        self.responses
+            .get(key)
+            .cloned()
+            .ok_or_else(|| format!("unexpected request {}", path).into())
+    }
+}
+
+fn canned(runs: Value, statuses: Value) -> CannedApi {
+    let mut responses = HashMap::new();
+    responses.insert("check-runs".to_string(), json!({ "check_runs": runs }));
+    responses.insert("status".to_string(), json!({ "statuses": statuses }));
+    CannedApi {
+        responses,
+        requested: RefCell::new(Vec::new()),
+    }
+}
+
+fn repo_with_origin(t: &std::path::Path, url: &str) -> (String, String) {
+    let dir = t.join("work");
+    let s = dir.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    let repo = Repository::open(&dir).unwrap();
+    repo.remote("origin", url).unwrap();
+    let sha = repo.head().unwrap().target().unwrap().to_string();
+    (s, sha)
+}
+
+fn run(name: &str, status: &str, conclusion: Value) -> Value {
+    json!({
+        "name": name,
+        "status": status,
+        "conclusion": conclusion,
+        "html_url": format!("https://github.com/o/r/runs/{}", name),
+    })
+}
+
+#[test]
+fn test_gh_status_summarizes_check_runs_and_statuses() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (dir, sha) = repo_with_origin(t.path(), "git@github.com:octo/widgets.git");
+
+    let green = canned(
+        json!([
+            run("build", "completed", json!("success")),
+            run("lint", "completed", json!("skipped"))
+        ]),
+        json!([{ "context": "ci/legacy", "state": "success", "target_url": "https://ci.example/1" }]),
+    );
+    assert_eq!(
+        gh_status_with_api(&dir, &green).unwrap(),
+        CheckState::Success
+    );
+    assert_eq!(
+        *green.requested.borrow(),
+        vec![
+            format!(
+                "/repos/octo/widgets/commits/{}/check-runs?per_page=100",
+                sha
+            ),
+            format!("/repos/octo/widgets/commits/{}/status", sha),
+        ]
+    );
+
+    let pending = canned(
+        json!([
+            run("build", "completed", json!("success")),
+            run("test", "in_progress", Value::Null)
+        ]),
+        json!([]),
+    );
+    assert_eq!(
+        gh_status_with_api(&dir, &pending).unwrap(),
+        CheckState::Pending
+    );
+
+    // A failure wins over pending checks, including legacy statuses.
+    let failing = canned(
+        json!([run("test", "queued", Value::Null)]),
+        json!([{ "context": "ci/legacy", "state": "error" }]),
+    );
+    assert_eq!(
+        gh_status_with_api(&dir, &failing).unwrap(),
+        CheckState::Failure
+    );
+
+    // No checks at all is not an error.
+    let none = canned(json!([]), json!([]));
+    assert_eq!(
+        gh_status_with_api(&dir, &none).unwrap(),
+        CheckState::Success
+    );
+}
+
+#[test]
+#[serial_test::serial]
+fn test_check_states_and_lines() {
+    let checks = vec![
+        CheckResult {
+            name: "build".into(),
+            status: "completed".into(),
+            conclusion: Some("cancelled".into()),
+            url: None,
+        },
+        CheckResult {
+            name: "docs".into(),
+            status: "queued".into(),
+            conclusion: None,
+            url: Some("https://x/1".into()),
+        },
+    ];
+    assert_eq!(checks[0].state(), CheckState::Failure);
+    assert_eq!(checks[1].state(), CheckState::Pending);
+    assert_eq!(overall_state(&checks), Some(CheckState::Failure));
+    assert_eq!(overall_state(&checks[1..]), Some(CheckState::Pending));
+    assert_eq!(overall_state(&[]), None);
+    assert_eq!(CheckState::Pending as i32, 2);
+
+    std::env::set_var("NO_COLOR", "1");
+    assert_eq!(
+        format_check_line(&checks[0]),
+        "build  completed/cancelled  -"
+    );
+    assert_eq!(format_check_line(&checks[1]), "docs  queued  https://x/1");
+    std::env::remove_var("NO_COLOR");
+
+    let err = MdcodeError::ExitCode(2, "CI checks still running".into());
+    assert_eq!(err.exit_code(), 2);
+    assert_eq!(err.to_string(), "CI checks still running");
+    assert_eq!(MdcodeError::NoRepository("x".into()).exit_code(), 3);
+}
+
+#[test]
+fn test_gh_status_requires_github_origin() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (dir, _) = repo_with_origin(t.path(), "https://gitlab.com/octo/widgets.git");
+    let err = gh_status_with_api(&dir, &canned(json!([]), json!([]))).unwrap_err();
+    assert!(
+        err.to_string().contains("is not a GitHub repository"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,136 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+fn commit_file(dir: &Path, name: &str) {
+    std::fs::write(dir.join(name), name).unwrap();
+    git(dir, &["add", name]);
+    git(dir, &["commit", "-q", "-m", name]);
+}
+
+fn branch_oid(repo: &Repository, refname: &str) -> git2::Oid {
+    repo.refname_to_id(refname).unwrap()
+}
+
+#[test]
+fn test_gh_sync_all_fast_forwards_every_tracking_branch() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let bare = tmp.path().join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+
+    // A publishes the default branch and 'feature'.
+    let a = tmp.path().join("A");
+    let a_s = a.to_str().unwrap();
+    new_repository(a_s, false, 50).unwrap();
+    add_remote(a_s, "origin", bare.to_str().unwrap()).unwrap();
+    git(&a, &["config", "user.name", "A"]);
+    git(&a, &["config", "user.email", "a@example.com"]);
+    let main = Repository::open(&a)
+        .unwrap()
+        .head()
+        .unwrap()
+        .shorthand()
+        .unwrap()
+        .to_string();
+    git(&a, &["push", "-q", "origin", &main]);
+    git(&a, &["checkout", "-q", "-b", "feature"]);
+    commit_file(&a, "f1.txt");
+    git(&a, &["push", "-q", "origin", "feature"]);
+    git(&a, &["checkout", "-q", &main]);
+
+    // B clones and tracks both branches, staying on the default branch.
+    let b = tmp.path().join("B");
+    let b_s = b.to_str().unwrap();
+    let status = Command::new("git")
+        .args(["clone", "-q"])
+        .arg(&bare)
+        .arg(&b)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    git(
+        &b,
+        &["branch", "-q", "--track", "feature", "origin/feature"],
+    );
+    git(&b, &["config", "user.name", "B"]);
+    git(&b, &["config", "user.email", "b@example.com"]);
+
+    // Nothing to do yet.
+    let results = gh_sync_all(b_s, "origin", None).unwrap();
+    assert_eq!(
+        results,
+        vec![
+            ("feature".to_string(), SyncAction::UpToDate),
+            (main.clone(), SyncAction::UpToDate),
+        ]
+    );
+
+    // Advance both branches remotely.
+    commit_file(&a, "m2.txt");
+    git(&a, &["push", "-q", "origin", &main]);
+    git(&a, &["checkout", "-q", "feature"]);
+    commit_file(&a, "f2.txt");
+    git(&a, &["push", "-q", "origin", "feature"]);
+
+    let results = gh_sync_all(b_s, "origin", None).unwrap();
+    assert_eq!(
+        results,
+        vec![
+            ("feature".to_string(), SyncAction::FastForwarded),
+            (main.clone(), SyncAction::FastForwarded),
+        ]
+    );
+    let repo_a = Repository::open(&a).unwrap();
+    let repo_b = Repository::open(&b).unwrap();
+    for branch in ["feature", main.as_str()] {
+        let refname = format!("refs/heads/{}", branch);
+        assert_eq!(branch_oid(&repo_b, &refname), branch_oid(&repo_a, &refname));
+    }
+    // Still on the original branch; only its worktree was updated.
+    assert_eq!(repo_b.head().unwrap().shorthand(), Some(main.as_str()));
+    assert!(b.join("m2.txt").exists());
+    assert!(!b.join("f2.txt").exists());
+    assert!(!is_dirty(b_s).unwrap());
+
+    // Local and remote commits on 'feature' diverge; a local-only commit is just ahead.
+    commit_file(&a, "f3.txt");
+    git(&a, &["push", "-q", "origin", "feature"]);
+    git(&b, &["checkout", "-q", "feature"]);
+    commit_file(&b, "local-f.txt");
+    git(&b, &["checkout", "-q", &main]);
+    commit_file(&b, "local-m.txt");
+    let before = branch_oid(&repo_b, "refs/heads/feature");
+    let results = gh_sync_all(b_s, "origin", None).unwrap();
+    assert_eq!(
+        results,
+        vec![
+            ("feature".to_string(), SyncAction::Diverged),
+            (main.clone(), SyncAction::Ahead),
+        ]
+    );
+    assert_eq!(branch_oid(&repo_b, "refs/heads/feature"), before);
+
+    let table = format_sync_table(&results);
+    let lines: Vec<&str> = table.lines().collect();
+    assert!(lines[0].starts_with("BRANCH"));
+    assert!(
+        lines[1].starts_with("feature") && lines[1].ends_with("diverged (needs manual attention)")
+    );
+    assert!(lines[2].ends_with("ahead (push needed)"));
+}
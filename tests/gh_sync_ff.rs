@@ -45,3 +45,106 @@ fn test_gh_sync_fast_forward_success() {
     // Now sync on B should fast-forward and succeed
     gh_sync(b.to_str().unwrap(), "origin").unwrap();
 }
+
+fn head_id(dir: &std::path::Path) -> git2::Oid {
+    Repository::open(dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id()
+}
+
+#[test]
+fn test_gh_sync_ff_only_refuses_diverged_history() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let bare = tmp.path().join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+
+    let a = tmp.path().join("A");
+    let a_s = a.to_str().unwrap();
+    new_repository(a_s, false, 50).unwrap();
+    Repository::open(&a)
+        .unwrap()
+        .remote("origin", bare.to_str().unwrap())
+        .unwrap();
+    gh_push(a_s, "origin").unwrap();
+
+    let b = tmp.path().join("B");
+    let b_s = b.to_str().unwrap();
+    Command::new("git")
+        .arg("clone")
+        .arg(bare.to_str().unwrap())
+        .arg(&b)
+        .status()
+        .unwrap();
+    let mut cfg = Repository::open(&b).unwrap().config().unwrap();
+    cfg.set_str("user.name", "B").unwrap();
+    cfg.set_str("user.email", "b@example.com").unwrap();
+
+    // Remote moves ahead: --ff-only fast-forwards like a plain sync.
+    std::fs::write(a.join("one.md"), "# one").unwrap();
+    update_repository(a_s, false, Some("one"), 50).unwrap();
+    gh_push(a_s, "origin").unwrap();
+    gh_sync_with_ff_only(b_s, "origin", true, None).unwrap();
+    assert_eq!(head_id(&b), head_id(&a));
+
+    // Both sides commit: the histories diverge and --ff-only refuses to merge.
+    std::fs::write(a.join("two.md"), "# two").unwrap();
+    update_repository(a_s, false, Some("two"), 50).unwrap();
+    gh_push(a_s, "origin").unwrap();
+    std::fs::write(b.join("local.md"), "# local").unwrap();
+    update_repository(b_s, false, Some("local"), 50).unwrap();
+    let before = head_id(&b);
+
+    let err = gh_sync_with_ff_only(b_s, "origin", true, None)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("have diverged"), "{}", err);
+    assert!(err.contains("1 local and 1 remote"), "{}", err);
+    assert!(err.contains("--ff-only"), "{}", err);
+    assert_eq!(head_id(&b), before, "no merge commit was created");
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(&b)
+        .args(["status", "--porcelain"])
+        .output()
+        .unwrap();
+    assert!(status.stdout.is_empty());
+
+    // The CLI flag takes the same path.
+    let cli = Cli {
+        command: Commands::GhSync {
+            directory: Some(b_s.to_string()),
+            remote: "origin".to_string(),
+            all: false,
+            ff_only: true,
+            insecure: false,
+            recursive: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    assert!(execute_cli(cli)
+        .unwrap_err()
+        .to_string()
+        .contains("have diverged"));
+    assert_eq!(head_id(&b), before);
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "offline_gh")]
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+#[serial_test::serial]
+fn test_resolve_github_token_precedence() {
+    let t = tempdir().unwrap();
+    let flag_file = t.path().join("flag_token");
+    std::fs::write(&flag_file, "  from-flag-file\n").unwrap();
+    let env_file = t.path().join("env_token");
+    std::fs::write(&env_file, "from-env-file\n").unwrap();
+
+    std::env::set_var("GITHUB_TOKEN_FILE", &env_file);
+    std::env::set_var("GITHUB_TOKEN", "from-github-token");
+    std::env::set_var("GH_TOKEN", "from-gh-token");
+
+    // --token-file wins over everything, and is trimmed.
+    let (token, source) = resolve_github_token(Some(flag_file.to_str().unwrap())).unwrap();
+    assert_eq!(token, "from-flag-file");
+    assert!(source.starts_with("--token-file"), "{}", source);
+
+    // Then GITHUB_TOKEN_FILE.
+    let (token, source) = resolve_github_token(None).unwrap();
+    assert_eq!(token, "from-env-file");
+    assert!(source.starts_with("env:GITHUB_TOKEN_FILE"), "{}", source);
+
+    // Then GITHUB_TOKEN, then GH_TOKEN.
+    std::env::remove_var("GITHUB_TOKEN_FILE");
+    assert_eq!(
+        resolve_github_token(None).unwrap(),
+        (
+            "from-github-token".to_string(),
+            "env:GITHUB_TOKEN".to_string()
+        )
+    );
+    std::env::remove_var("GITHUB_TOKEN");
+    assert_eq!(
+        resolve_github_token(None).unwrap().1,
+        "env:GH_TOKEN".to_string()
+    );
+    std::env::remove_var("GH_TOKEN");
+    assert!(resolve_github_token(None).is_err());
+
+    // Unreadable or empty files are errors rather than silently falling through.
+    let empty = t.path().join("empty");
+    std::fs::write(&empty, " \n").unwrap();
+    let err = resolve_github_token(Some(empty.to_str().unwrap())).unwrap_err();
+    assert!(err.to_string().contains("is empty"));
+    let missing = t.path().join("missing");
+    let err = resolve_github_token(Some(missing.to_str().unwrap())).unwrap_err();
+    assert!(err.to_string().contains("cannot read token file"));
+}
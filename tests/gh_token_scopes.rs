@@ -0,0 +1,53 @@
+use mdcode::*;
+
+#[test]
+fn test_parse_oauth_scopes_header_values() {
+    assert_eq!(parse_oauth_scopes("repo"), vec!["repo"]);
+    assert_eq!(
+        parse_oauth_scopes("repo, read:org, workflow"),
+        vec!["repo", "read:org", "workflow"]
+    );
+    assert_eq!(
+        parse_oauth_scopes("gist,read:user"),
+        vec!["gist", "read:user"]
+    );
+    assert!(parse_oauth_scopes("").is_empty());
+    assert!(parse_oauth_scopes(" , ").is_empty());
+}
+
+#[test]
+fn test_token_scope_error_requires_repo() {
+    assert_eq!(
+        token_scope_error(Some("repo, read:org"), "env:GITHUB_TOKEN"),
+        None
+    );
+
+    let msg = token_scope_error(Some("read:org, gist"), "env:GITHUB_TOKEN").unwrap();
+    assert!(msg.contains("missing the 'repo' scope"), "{}", msg);
+    assert!(msg.contains("granted: read:org, gist"), "{}", msg);
+    assert!(msg.contains("env:GITHUB_TOKEN"), "{}", msg);
+    assert!(msg.contains("gh auth refresh -s repo"), "{}", msg);
+
+    // `public_repo` alone is not enough, and `repo:status` is not `repo`.
+    assert!(token_scope_error(Some("public_repo, repo:status"), "x").is_some());
+    // A classic token with no scopes sends an empty header.
+    let msg = token_scope_error(Some(""), "x").unwrap();
+    assert!(msg.contains("granted: none"), "{}", msg);
+}
+
+#[test]
+fn test_fine_grained_tokens_skip_preflight_and_get_hint() {
+    assert_eq!(token_scope_error(None, "--token-file (t.txt)"), None);
+    let msg = fine_grained_create_hint(
+        "GitHub repository creation failed after 1 attempt(s): HTTP 403: Resource not accessible by personal access token.",
+        "--token-file (t.txt)",
+    );
+    assert!(
+        msg.starts_with("GitHub repository creation failed"),
+        "{}",
+        msg
+    );
+    assert!(msg.contains("fine-grained"), "{}", msg);
+    assert!(msg.contains("Administration"), "{}", msg);
+    assert!(!msg.contains("token.."), "{}", msg);
+}
@@ -30,7 +30,7 @@ fn test_resolve_signature_uses_repo_config_when_no_env() {
         .set_str("user.email", "repo@example.com")
         .unwrap();
 
-    let (sig, src) = resolve_signature_with_source(&repo).unwrap();
+    let (sig, src) = resolve_signature_with_source(&repo, &SignatureOptions::default()).unwrap();
     assert_eq!(sig.name(), Some("Repo User"));
     assert_eq!(sig.email(), Some("repo@example.com"));
     assert!(src.contains("git config"));
@@ -76,9 +76,9 @@ fn test_remote_branch_exists_true_and_false() {
     repo_add_remote(ws, "origin", bare.to_str().unwrap());
     gh_push(ws, "origin").unwrap();
     // true case
-    assert!(remote_branch_exists(ws, "origin", "master").unwrap());
+    assert!(remote_branch_exists(ws, "origin", "master", None).unwrap());
     // false case
-    assert!(!remote_branch_exists(ws, "origin", "nonexist").unwrap());
+    assert!(!remote_branch_exists(ws, "origin", "nonexist", None).unwrap());
 }
 
 fn repo_add_remote(dir: &str, name: &str, url: &str) {
@@ -0,0 +1,92 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+/// Run `f` with a PATH that has no `git` on it, restoring the original afterwards.
+fn without_git<T>(f: impl FnOnce() -> T) -> T {
+    let orig_path = std::env::var_os("PATH");
+    let empty = tempdir().unwrap();
+    std::env::set_var("PATH", empty.path());
+    let out = f();
+    match orig_path {
+        Some(p) => std::env::set_var("PATH", p),
+        None => std::env::remove_var("PATH"),
+    }
+    out
+}
+
+#[cfg(coverage)]
+#[test]
+#[serial_test::serial]
+fn test_new_repository_errors_when_git_missing() {
+    // The coverage build creates repositories through the git CLI.
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("repo");
+    let s = dir.to_str().unwrap();
+    let err = without_git(|| new_repository(s, false, 50)).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("new requires the git CLI for init"));
+}
+
+#[cfg(not(coverage))]
+#[test]
+#[serial_test::serial]
+fn test_read_only_commands_work_without_git() {
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("repo");
+    let s = dir.to_str().unwrap();
+    without_git(|| {
+        assert!(!check_git_installed());
+        new_repository(s, false, 50).unwrap();
+        std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        update_repository(s, false, Some("add main"), 50).unwrap();
+        info_repository(s).unwrap();
+        let versions = vec!["1".to_string(), "0".to_string()];
+        diff_command(s, &versions, true).unwrap();
+        assert_eq!(diff_name_only(s, &versions).unwrap(), vec!["main.rs"]);
+    });
+}
+
+#[cfg(not(coverage))]
+#[test]
+#[serial_test::serial]
+fn test_remote_commands_name_the_git_step_they_need() {
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("repo");
+    let s = dir.to_str().unwrap();
+    let bare = tmp.path().join("remote.git");
+    git2::Repository::init_bare(&bare).unwrap();
+    without_git(|| {
+        new_repository(s, false, 50).unwrap();
+        git2::Repository::open(&dir)
+            .unwrap()
+            .remote("origin", bare.to_str().unwrap())
+            .unwrap();
+
+        let err = gh_push(s, "origin").unwrap_err().to_string();
+        assert!(
+            err.contains("gh_push requires the git CLI for push; install git"),
+            "{}",
+            err
+        );
+
+        let versions = vec!["H".to_string(), "0".to_string()];
+        let err = diff_command(s, &versions, true).unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "H/L-mode diff requires the git CLI for fetch; install git or pass --no-fetch"
+            ),
+            "{}",
+            err
+        );
+        let opts = DiffOptions {
+            no_fetch: true,
+            ..DiffOptions::default()
+        };
+        // With --no-fetch the diff reads origin's tracking refs and never runs git.
+        let err = diff_command_with_options(s, &versions, true, &opts)
+            .unwrap_err()
+            .to_string();
+        assert!(!err.contains("requires the git CLI"), "{}", err);
+    });
+}
@@ -0,0 +1,114 @@
+use mdcode::*;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+/// Put a `git` shim first on PATH that hangs on `fetch` and defers to the real git
+/// otherwise. Returns the original PATH.
+#[cfg(unix)]
+fn install_hanging_fetch_shim(bin: &std::path::Path) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let out = Command::new("which").arg("git").output().unwrap();
+    let real_git = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    std::fs::create_dir_all(bin).unwrap();
+    let shim = bin.join("git");
+    std::fs::write(
+        &shim,
+        format!(
+            "#!/bin/sh\ncase \" $* \" in *\" fetch \"*) exec sleep 30 ;; esac\nexec {} \"$@\"\n",
+            real_git
+        ),
+    )
+    .unwrap();
+    let mut p = std::fs::metadata(&shim).unwrap().permissions();
+    p.set_mode(0o755);
+    std::fs::set_permissions(&shim, p).unwrap();
+    let orig_path = std::env::var("PATH").unwrap();
+    std::env::set_var("PATH", format!("{}:{}", bin.display(), orig_path));
+    orig_path
+}
+
+fn repo_with_remote(root: &std::path::Path) -> String {
+    let dir = root.join("repo");
+    let s = dir.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    let bare = root.join("remote.git");
+    git2::Repository::init_bare(&bare).unwrap();
+    git2::Repository::open(&dir)
+        .unwrap()
+        .remote("origin", bare.to_str().unwrap())
+        .unwrap();
+    s
+}
+
+#[test]
+#[serial_test::serial]
+#[cfg(unix)]
+fn test_gh_fetch_times_out_on_hanging_git() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = repo_with_remote(t.path());
+    let orig_path = install_hanging_fetch_shim(&t.path().join("bin"));
+
+    let started = Instant::now();
+    let err = gh_fetch_with_prune(&dir, "origin", false, Some(Duration::from_secs(1))).unwrap_err();
+    std::env::set_var("PATH", orig_path);
+
+    assert!(started.elapsed() < Duration::from_secs(20));
+    let msg = err.to_string();
+    assert!(msg.contains("operation timed out"), "{}", msg);
+    assert!(msg.contains("git fetch origin"), "{}", msg);
+    assert_eq!(exit_code_for(err.as_ref()), 7);
+}
+
+#[test]
+#[serial_test::serial]
+#[cfg(unix)]
+fn test_timeout_flag_applies_to_gh_fetch_cli() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = repo_with_remote(t.path());
+    let orig_path = install_hanging_fetch_shim(&t.path().join("bin"));
+
+    let cli = Cli {
+        command: Commands::GhFetch {
+            directory: Some(dir),
+            remote: "origin".to_string(),
+            prune: false,
+            insecure: false,
+            recursive: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: Some(1),
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    assert_eq!(cli.remote_timeout(), Some(Duration::from_secs(1)));
+    let result = execute_cli(cli);
+    std::env::set_var("PATH", orig_path);
+
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("operation timed out"), "{}", err);
+}
+
+#[test]
+fn test_timeout_flag_parsing() {
+    use clap::Parser;
+    let cli = Cli::try_parse_from(["mdcode", "--timeout", "30", "gh_fetch", "."]).unwrap();
+    assert_eq!(cli.timeout, Some(30));
+    assert!(Cli::try_parse_from(["mdcode", "--timeout", "0", "gh_fetch", "."]).is_err());
+}
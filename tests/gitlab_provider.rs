@@ -0,0 +1,165 @@
+use clap::Parser;
+use mdcode::*;
+
+#[test]
+fn test_parse_slug_per_provider() {
+    let gh = ProviderKind::Github.remote_provider(None, None);
+    let gl = ProviderKind::Gitlab.remote_provider(None, None);
+    assert_eq!(gh.default_host(), "github.com");
+    assert_eq!(gl.default_host(), "gitlab.com");
+
+    assert_eq!(gh.parse_slug("owner/name").as_deref(), Some("owner/name"));
+    assert_eq!(
+        gh.parse_slug("https://github.com/owner/name.git")
+            .as_deref(),
+        Some("owner/name")
+    );
+    assert_eq!(
+        gh.parse_slug("git@github.com:owner/name.git").as_deref(),
+        Some("owner/name")
+    );
+    assert_eq!(gh.parse_slug("group/sub/name"), None);
+    assert_eq!(gh.parse_slug("https://gitlab.com/owner/name"), None);
+
+    assert_eq!(
+        gl.parse_slug("group/sub/name").as_deref(),
+        Some("group/sub/name")
+    );
+    assert_eq!(
+        gl.parse_slug("ssh://git@gitlab.com/group/name.git")
+            .as_deref(),
+        Some("group/name")
+    );
+    assert_eq!(gl.parse_slug("name"), None);
+    assert_eq!(gl.parse_slug("group//name"), None);
+    assert_eq!(gl.parse_slug("https://example.com/group/name"), None);
+}
+
+#[test]
+fn test_resolve_clone_source_with_gitlab() {
+    let gl = ProviderKind::Gitlab.remote_provider(None, None);
+    assert_eq!(
+        resolve_clone_source_with("group/sub/name", gl.as_ref()),
+        "https://gitlab.com/group/sub/name.git"
+    );
+    assert_eq!(
+        resolve_clone_source_with("https://gitlab.com/group/name.git", gl.as_ref()),
+        "https://gitlab.com/group/name.git"
+    );
+    // The GitHub default is unchanged.
+    assert_eq!(
+        resolve_clone_source("owner/name"),
+        "https://github.com/owner/name.git"
+    );
+}
+
+#[test]
+fn test_provider_flag_parsing() {
+    let cli = Cli::try_parse_from(["mdcode", "gh_create", ".", "--provider", "gitlab"]).unwrap();
+    match cli.command {
+        Commands::GhCreate { provider, .. } => assert_eq!(provider, ProviderKind::Gitlab),
+        _ => panic!("expected gh_create"),
+    }
+    let cli = Cli::try_parse_from(["mdcode", "gh_create", "."]).unwrap();
+    match cli.command {
+        Commands::GhCreate { provider, .. } => assert_eq!(provider, ProviderKind::Github),
+        _ => panic!("expected gh_create"),
+    }
+    assert!(Cli::try_parse_from(["mdcode", "gh_create", ".", "--provider", "bitbucket"]).is_err());
+}
+
+#[test]
+fn test_format_provider_summary() {
+    assert_eq!(
+        format_provider_summary("GitLab", "demo", "Private", "https://gitlab.com/me/demo"),
+        "Created GitLab repository: demo (private) https://gitlab.com/me/demo"
+    );
+}
+
+// The offline stub provider "creates" the project at MDCODE_TEST_BARE_REMOTE; gitlab must
+// not go through the `gh` CLI even when it is installed.
+#[cfg(all(unix, feature = "offline_gh"))]
+#[test]
+#[serial_test::serial]
+fn test_gh_create_gitlab_offline_adds_origin_and_pushes() {
+    use std::io::Write as _;
+    use std::os::unix::fs::PermissionsExt;
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let temp = tempfile::tempdir().unwrap();
+    let bare = temp.path().join("remote.git");
+    git2::Repository::init_bare(&bare).unwrap();
+    let bare_url = format!("file://{}", bare.to_str().unwrap());
+
+    let work = temp.path().join("work");
+    let work_str = work.to_str().unwrap();
+    new_repository(work_str, false, 50).unwrap();
+
+    let bin = temp.path().join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let gh = bin.join("gh");
+    let log_path = temp.path().join("gh_args.txt");
+    let mut f = std::fs::File::create(&gh).unwrap();
+    writeln!(
+        f,
+        "#!/bin/sh\necho \"$@\" >> {}\nexit 0",
+        log_path.display()
+    )
+    .unwrap();
+    drop(f);
+    let mut p = std::fs::metadata(&gh).unwrap().permissions();
+    p.set_mode(0o755);
+    std::fs::set_permissions(&gh, p).unwrap();
+    let orig_path = std::env::var_os("PATH");
+    std::env::set_var(
+        "PATH",
+        format!("{}:{}", bin.display(), std::env::var("PATH").unwrap()),
+    );
+    std::env::set_var("MDCODE_TEST_BARE_REMOTE", &bare_url);
+
+    let res = execute_cli(Cli {
+        command: Commands::GhCreate {
+            directory: Some(work_str.to_string()),
+            description: None,
+            public: false,
+            private: false,
+            internal: true,
+            init_if_missing: false,
+            token_file: None,
+            provider: ProviderKind::Gitlab,
+            org: None,
+            set_default_branch: false,
+            remote_url: None,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    });
+
+    if let Some(p) = orig_path {
+        std::env::set_var("PATH", p);
+    }
+    std::env::remove_var("MDCODE_TEST_BARE_REMOTE");
+    res.unwrap();
+    assert!(!log_path.exists(), "gh must not be invoked for gitlab");
+    let repo = git2::Repository::open(&work).unwrap();
+    assert_eq!(
+        repo.find_remote("origin").unwrap().url(),
+        Some(bare_url.as_str())
+    );
+    let remote = git2::Repository::open_bare(&bare).unwrap();
+    assert!(remote.find_reference("refs/heads/master").is_ok());
+}
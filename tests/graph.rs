@@ -0,0 +1,101 @@
+use mdcode::*;
+
+/// Render `commits` (newest first, as `(id, parents)`) the way `info --graph` prints them.
+fn render(commits: &[(&'static str, &[&'static str])]) -> Vec<String> {
+    let mut graph = CommitGraph::new();
+    let mut out = Vec::new();
+    for (id, parents) in commits {
+        let row = graph.next(id, parents);
+        out.push(format!("{} {}", row.commit, id));
+        out.extend(row.connector);
+    }
+    out
+}
+
+#[test]
+fn test_graph_linear_history_is_one_lane() {
+    assert_eq!(
+        render(&[("c", &["b"]), ("b", &["a"]), ("a", &[])]),
+        vec!["* c", "* b", "* a"]
+    );
+}
+
+#[test]
+fn test_graph_single_merge() {
+    // m merges topic (t) into the main line (b); both branched off a.
+    assert_eq!(
+        render(&[("m", &["b", "t"]), ("t", &["a"]), ("b", &["a"]), ("a", &[]),]),
+        vec!["* m", "|\\", "| * t", "* | b", "|/", "* a"]
+    );
+}
+
+#[test]
+fn test_graph_octopus_merge() {
+    assert_eq!(
+        render(&[
+            ("m", &["a", "b", "c"]),
+            ("b", &["a"]),
+            ("c", &["a"]),
+            ("a", &[]),
+        ]),
+        vec!["* m", "|\\ \\", "| * | b", "|/ /", "| * c", "|/", "* a"]
+    );
+}
+
+#[test]
+fn test_graph_separate_tips_open_new_lanes() {
+    // Two unrelated roots: the second tip starts its own lane, which ends with its root.
+    let mut graph = CommitGraph::new();
+    assert_eq!(graph.next(&1, &[3]).commit, "*");
+    let row = graph.next(&2, &[]);
+    assert_eq!(row.commit, "| *");
+    assert_eq!(row.connector, None);
+    assert_eq!(graph.next(&3, &[]).commit, "*");
+}
+
+#[test]
+fn test_info_graph_on_a_merge() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let git = |args: &[&str]| {
+        let ok = std::process::Command::new("git")
+            .arg("-C")
+            .arg(s)
+            .args(args)
+            .status()
+            .unwrap()
+            .success();
+        assert!(ok, "git {:?}", args);
+    };
+    git(&["checkout", "-q", "-b", "topic"]);
+    std::fs::write(dir.join("t.txt"), "t\n").unwrap();
+    update_repository(s, false, Some("topic work"), 50).unwrap();
+    git(&["checkout", "-q", "-"]);
+    std::fs::write(dir.join("b.txt"), "b\n").unwrap();
+    update_repository(s, false, Some("main work"), 50).unwrap();
+    git(&["merge", "-q", "--no-edit", "topic"]);
+
+    let opts = HistoryOptions {
+        graph: true,
+        ..HistoryOptions::default()
+    };
+    let mut rows = Vec::new();
+    let mut graph = CommitGraph::new();
+    for_each_commit(s, 50, &opts, |info| {
+        rows.push(graph.next(&info.id, &info.parents));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0].connector.as_deref(), Some("|\\"));
+    assert_eq!(rows[3].commit, "*");
+    info_repository_with_history(s, 50, &opts).unwrap();
+}
@@ -0,0 +1,135 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn test_hidden_policy_skips_editor_and_os_paths() {
+    let default = HiddenPolicy::default();
+    assert!(default.is_excluded(Path::new(".DS_Store")));
+    assert!(default.is_excluded(Path::new("src/.DS_Store")));
+    assert!(default.is_excluded(Path::new(".idea/workspace.xml")));
+    assert!(default.is_excluded(Path::new(".vscode/settings.json")));
+    assert!(!default.is_excluded(Path::new(".github/workflows/ci.yml")));
+    assert!(!default.is_excluded(Path::new(".cargo/config.toml")));
+    assert!(!default.is_excluded(Path::new("src/idea.rs")));
+
+    let policy =
+        HiddenPolicy::new(&[".vscode/settings.json".to_string(), ".idea".to_string()]).unwrap();
+    assert!(!policy.is_excluded(Path::new(".vscode/settings.json")));
+    assert!(policy.is_excluded(Path::new(".vscode/launch.json")));
+    assert!(!policy.is_excluded(Path::new(".idea/workspace.xml")));
+    assert!(!policy.is_excluded(Path::new("sub/.idea/misc.xml")));
+    assert!(policy.is_excluded(Path::new(".DS_Store")));
+
+    let err = HiddenPolicy::new(&["[".to_string()])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("invalid --include-hidden glob '['"), "{}", err);
+}
+
+#[test]
+fn test_ci_config_category() {
+    assert_eq!(
+        detect_file_type(Path::new(".github/workflows/ci.yml")),
+        Some("CI Config")
+    );
+    assert_eq!(
+        detect_file_type(Path::new("repo/.github/workflows/release.yaml")),
+        Some("CI Config")
+    );
+    assert_eq!(
+        detect_file_type(Path::new(".gitlab-ci.yml")),
+        Some("CI Config")
+    );
+    assert_eq!(
+        detect_file_type(Path::new("deploy/workflows/ci.yml")),
+        Some("YAML")
+    );
+    assert_eq!(
+        detect_file_type(Path::new(".github/dependabot.yml")),
+        Some("YAML")
+    );
+}
+
+fn write(dir: &Path, rel: &str, content: &str) {
+    let path = dir.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+#[serial_test::serial]
+fn test_new_skips_editor_files_and_stages_workflows() {
+    let t = tempdir().unwrap();
+    let dir = t.path().join("r");
+    let s = dir.to_str().unwrap();
+    write(&dir, "main.rs", "fn main() {}\n");
+    write(&dir, ".DS_Store", "\0\0\0\x01Bud1");
+    write(&dir, ".idea/workspace.xml", "<project/>\n");
+    write(&dir, ".vscode/settings.json", "{}\n");
+    write(&dir, ".github/workflows/ci.yml", "on: push\n");
+    new_repository(s, false, 50).unwrap();
+
+    let repo = Repository::open(&dir).unwrap();
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    assert!(tree.get_path(Path::new("main.rs")).is_ok());
+    assert!(tree.get_path(Path::new(".github/workflows/ci.yml")).is_ok());
+    assert!(tree.get_path(Path::new(".DS_Store")).is_err());
+    assert!(tree.get_name(".idea").is_none());
+    assert!(tree.get_name(".vscode").is_none());
+
+    let ignored = list_ignored_files(s, 50).unwrap();
+    assert!(ignored
+        .iter()
+        .any(|f| f.path == ".idea/workspace.xml" && f.reason == IgnoreReason::Hidden));
+    assert_eq!(IgnoreReason::Hidden.to_string(), "hidden");
+
+    // Config and command line bring editor settings back.
+    write(
+        &dir,
+        ".mdcode.toml",
+        "[scan]\ninclude_hidden = [\".vscode/settings.json\"]\n",
+    );
+    let scan = ScanOptions {
+        include_hidden: vec![".idea".to_string()],
+        ..ScanOptions::default()
+    };
+    let listed = list_source_files_with_options(s, 50, &scan).unwrap();
+    let paths: Vec<String> = listed.into_iter().map(|(p, _)| p).collect();
+    assert!(
+        paths.contains(&".vscode/settings.json".to_string()),
+        "{:?}",
+        paths
+    );
+    assert!(
+        paths.contains(&".idea/workspace.xml".to_string()),
+        "{:?}",
+        paths
+    );
+
+    write(
+        &dir,
+        ".mdcode.toml",
+        "[scan]\ninclude_hidden = \".vscode\"\n",
+    );
+    let err = list_source_files(s, 50).unwrap_err().to_string();
+    assert!(
+        err.contains("scan.include_hidden must be a list"),
+        "{}",
+        err
+    );
+
+    use clap::Parser;
+    let cli = Cli::try_parse_from([
+        "mdcode",
+        "--include-hidden",
+        ".vscode",
+        "--include-hidden",
+        ".idea/*.xml",
+        "list-files",
+    ])
+    .unwrap();
+    assert_eq!(cli.include_hidden, vec![".vscode", ".idea/*.xml"]);
+    assert_eq!(cli.scan_options(None).include_hidden, cli.include_hidden);
+}
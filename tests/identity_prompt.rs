@@ -0,0 +1,97 @@
+use git2::{ConfigLevel, Repository};
+use mdcode::*;
+use std::io::Cursor;
+use tempfile::tempdir;
+
+/// Point libgit2 at empty global/XDG/system config dirs and clear identity env vars, so
+/// nothing but the repository itself can provide a name/email.
+fn isolate_identity(empty: &std::path::Path) {
+    for level in [ConfigLevel::Global, ConfigLevel::XDG, ConfigLevel::System] {
+        unsafe { git2::opts::set_search_path(level, empty).unwrap() };
+    }
+    for k in [
+        "GIT_AUTHOR_NAME",
+        "GIT_AUTHOR_EMAIL",
+        "GIT_COMMITTER_NAME",
+        "GIT_COMMITTER_EMAIL",
+        "MDCODE_IGNORE_GLOBAL_GIT",
+    ] {
+        std::env::remove_var(k);
+    }
+}
+
+const STRICT: SignatureOptions = SignatureOptions {
+    allow_fallback_identity: false,
+    interactive: false,
+    sign: false,
+};
+
+#[test]
+#[serial_test::serial]
+fn test_missing_identity_refused_without_flag_when_not_interactive() {
+    let t = tempdir().unwrap();
+    isolate_identity(t.path());
+    let repo = Repository::init(t.path().join("repo")).unwrap();
+
+    let err = resolve_signature_with_source(&repo, &STRICT).err().unwrap();
+    assert!(err.to_string().contains("no Git identity configured"));
+    assert!(err.to_string().contains("--allow-default-author"));
+
+    // With the flag the prompt is skipped and the fallback identity is used.
+    let allow = SignatureOptions {
+        allow_fallback_identity: true,
+        ..STRICT
+    };
+    let (sig, source) = resolve_signature_with_source(&repo, &allow).unwrap();
+    assert_eq!(sig.email(), Some("mdcode@example.com"));
+    assert_eq!(source, FALLBACK_SIGNATURE_SOURCE);
+
+    // An explicit --author or a configured identity never reaches the fallback logic.
+    let (sig, _) = resolve_commit_signature(&repo, Some("Ada <ada@example.com>"), &STRICT).unwrap();
+    assert_eq!(sig.name(), Some("Ada"));
+    let mut cfg = repo.config().unwrap();
+    cfg.set_str("user.name", "Repo User").unwrap();
+    cfg.set_str("user.email", "repo@example.com").unwrap();
+    let (sig, _) = resolve_signature_with_source(&repo, &STRICT).unwrap();
+    assert_eq!(sig.name(), Some("Repo User"));
+
+    assert!(SignatureOptions::for_cli(true, false).allow_fallback_identity);
+    assert!(!SignatureOptions::for_cli(false, false).allow_fallback_identity);
+}
+
+#[test]
+fn test_prompt_identity_saves_to_repo_config() {
+    let t = tempdir().unwrap();
+    let repo = Repository::init(t.path()).unwrap();
+    let local = || {
+        repo.config()
+            .unwrap()
+            .open_level(ConfigLevel::Local)
+            .unwrap()
+    };
+
+    let mut output = Vec::new();
+    let answers = prompt_identity(
+        &repo,
+        &mut Cursor::new("Bob\nbob@example.com\nn\n"),
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(answers, ("Bob".to_string(), "bob@example.com".to_string()));
+    assert!(local().get_string("user.name").is_err());
+    let shown = String::from_utf8(output).unwrap();
+    assert!(shown.contains("Name: ") && shown.contains("Email: "));
+
+    // Enter at the save question accepts the default (yes).
+    prompt_identity(
+        &repo,
+        &mut Cursor::new("Ada Lovelace\nada@example.com\n\n"),
+        &mut Vec::new(),
+    )
+    .unwrap();
+    assert_eq!(local().get_string("user.name").unwrap(), "Ada Lovelace");
+    assert_eq!(local().get_string("user.email").unwrap(), "ada@example.com");
+
+    let err = prompt_identity(&repo, &mut Cursor::new("\n"), &mut Vec::new()).unwrap_err();
+    assert!(err.to_string().contains("name and email are required"));
+}
@@ -0,0 +1,73 @@
+use clap::Parser;
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_info_remote_marks_pushed_and_local_commits() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("work");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::write(dir.join("b.txt"), "b\n").unwrap();
+    update_repository(s, false, Some("second"), 50).unwrap();
+
+    let bare = tmp.path().join("server.git");
+    git2::Repository::init_bare(&bare).unwrap();
+    add_remote(s, "origin", bare.to_str().unwrap()).unwrap();
+
+    // Nothing pushed yet: every commit is local.
+    assert_eq!(remote_tracking_tip(s, "origin").unwrap(), None);
+
+    gh_push(s, "origin").unwrap();
+    std::fs::write(dir.join("c.txt"), "c\n").unwrap();
+    update_repository(s, false, Some("third"), 50).unwrap();
+    std::fs::write(dir.join("d.txt"), "d\n").unwrap();
+    update_repository(s, false, Some("fourth"), 50).unwrap();
+
+    let tip = remote_tracking_tip(s, "origin").unwrap();
+    assert!(tip.is_some());
+    let repo = git2::Repository::open(&dir).unwrap();
+    let mut marks = Vec::new();
+    for_each_commit(s, 50, &HistoryOptions::default(), |info| {
+        marks.push((info.summary.clone(), is_pushed(&repo, info.id, tip)));
+        Ok(())
+    })
+    .unwrap();
+    // Commits made within the same second have no stable order.
+    marks.sort();
+    assert_eq!(
+        marks,
+        vec![
+            ("Initial commit".to_string(), true),
+            ("fourth".to_string(), false),
+            ("second".to_string(), true),
+            ("third".to_string(), false),
+        ]
+    );
+    assert!(!is_pushed(
+        &repo,
+        repo.head().unwrap().target().unwrap(),
+        None
+    ));
+
+    let opts = HistoryOptions {
+        mark_pushed: true,
+        remote_tip: tip,
+        ..HistoryOptions::default()
+    };
+    info_repository_with_history(s, 50, &opts).unwrap();
+
+    let err = remote_tracking_tip(s, "upstream").unwrap_err();
+    assert!(
+        err.to_string().contains("remote 'upstream' not found"),
+        "{}",
+        err
+    );
+    assert!(Cli::try_parse_from(["mdcode", "info", s, "--remote", "origin"]).is_ok());
+}
@@ -0,0 +1,218 @@
+use git2::{Oid, Repository, Signature, Time};
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// Build a linear history of `n` commits directly in the object database (no work tree
+/// churn), each rewriting `counter.txt` and, every tenth commit, adding a file.
+fn synthetic_history(dir: &Path, n: usize) -> Vec<Oid> {
+    let repo = Repository::init(dir).unwrap();
+    let mut ids = Vec::with_capacity(n);
+    let mut parent: Option<Oid> = None;
+    let mut extra = Vec::new();
+    for i in 0..n {
+        let sig = Signature::new(
+            "t",
+            "t@example.com",
+            &Time::new(1_600_000_000 + i as i64, 0),
+        )
+        .unwrap();
+        if i % 10 == 0 {
+            extra.push((
+                format!("f{}.txt", i),
+                repo.blob(format!("{}\n", i).as_bytes()).unwrap(),
+            ));
+        }
+        let mut tb = repo.treebuilder(None).unwrap();
+        tb.insert(
+            "counter.txt",
+            repo.blob(format!("{}\n", i).as_bytes()).unwrap(),
+            0o100644,
+        )
+        .unwrap();
+        for (name, blob) in &extra {
+            tb.insert(name, *blob, 0o100644).unwrap();
+        }
+        let tree = repo.find_tree(tb.write().unwrap()).unwrap();
+        let parents: Vec<_> = parent
+            .map(|p| repo.find_commit(p).unwrap())
+            .into_iter()
+            .collect();
+        let refs: Vec<_> = parents.iter().collect();
+        let id = repo
+            .commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                &format!("commit {}", i),
+                &tree,
+                &refs,
+            )
+            .unwrap();
+        ids.push(id);
+        parent = Some(id);
+    }
+    ids
+}
+
+fn visit(dir: &str, opts: &HistoryOptions) -> Vec<CommitInfo> {
+    let mut out = Vec::new();
+    let n = for_each_commit(dir, DEFAULT_RENAME_THRESHOLD, opts, |info| {
+        out.push(info);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(n, out.len());
+    out
+}
+
+fn key(infos: &[CommitInfo]) -> Vec<(usize, Oid, String, Vec<FileChange>)> {
+    infos
+        .iter()
+        .map(|c| (c.index, c.id, c.summary.clone(), c.changes.clone()))
+        .collect()
+}
+
+#[test]
+fn test_streamed_history_matches_collected_history() {
+    let t = tempdir().unwrap();
+    let ids = synthetic_history(t.path(), 25);
+    let s = t.path().to_str().unwrap();
+
+    // Reference: every commit, oldest first, as `commit_history` has always returned.
+    let all = commit_history(s, DEFAULT_RENAME_THRESHOLD).unwrap();
+    assert_eq!(all.len(), 25);
+    assert_eq!(all[0].id, ids[0]);
+    assert_eq!(all[0].index, 24);
+
+    let oldest_first = HistoryOptions {
+        oldest_first: true,
+        ..HistoryOptions::default()
+    };
+    assert_eq!(key(&visit(s, &oldest_first)), key(&all));
+
+    let mut newest_first = visit(s, &HistoryOptions::default());
+    assert_eq!(newest_first[0].index, 0);
+    assert_eq!(newest_first[0].id, *ids.last().unwrap());
+    newest_first.reverse();
+    assert_eq!(key(&newest_first), key(&all));
+
+    // --limit keeps the newest commits in either order, with unchanged indices.
+    let limited = visit(
+        s,
+        &HistoryOptions {
+            limit: Some(3),
+            ..HistoryOptions::default()
+        },
+    );
+    assert_eq!(
+        limited.iter().map(|c| c.index).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    let limited_oldest = visit(
+        s,
+        &HistoryOptions {
+            limit: Some(3),
+            oldest_first: true,
+            ..HistoryOptions::default()
+        },
+    );
+    assert_eq!(key(&limited_oldest), key(&all[22..]));
+
+    // --since-tag bounds combine with both orders.
+    let since = HistoryOptions {
+        since: Some(ids[19]),
+        oldest_first: true,
+        ..HistoryOptions::default()
+    };
+    let tail = visit(s, &since);
+    assert_eq!(key(&tail), key(&all[20..]));
+    assert_eq!(
+        key(&tail),
+        key(&commit_history_since(s, DEFAULT_RENAME_THRESHOLD, Some(ids[19])).unwrap())
+    );
+
+    info_repository_with_history(s, DEFAULT_RENAME_THRESHOLD, &oldest_first).unwrap();
+    info_repository(s).unwrap();
+}
+
+#[test]
+fn test_large_history_streams_without_visiting_everything() {
+    let t = tempdir().unwrap();
+    let ids = synthetic_history(t.path(), 2000);
+    let s = t.path().to_str().unwrap();
+
+    // Only the displayed commits reach the callback (and are diffed).
+    let newest = visit(
+        s,
+        &HistoryOptions {
+            limit: Some(5),
+            ..HistoryOptions::default()
+        },
+    );
+    assert_eq!(newest.len(), 5);
+    assert_eq!(newest[0].id, ids[1999]);
+    assert_eq!(newest[4].index, 4);
+    assert_eq!(newest[0].changes.len(), 1);
+
+    let oldest = visit(
+        s,
+        &HistoryOptions {
+            limit: Some(2),
+            oldest_first: true,
+            ..HistoryOptions::default()
+        },
+    );
+    assert_eq!(
+        oldest.iter().map(|c| (c.index, c.id)).collect::<Vec<_>>(),
+        vec![(1, ids[1998]), (0, ids[1999])]
+    );
+
+    // A callback error stops the walk at the first commit.
+    let mut seen = 0;
+    let err = for_each_commit(
+        s,
+        DEFAULT_RENAME_THRESHOLD,
+        &HistoryOptions::default(),
+        |_| {
+            seen += 1;
+            Err("stop".into())
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "stop");
+    assert_eq!(seen, 1);
+
+    let cli = Cli {
+        command: Commands::Info {
+            directory: Some(s.to_string()),
+            rename_threshold: DEFAULT_RENAME_THRESHOLD,
+            since_tag: None,
+            limit: Some(10),
+            oldest_first: false,
+            utc: false,
+            local: false,
+            relative: false,
+            files: FileListMode::Summary,
+            paginate: false,
+            graph: false,
+            remote: None,
+            recursive: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
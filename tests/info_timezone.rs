@@ -0,0 +1,90 @@
+use git2::Time;
+use mdcode::*;
+
+fn with_tz<T>(tz: &str, f: impl FnOnce() -> T) -> T {
+    let prev = std::env::var("TZ").ok();
+    std::env::set_var("TZ", tz);
+    let out = f();
+    match prev {
+        Some(v) => std::env::set_var("TZ", v),
+        None => std::env::remove_var("TZ"),
+    }
+    out
+}
+
+const UTC: TimeFormat = TimeFormat {
+    utc: true,
+    relative: false,
+};
+
+#[test]
+#[serial_test::serial]
+fn test_commit_time_local_by_default_utc_with_flag() {
+    // 2024-01-31 20:05:09 UTC: local time is already the next day at UTC+9.
+    let t = Time::new(1_706_731_509, 0);
+    // Fixed offset UTC+9 with no daylight saving time (POSIX TZ syntax).
+    let (utc, local) = with_tz("JST-9", || {
+        (
+            format_commit_time(t, UTC),
+            format_commit_time(t, TimeFormat::default()),
+        )
+    });
+    assert_eq!(utc, "2024-01-31 20:05:09 (Wed)");
+    assert_eq!(local, "2024-02-01 05:05:09 (Thu)");
+}
+
+#[test]
+fn test_commit_time_across_offsets() {
+    // The same instant recorded with different committer offsets prints the same time.
+    let seconds = 1_706_731_509;
+    let local = format_commit_time(Time::new(seconds, 0), TimeFormat::default());
+    for offset in [-480, -300, 0, 330, 540] {
+        let t = Time::new(seconds, offset);
+        assert_eq!(format_commit_time(t, UTC), "2024-01-31 20:05:09 (Wed)");
+        assert_eq!(format_commit_time(t, TimeFormat::default()), local);
+    }
+}
+
+#[test]
+fn test_commit_time_invalid_does_not_fail() {
+    let t = Time::new(i64::MAX, 0);
+    assert_eq!(format_commit_time(t, UTC), "(invalid time)");
+    assert_eq!(
+        format_commit_time(t, TimeFormat::default()),
+        "(invalid time)"
+    );
+    let relative = TimeFormat {
+        utc: false,
+        relative: true,
+    };
+    assert_eq!(format_commit_time(t, relative), "(invalid time)");
+}
+
+#[test]
+fn test_relative_time() {
+    let now = 1_706_731_509;
+    assert_eq!(relative_time(now, now), "just now");
+    assert_eq!(relative_time(now - 1, now), "1 second ago");
+    assert_eq!(relative_time(now - 90, now), "1 minute ago");
+    assert_eq!(relative_time(now - 5 * 3_600, now), "5 hours ago");
+    assert_eq!(relative_time(now - 3 * 86_400, now), "3 days ago");
+    assert_eq!(relative_time(now - 15 * 86_400, now), "2 weeks ago");
+    assert_eq!(relative_time(now - 70 * 86_400, now), "2 months ago");
+    assert_eq!(relative_time(now - 800 * 86_400, now), "2 years ago");
+    assert_eq!(relative_time(now + 60, now), "in the future");
+
+    let recent = Time::new(chrono::Utc::now().timestamp() - 3 * 86_400 - 60, 120);
+    let relative = TimeFormat {
+        utc: false,
+        relative: true,
+    };
+    assert_eq!(format_commit_time(recent, relative), "3 days ago");
+}
+
+#[test]
+fn test_info_time_flags() {
+    use clap::Parser;
+    assert!(Cli::try_parse_from(["mdcode", "info", ".", "--local", "--relative"]).is_ok());
+    assert!(Cli::try_parse_from(["mdcode", "info", ".", "--utc"]).is_ok());
+    assert!(Cli::try_parse_from(["mdcode", "info", ".", "--utc", "--local"]).is_err());
+}
@@ -0,0 +1,233 @@
+use git2::Repository;
+use mdcode::*;
+use serde_json::json;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn run(command: Commands, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    execute_cli(Cli {
+        command,
+        dry_run,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: true,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    })
+}
+
+fn update(dir: &str, message: &str) -> Commands {
+    Commands::Update {
+        directory: Some(dir.to_string()),
+        allow_merge_commit: false,
+        interactive: false,
+        rename_threshold: DEFAULT_RENAME_THRESHOLD,
+        gc: false,
+        author: None,
+        allow_default_author: true,
+        no_secret_scan: false,
+        only: vec![],
+        message: Some(message.to_string()),
+        auto_message: false,
+        allow_empty: false,
+        keep_empty_dirs: false,
+        amend: false,
+        max_new_files: None,
+        signoff: false,
+        trailer: Vec::new(),
+        detached_ok: false,
+        branch: None,
+        recursive: false,
+    }
+}
+
+fn tag(dir: &str, version: &str) -> Commands {
+    Commands::Tag {
+        directory: Some(dir.to_string()),
+        version: Some(version.to_string()),
+        message: None,
+        message_file: None,
+        no_push: true,
+        strict_push: false,
+        remote: "origin".into(),
+        force: false,
+        allow_dirty: false,
+        author: Some("Rel <rel@example.com>".into()),
+    }
+}
+
+fn head(dir: &Path) -> String {
+    Repository::open(dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .target()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_journal_records_operations_in_order() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    let bare = t.path().join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+
+    run(
+        Commands::New {
+            directory: s.to_string(),
+            gc: false,
+            author: Some("Dev <dev@example.com>".into()),
+            allow_default_author: false,
+            adopt: false,
+            keep_empty_dirs: true,
+            lfs: false,
+        },
+        false,
+    )
+    .unwrap();
+    let initial = head(&dir);
+    std::fs::write(dir.join("main.rs"), "fn main() { run(); }\n").unwrap();
+    // Dry runs change nothing and are not journaled.
+    run(update(s, "preview"), true).unwrap();
+    // A dirty tree makes the tag fail; the failure is journaled too.
+    assert!(run(tag(s, "1.2.0"), false).is_err());
+    run(update(s, "Call run"), false).unwrap();
+    let second = head(&dir);
+    run(tag(s, "1.2.0"), false).unwrap();
+    add_remote(s, "origin", bare.to_str().unwrap()).unwrap();
+    run(
+        Commands::GhPush {
+            directory: Some(s.to_string()),
+            remote: "origin".into(),
+            no_set_upstream: false,
+            all_branches: false,
+            tags: true,
+            follow_tags: false,
+            insecure: false,
+            set_default_branch: false,
+            recursive: false,
+        },
+        false,
+    )
+    .unwrap();
+    // Nothing to commit: journaled without a commit.
+    run(update(s, "No-op"), false).unwrap();
+    // Not journaled: read-only commands.
+    run(
+        Commands::History {
+            directory: Some(s.to_string()),
+            limit: None,
+        },
+        false,
+    )
+    .unwrap();
+
+    let entries = read_journal(s).unwrap();
+    let events: Vec<(&str, bool)> = entries.iter().map(|e| (e.event.as_str(), e.ok())).collect();
+    assert_eq!(
+        events,
+        vec![
+            ("new", true),
+            ("tag", false),
+            ("update", true),
+            ("tag", true),
+            ("push", true),
+            ("update", true),
+        ]
+    );
+    assert_eq!(entries[0].commit.as_deref(), Some(initial.as_str()));
+    assert_eq!(entries[0].args["author"], "Dev <dev@example.com>");
+    assert_eq!(entries[1].commit, None);
+    assert!(entries[1].error.as_deref().unwrap().contains("uncommitted"));
+    assert_eq!(entries[2].commit.as_deref(), Some(second.as_str()));
+    assert_eq!(entries[2].args["message"], "Call run");
+    assert_eq!(entries[3].tag.as_deref(), Some("v1.2.0"));
+    assert_eq!(entries[3].args["version"], "1.2.0");
+    assert_eq!(entries[3].commit, None);
+    assert_eq!(entries[4].args["remote"], "origin");
+    assert_eq!(entries[4].args["tags"], true);
+    assert_eq!(entries[4].commit, None);
+    assert_eq!(entries[5].commit, None);
+    assert_eq!(entries[5].args["message"], "No-op");
+    assert!(entries.windows(2).all(|w| w[0].time <= w[1].time));
+
+    let newest = history_command(s, Some(2)).unwrap();
+    assert_eq!(
+        newest.iter().map(|e| e.event.as_str()).collect::<Vec<_>>(),
+        vec!["update", "push"]
+    );
+    assert_eq!(
+        journal_path(s).unwrap(),
+        Repository::open(&dir).unwrap().path().join(JOURNAL_FILE)
+    );
+}
+
+#[test]
+fn test_journal_write_failure_does_not_fail_command() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.md"), "# a\n").unwrap();
+    new_repository(s, false, 50).unwrap();
+    let recorded = history_command(s, None).unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].event, "new");
+
+    // A directory where the journal file should be makes every append fail.
+    std::fs::remove_file(journal_path(s).unwrap()).unwrap();
+    std::fs::create_dir_all(journal_path(s).unwrap()).unwrap();
+    std::fs::write(dir.join("a.md"), "# b\n").unwrap();
+    run(update(s, "Edit a"), false).unwrap();
+    assert!(read_journal(s).is_err());
+}
+
+#[test]
+fn test_format_journal_entry() {
+    let ok = JournalEntry {
+        time: 1_700_000_000,
+        event: "tag".into(),
+        args: json!({ "version": "1.0.0", "force": false, "remote": null }),
+        commit: Some("0123456789abcdef".into()),
+        tag: Some("v1.0.0".into()),
+        error: None,
+    };
+    assert_eq!(
+        format_journal_entry(&ok),
+        "2023-11-14 22:13:20  tag     ok 0123456 v1.0.0  [version=\"1.0.0\"]"
+    );
+    let failed = JournalEntry {
+        event: "push".into(),
+        args: json!({ "remote": "origin", "tags": true }),
+        commit: None,
+        tag: None,
+        error: Some("push rejected\nhint: fetch first".into()),
+        ..ok.clone()
+    };
+    assert_eq!(
+        format_journal_entry(&failed),
+        "2023-11-14 22:13:20  push    FAILED push rejected  [remote=\"origin\" tags=true]"
+    );
+    assert_eq!(JournalEntry::from_json(&failed.to_json()), Some(failed));
+    assert_eq!(JournalEntry::from_json(&json!({ "event": "x" })), None);
+}
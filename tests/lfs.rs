@@ -0,0 +1,231 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+const MB: usize = 1024 * 1024;
+
+fn scanned(dir: &str, max_file_mb: u64) -> Vec<String> {
+    list_source_files(dir, max_file_mb)
+        .unwrap()
+        .into_iter()
+        .map(|(p, _)| p)
+        .collect()
+}
+
+fn committed_paths(dir: &Path) -> Vec<String> {
+    let repo = Repository::open(dir).unwrap();
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let mut out = Vec::new();
+    tree.walk(git2::TreeWalkMode::PreOrder, |root, e| {
+        if e.kind() == Some(git2::ObjectType::Blob) {
+            out.push(format!("{}{}", root, e.name().unwrap()));
+        }
+        git2::TreeWalkResult::Ok
+    })
+    .unwrap();
+    out.sort();
+    out
+}
+
+#[test]
+fn test_lfs_categories_match_detected_types() {
+    for (label, exts) in LFS_CATEGORIES {
+        for ext in *exts {
+            let name = format!("file.{}", ext);
+            assert_eq!(detect_file_type(Path::new(&name)), Some(*label), "{}", name);
+        }
+    }
+}
+
+#[test]
+fn test_gitattributes_lfs_entries() {
+    let existing = "*.sh text eol=lf\n*.png filter=lfs diff=lfs merge=lfs -text";
+    assert_eq!(parse_lfs_patterns(existing), vec!["*.png"]);
+    let patterns = vec!["*.png".to_string(), "*.psd".to_string()];
+    let updated = gitattributes_with_lfs(existing, &patterns);
+    assert_eq!(
+        updated,
+        "*.sh text eol=lf\n*.png filter=lfs diff=lfs merge=lfs -text\n\
+*.psd filter=lfs diff=lfs merge=lfs -text\n"
+    );
+    assert_eq!(gitattributes_with_lfs(&updated, &patterns), updated);
+
+    let t = tempdir().unwrap();
+    let s = t.path().to_str().unwrap();
+    std::fs::write(
+        t.path().join(MDCODE_CONFIG_FILE),
+        "[lfs]\npatterns = [\"*.psd\", \"*.png\", \"assets/raw/*\"]\n",
+    )
+    .unwrap();
+    let all = lfs_patterns_for(s, None).unwrap();
+    assert!(all.contains(&"*.wav".to_string()) && all.contains(&"*.woff2".to_string()));
+    assert_eq!(all.iter().filter(|p| *p == "*.png").count(), 1);
+    assert_eq!(&all[all.len() - 2..], ["*.psd", "assets/raw/*"]);
+
+    // Invalid globs are errors, in the config and in .gitattributes alike.
+    std::fs::write(
+        t.path().join(MDCODE_CONFIG_FILE),
+        "[lfs]\npatterns = [\"[\"]\n",
+    )
+    .unwrap();
+    let err = lfs_patterns_for(s, None).unwrap_err().to_string();
+    assert!(err.contains("invalid LFS pattern '['"), "{}", err);
+    std::fs::write(t.path().join(GITATTRIBUTES_FILE), "[ filter=lfs\n").unwrap();
+    assert!(LfsPatterns::load(s).is_err());
+}
+
+#[test]
+fn test_lfs_patterns_exempt_files_from_size_cap() {
+    let t = tempdir().unwrap();
+    let d = t.path();
+    let s = d.to_str().unwrap();
+    std::fs::create_dir_all(d.join("art/deep")).unwrap();
+    std::fs::create_dir_all(d.join("docs")).unwrap();
+    std::fs::write(d.join("art/deep/cover.png"), vec![0u8; 2 * MB]).unwrap();
+    std::fs::write(d.join("docs/raw.txt"), vec![b'a'; 2 * MB]).unwrap();
+    std::fs::write(d.join("notes.txt"), vec![b'a'; 2 * MB]).unwrap();
+    std::fs::write(d.join("big.rs"), vec![b'a'; 2 * MB]).unwrap();
+    assert!(scanned(s, 1).is_empty());
+
+    // `*.png` matches at any depth, `docs/*.txt` only under docs/ at the root.
+    std::fs::write(
+        d.join(GITATTRIBUTES_FILE),
+        "*.png filter=lfs diff=lfs merge=lfs -text\n/docs/*.txt filter=lfs -text\n*.rs text\n",
+    )
+    .unwrap();
+    assert_eq!(scanned(s, 1), vec!["art/deep/cover.png", "docs/raw.txt"]);
+
+    let caps = SizeCaps::load(s, None).unwrap();
+    assert_eq!(caps.lfs.patterns(), ["*.png", "/docs/*.txt"]);
+    let cap = caps.cap_for(&d.join("art/deep/cover.png"), 1);
+    assert_eq!(cap.category, Some(LFS_CAP_SOURCE));
+    assert!(!cap.exceeded_by(u64::MAX));
+    assert_eq!(caps.source(&cap), "Git LFS pattern in .gitattributes");
+    assert_eq!(caps.cap_for(Path::new("notes.txt"), 1).category, None);
+    assert!(caps
+        .cap_for(Path::new("big.rs"), 1)
+        .exceeded_by(2 * MB as u64));
+}
+
+/// Put a stand-in `git-lfs` on PATH that answers `version` and exits with `install_status`
+/// for `install`.
+#[cfg(unix)]
+fn fake_git_lfs(bin: &Path, install_status: i32) {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::create_dir_all(bin).unwrap();
+    let shim = bin.join("git-lfs");
+    std::fs::write(
+        &shim,
+        format!(
+            "#!/bin/sh\ncase \"$1\" in\n  version) echo 'git-lfs/3.4.0 (test)' ;;\n  install) echo 'hooks not writable' >&2; exit {} ;;\n  *) cat ;;\nesac\n",
+            install_status
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&shim, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+#[serial_test::serial]
+#[cfg(unix)]
+fn test_new_with_lfs_commits_gitattributes_and_large_binaries() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("design");
+    let s = dir.to_str().unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("poster.png"), vec![0u8; 2 * MB]).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    let opts = NewOptions {
+        max_file_mb: 1,
+        lfs: true,
+        ..NewOptions::default()
+    };
+
+    if !check_git_lfs_installed() {
+        let err = new_repository_from_options(s, false, &opts)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("git-lfs.com"), "{}", err);
+        assert!(!dir.join(".git").exists());
+    }
+
+    let orig_path = std::env::var("PATH").unwrap();
+    let bin = t.path().join("bin");
+    fake_git_lfs(&bin, 0);
+    std::env::set_var("PATH", format!("{}:{}", bin.display(), orig_path));
+    let result = new_repository_from_options(s, false, &opts);
+    std::env::set_var("PATH", orig_path);
+    result.unwrap();
+
+    assert_eq!(
+        committed_paths(&dir),
+        vec![".gitattributes", "main.rs", "poster.png"]
+    );
+    let attrs = std::fs::read_to_string(dir.join(GITATTRIBUTES_FILE)).unwrap();
+    assert!(attrs.contains("*.png filter=lfs diff=lfs merge=lfs -text\n"));
+    assert!(attrs.contains("*.mp3 filter=lfs"));
+    assert!(attrs.contains("*.otf filter=lfs"));
+}
+
+#[test]
+#[serial_test::serial]
+#[cfg(unix)]
+fn test_lfs_enable_stages_gitattributes() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::write(
+        dir.join(MDCODE_CONFIG_FILE),
+        "[lfs]\npatterns = [\"*.psd\"]\n",
+    )
+    .unwrap();
+
+    let orig_path = std::env::var("PATH").unwrap();
+    let bin = t.path().join("bin");
+    fake_git_lfs(&bin, 0);
+    std::env::set_var("PATH", format!("{}:{}", bin.display(), orig_path));
+    let patterns = lfs_enable_command(s, None, false);
+    std::env::set_var("PATH", orig_path);
+    assert!(patterns.unwrap().contains(&"*.psd".to_string()));
+
+    let repo = Repository::open(&dir).unwrap();
+    assert!(repo
+        .index()
+        .unwrap()
+        .get_path(Path::new(GITATTRIBUTES_FILE), 0)
+        .is_some());
+    update_repository(s, false, Some("Track binaries with LFS"), 50).unwrap();
+    assert!(committed_paths(&dir).contains(&".gitattributes".to_string()));
+}
+
+#[test]
+#[serial_test::serial]
+#[cfg(unix)]
+fn test_lfs_enable_writes_nothing_when_install_fails() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    let orig_path = std::env::var("PATH").unwrap();
+    let bin = t.path().join("bin");
+    fake_git_lfs(&bin, 1);
+    std::env::set_var("PATH", format!("{}:{}", bin.display(), orig_path));
+    let result = lfs_enable_command(s, None, false);
+    std::env::set_var("PATH", orig_path);
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("git lfs install --local failed"), "{}", err);
+    assert!(err.contains("hooks not writable"), "{}", err);
+    assert!(!dir.join(GITATTRIBUTES_FILE).exists());
+}
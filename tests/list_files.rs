@@ -0,0 +1,52 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_list_source_files_mixed_tree() {
+    let t = tempdir().unwrap();
+    let d = t.path();
+    let s = d.to_str().unwrap();
+    std::fs::create_dir_all(d.join("src")).unwrap();
+    std::fs::create_dir_all(d.join("target").join("debug")).unwrap();
+    std::fs::create_dir_all(d.join("docs")).unwrap();
+    std::fs::write(d.join("src").join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(d.join("docs").join("guide.md"), "# guide").unwrap();
+    std::fs::write(d.join("script.py"), "print(1)").unwrap();
+    std::fs::write(d.join("target").join("debug").join("gen.rs"), "x").unwrap();
+    std::fs::write(d.join("data.unknownext"), "x").unwrap();
+    std::fs::write(d.join("huge.rs"), vec![b'a'; 2 * 1024 * 1024]).unwrap();
+    std::fs::write(d.join(".gitignore"), "secret.py\n").unwrap();
+    std::fs::write(d.join("secret.py"), "x").unwrap();
+
+    let listed = list_source_files(s, 1).unwrap();
+    let paths: Vec<&str> = listed.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(paths, vec!["docs/guide.md", "script.py", "src/main.rs"]);
+    assert_eq!(listed[2].1, "Rust");
+    assert_eq!(listed[1].1, "Python");
+
+    // Raising the cap brings the large file back.
+    let listed = list_source_files(s, 50).unwrap();
+    assert!(listed.iter().any(|(p, _)| p == "huge.rs"));
+
+    let cli = Cli {
+        command: Commands::ListFiles {
+            directory: Some(s.to_string()),
+            with_type: true,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
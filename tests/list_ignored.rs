@@ -0,0 +1,87 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_list_ignored_files_reports_reasons() {
+    let t = tempdir().unwrap();
+    let d = t.path();
+    let s = d.to_str().unwrap();
+    std::fs::create_dir_all(d.join("src")).unwrap();
+    std::fs::create_dir_all(d.join("target").join("debug")).unwrap();
+    std::fs::write(d.join("src").join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(d.join(".gitignore"), "secret.py\n").unwrap();
+    std::fs::write(d.join("secret.py"), "x").unwrap();
+    std::fs::write(d.join("target").join("debug").join("gen.rs"), "x").unwrap();
+    std::fs::write(d.join("huge.rs"), vec![b'a'; 2 * 1024 * 1024]).unwrap();
+    std::fs::write(d.join("data.unknownext"), "x").unwrap();
+    std::fs::write(d.join(".mdcodeexclude"), "scratch.rs\n").unwrap();
+    std::fs::write(d.join("scratch.rs"), "fn s() {}").unwrap();
+
+    let ignored = list_ignored_files(s, 1).unwrap();
+    let pairs: Vec<(&str, IgnoreReason)> = ignored
+        .iter()
+        .map(|f| (f.path.as_str(), f.reason))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("huge.rs", IgnoreReason::SizeCap),
+            ("scratch.rs", IgnoreReason::MdcodeExclude),
+            ("secret.py", IgnoreReason::Gitignore),
+            ("target/debug/gen.rs", IgnoreReason::ExcludedDir),
+        ]
+    );
+    assert_eq!(IgnoreReason::Gitignore.to_string(), "gitignore");
+
+    // Files the scan keeps are never reported as ignored.
+    let listed = list_source_files(s, 1).unwrap();
+    assert!(listed
+        .iter()
+        .all(|(p, _)| ignored.iter().all(|f| &f.path != p)));
+
+    let cli = Cli {
+        command: Commands::ListIgnored {
+            directory: Some(s.to_string()),
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
+
+#[test]
+fn test_list_ignored_files_uses_repository_ignore_rules() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let d = t.path();
+    let s = d.to_str().unwrap();
+    git2::Repository::init(d).unwrap();
+    std::fs::create_dir_all(d.join("gen")).unwrap();
+    // A nested .gitignore is only seen through the repository's own ignore rules.
+    std::fs::write(d.join("gen").join(".gitignore"), "*.rs\n").unwrap();
+    std::fs::write(d.join("gen").join("out.rs"), "x").unwrap();
+    std::fs::write(d.join("lib.rs"), "x").unwrap();
+
+    let ignored = list_ignored_files(s, 50).unwrap();
+    assert_eq!(
+        ignored,
+        vec![IgnoredFile {
+            path: "gen/out.rs".to_string(),
+            reason: IgnoreReason::Gitignore,
+        }]
+    );
+}
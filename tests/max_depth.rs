@@ -0,0 +1,82 @@
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn nested_tree(root: &Path) {
+    std::fs::create_dir_all(root.join("src/cli")).unwrap();
+    std::fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(root.join("notes.txt"), "top").unwrap();
+    std::fs::write(root.join("src/lib.rs"), "pub fn a() {}").unwrap();
+    std::fs::write(root.join("src/cli/tool.rs"), "fn main() {}").unwrap();
+}
+
+fn depth(max_depth: Option<usize>) -> ScanOptions {
+    ScanOptions {
+        max_depth,
+        ..ScanOptions::default()
+    }
+}
+
+fn scanned(dir: &str, max_depth: Option<usize>) -> Vec<String> {
+    list_source_files_with_options(dir, 50, &depth(max_depth))
+        .unwrap()
+        .into_iter()
+        .map(|(p, _)| p)
+        .collect()
+}
+
+#[test]
+fn test_max_depth_limits_scan_recursion() {
+    let t = tempdir().unwrap();
+    nested_tree(t.path());
+    let s = t.path().to_str().unwrap();
+    assert_eq!(scan_total_files(s).unwrap(), 4);
+
+    assert_eq!(scanned(s, Some(1)), vec!["main.rs", "notes.txt"]);
+    assert_eq!(
+        scan_total_files_with_options(s, &depth(Some(1))).unwrap(),
+        2
+    );
+    assert_eq!(
+        scanned(s, Some(2)),
+        vec!["main.rs", "notes.txt", "src/lib.rs"]
+    );
+    assert_eq!(scanned(s, None).len(), 4);
+    assert_eq!(list_source_files(s, 50).unwrap().len(), 4);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_max_depth_flag() {
+    use clap::Parser;
+    let cli = Cli::try_parse_from(["mdcode", "--max-depth", "1", "list-files", "."]).unwrap();
+    assert_eq!(cli.max_depth, Some(1));
+    assert!(Cli::try_parse_from(["mdcode", "--max-depth", "0", "list-files", "."]).is_err());
+
+    let t = tempdir().unwrap();
+    nested_tree(t.path());
+    let cli = Cli {
+        command: Commands::ListFiles {
+            directory: Some(t.path().to_str().unwrap().to_string()),
+            with_type: false,
+        },
+        dry_run: false,
+        max_file_mb: None,
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: Some(1),
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    assert_eq!(cli.scan_options(None), depth(Some(1)));
+    execute_cli(cli).unwrap();
+    // The limit applied to that one command only.
+    assert_eq!(scan_total_files(t.path().to_str().unwrap()).unwrap(), 4);
+}
@@ -0,0 +1,73 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn in_head(repo: &Repository, path: &str) -> bool {
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    tree.get_path(Path::new(path)).is_ok()
+}
+
+#[test]
+fn test_scan_applies_include_and_exclude_overrides() {
+    let t = tempdir().unwrap();
+    let d = t.path();
+    std::fs::create_dir_all(d.join("generated")).unwrap();
+    std::fs::write(d.join(".gitignore"), "generated/\n").unwrap();
+    std::fs::write(d.join("main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(d.join("scratch.rs"), "fn s() {}\n").unwrap();
+    std::fs::write(d.join("generated/a.rs"), "fn a() {}\n").unwrap();
+    std::fs::write(d.join("generated/b.rs"), "fn b() {}\n").unwrap();
+    let s = d.to_str().unwrap();
+    let rels = |dir: &str| -> Vec<String> {
+        list_source_files(dir, 50)
+            .unwrap()
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect()
+    };
+    assert_eq!(rels(s), vec!["main.rs", "scratch.rs"]);
+
+    // Exclude beats include, which beats .gitignore.
+    std::fs::write(d.join(MDCODE_INCLUDE_FILE), "generated/\n").unwrap();
+    std::fs::write(d.join(MDCODE_EXCLUDE_FILE), "generated/b.rs\nscratch.rs\n").unwrap();
+    assert_eq!(rels(s), vec!["generated/a.rs", "main.rs"]);
+
+    let ov = FileOverrides::load(s);
+    assert!(ov.is_included(&d.join("generated/a.rs")));
+    assert!(!ov.is_included(&d.join("generated/b.rs")));
+    assert!(ov.is_excluded(&d.join("scratch.rs"), false));
+    // .gitignore, .mdcodeinclude, .mdcodeexclude, main.rs, generated/a.rs
+    assert_eq!(scan_total_files(s).unwrap(), 5);
+}
+
+#[test]
+fn test_update_stages_gitignored_file_listed_in_mdcodeinclude() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    let gi = std::fs::read_to_string(dir.join(".gitignore")).unwrap();
+    std::fs::write(dir.join(".gitignore"), gi + "\ngenerated/\n").unwrap();
+    std::fs::create_dir_all(dir.join("generated")).unwrap();
+    std::fs::write(dir.join("generated/schema.rs"), "pub struct S;\n").unwrap();
+    std::fs::write(dir.join("generated/cache.rs"), "pub struct C;\n").unwrap();
+    std::fs::write(dir.join(MDCODE_INCLUDE_FILE), "generated/schema.rs\n").unwrap();
+    std::fs::write(dir.join("notes.rs"), "// local notes\n").unwrap();
+    std::fs::write(dir.join(MDCODE_EXCLUDE_FILE), "notes.rs\n").unwrap();
+
+    update_repository(s, false, Some("overrides"), 50).unwrap();
+
+    let repo = Repository::open(&dir).unwrap();
+    assert!(in_head(&repo, "generated/schema.rs"));
+    assert!(!in_head(&repo, "generated/cache.rs"));
+    // Coverage builds stage with `git add -A`, which knows nothing of .mdcodeexclude.
+    if cfg!(not(coverage)) {
+        assert!(!in_head(&repo, "notes.rs"));
+    }
+}
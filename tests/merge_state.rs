@@ -0,0 +1,125 @@
+use git2::{Repository, RepositoryState};
+use mdcode::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let ok = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap()
+        .success();
+    assert!(ok, "git {:?} failed", args);
+}
+
+/// Leave clone B mid-merge with a conflict on x.txt.
+fn conflicted_clone(tmp: &Path) -> std::path::PathBuf {
+    let bare = tmp.join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    let a = tmp.join("A");
+    std::fs::create_dir_all(&a).unwrap();
+    std::fs::write(a.join("x.txt"), "base\n").unwrap();
+    new_repository(a.to_str().unwrap(), false, 50).unwrap();
+    git(&a, &["remote", "add", "origin", bare.to_str().unwrap()]);
+    gh_push(a.to_str().unwrap(), "origin").unwrap();
+
+    let b = tmp.join("B");
+    git(tmp, &["clone", bare.to_str().unwrap(), b.to_str().unwrap()]);
+
+    std::fs::write(a.join("x.txt"), "from A\n").unwrap();
+    update_repository(a.to_str().unwrap(), false, Some("A change"), 50).unwrap();
+    gh_push(a.to_str().unwrap(), "origin").unwrap();
+
+    std::fs::write(b.join("x.txt"), "from B\n").unwrap();
+    git(&b, &["commit", "-am", "B change"]);
+    let pulled = Command::new("git")
+        .arg("-C")
+        .arg(&b)
+        .args(["pull", "--no-rebase", "--no-edit"])
+        .status()
+        .unwrap();
+    assert!(!pulled.success(), "pull should stop on the conflict");
+    b
+}
+
+#[test]
+fn test_update_refuses_mid_merge_and_resolve_continue_finishes() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let b = conflicted_clone(tmp.path());
+    let b_s = b.to_str().unwrap();
+    let repo = Repository::open(&b).unwrap();
+    assert_eq!(repo.state(), RepositoryState::Merge);
+    assert_eq!(conflicted_paths(&repo).unwrap(), vec!["x.txt".to_string()]);
+
+    let err = update_repository(b_s, false, Some("oops"), 50).unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("x.txt"), "{}", msg);
+    assert!(msg.contains("--allow-merge-commit"), "{}", msg);
+
+    let err = tag_release(
+        b_s,
+        Some("1.0.0".into()),
+        None,
+        false,
+        "origin",
+        false,
+        true,
+        false,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("cannot tag"));
+
+    // Markers still present: continue refuses.
+    assert!(resolve_continue(b_s, false, false)
+        .unwrap_err()
+        .to_string()
+        .contains("conflict markers remain"));
+
+    std::fs::write(b.join("x.txt"), "resolved\n").unwrap();
+    let oid = resolve_continue(b_s, false, false).unwrap();
+    let repo = Repository::open(&b).unwrap();
+    assert_eq!(repo.state(), RepositoryState::Clean);
+    let commit = repo.find_commit(oid).unwrap();
+    assert_eq!(commit.parent_count(), 2);
+    assert!(conflicted_paths(&repo).unwrap().is_empty());
+}
+
+#[test]
+fn test_update_allow_merge_commit_records_both_parents() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let b = conflicted_clone(tmp.path());
+    std::fs::write(b.join("x.txt"), "resolved\n").unwrap();
+    let opts = UpdateOptions {
+        allow_merge_commit: true,
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(b.to_str().unwrap(), false, Some("merge"), 50, &opts).unwrap();
+    let repo = Repository::open(&b).unwrap();
+    assert_eq!(repo.state(), RepositoryState::Clean);
+    let head = get_last_commit(&repo).unwrap();
+    assert_eq!(head.parent_count(), 2);
+}
+
+#[test]
+fn test_resolve_continue_without_merge_errors() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    new_repository(dir.to_str().unwrap(), false, 50).unwrap();
+    let err = resolve_continue(dir.to_str().unwrap(), false, false).unwrap_err();
+    assert!(err.to_string().contains("No merge in progress"));
+}
@@ -0,0 +1,114 @@
+use git2::Repository;
+use mdcode::*;
+use serde_json::Value;
+use tempfile::tempdir;
+
+const KEYS: &[&str] = &[
+    "name",
+    "branch",
+    "head",
+    "latest_tag",
+    "version",
+    "dirty",
+    "origin",
+    "origin_url",
+];
+
+fn assert_shape(json: &Value) {
+    let obj = json.as_object().unwrap();
+    let mut keys: Vec<&str> = obj.keys().map(String::as_str).collect();
+    keys.sort();
+    let mut expected = KEYS.to_vec();
+    expected.sort();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn test_meta_without_remote_or_tag_reports_nulls() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("plain");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    let json = repo_meta(s).unwrap().to_json();
+    assert_shape(&json);
+    assert_eq!(json["name"], "plain");
+    assert!(json["branch"].is_string());
+    assert_eq!(json["head"].as_str().unwrap().len(), 40);
+    assert_eq!(json["latest_tag"], Value::Null);
+    assert_eq!(json["version"], Value::Null);
+    assert_eq!(json["dirty"], false);
+    assert_eq!(json["origin"], Value::Null);
+    assert_eq!(json["origin_url"], Value::Null);
+
+    // Before the first commit there is no HEAD yet, but that is not an error either.
+    let empty = t.path().join("empty");
+    Repository::init(&empty).unwrap();
+    let json = repo_meta(empty.to_str().unwrap()).unwrap().to_json();
+    assert_shape(&json);
+    assert_eq!(json["head"], Value::Null);
+    assert!(json["branch"].is_string());
+
+    assert!(repo_meta(t.path().join("missing").to_str().unwrap()).is_err());
+}
+
+#[test]
+fn test_meta_with_remote_tag_and_version() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("tool");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"tool\"\nversion = \"1.3.0\"\n",
+    )
+    .unwrap();
+    update_repository(s, false, Some("add manifest"), 50).unwrap();
+    let repo = Repository::open(&dir).unwrap();
+    repo.remote("origin", "git@github.com:acme/tool.git")
+        .unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    for tag in ["v1.2.0", "v1.10.0", "nightly"] {
+        repo.tag_lightweight(tag, head.as_object(), false).unwrap();
+    }
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nversion = \"1.3.1\"\n").unwrap();
+
+    let meta = repo_meta(s).unwrap();
+    assert_eq!(meta.head, Some(head.id().to_string()));
+    let json = meta.to_json();
+    assert_shape(&json);
+    assert_eq!(json["name"], "tool");
+    assert_eq!(json["latest_tag"], "v1.10.0");
+    assert_eq!(json["version"], "1.3.1");
+    assert_eq!(json["dirty"], true);
+    assert_eq!(json["origin"], "acme/tool");
+    assert_eq!(json["origin_url"], "git@github.com:acme/tool.git");
+
+    let cli = Cli {
+        command: Commands::Meta {
+            directory: Some(s.to_string()),
+            json: true,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
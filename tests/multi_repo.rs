@@ -0,0 +1,184 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// A parent holding repositories `alpha` and `gamma`, a broken `beta` whose `.git` points
+/// nowhere, a plain directory and a file.
+fn workspace(parent: &Path) {
+    for name in ["alpha", "gamma"] {
+        let dir = parent.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("README.md"), format!("# {}\n", name)).unwrap();
+        new_repository(dir.to_str().unwrap(), false, 50).unwrap();
+    }
+    std::fs::create_dir_all(parent.join("beta")).unwrap();
+    std::fs::write(parent.join("beta/.git"), "gitdir: ../missing\n").unwrap();
+    std::fs::create_dir_all(parent.join("docs")).unwrap();
+    std::fs::write(parent.join("notes.txt"), "not a repo\n").unwrap();
+}
+
+fn head_message(dir: &Path) -> String {
+    Repository::open(dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .summary()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_run_each_continues_past_failures() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    workspace(t.path());
+    let parent = t.path().to_str().unwrap();
+
+    let names: Vec<String> = discover_child_repos(parent)
+        .unwrap()
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, vec!["alpha", "beta", "gamma"]);
+
+    let mut seen = Vec::new();
+    let outcomes = run_each(parent, |dir| {
+        seen.push(
+            std::path::Path::new(dir)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+        );
+        open_repo(dir)?;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(seen, vec!["alpha", "beta", "gamma"]);
+    assert_eq!(outcomes[0].error, None);
+    assert!(outcomes[1].error.is_some());
+    assert_eq!(outcomes[2].error, None);
+
+    let summary = format_summary(&[
+        RepoOutcome {
+            name: "alpha".into(),
+            error: None,
+        },
+        RepoOutcome {
+            name: "beta-long".into(),
+            error: Some("push rejected\nhint: pull first".into()),
+        },
+    ]);
+    assert_eq!(
+        summary,
+        "Summary:\n  alpha      ok\n  beta-long  FAILED  push rejected\n2 repositories: 1 succeeded, 1 failed"
+    );
+
+    let empty = tempdir().unwrap();
+    let err = run_each(empty.path().to_str().unwrap(), |_| Ok(())).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<MdcodeError>(),
+        Some(MdcodeError::NoRepository(_))
+    ));
+}
+
+#[test]
+fn test_recursive_update_and_info() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    workspace(t.path());
+    let parent = t.path().to_str().unwrap();
+    for name in ["alpha", "gamma"] {
+        std::fs::write(t.path().join(name).join("main.rs"), "fn main() {}\n").unwrap();
+    }
+
+    let cli = |command| Cli {
+        command,
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    let err = execute_cli(cli(Commands::Update {
+        directory: Some(parent.to_string()),
+        allow_merge_commit: false,
+        interactive: false,
+        rename_threshold: DEFAULT_RENAME_THRESHOLD,
+        gc: false,
+        author: None,
+        allow_default_author: true,
+        no_secret_scan: false,
+        only: vec![],
+        message: Some("weekly sync".into()),
+        auto_message: false,
+        allow_empty: false,
+        keep_empty_dirs: false,
+        amend: false,
+        max_new_files: None,
+        signoff: false,
+        trailer: Vec::new(),
+        detached_ok: false,
+        branch: None,
+        recursive: true,
+    }))
+    .unwrap_err();
+    assert_eq!(err.to_string(), "1 of 3 repositories failed");
+    assert_eq!(head_message(&t.path().join("alpha")), "weekly sync");
+    assert_eq!(head_message(&t.path().join("gamma")), "weekly sync");
+
+    let err = execute_cli(cli(Commands::Info {
+        directory: Some(parent.to_string()),
+        rename_threshold: DEFAULT_RENAME_THRESHOLD,
+        since_tag: None,
+        limit: Some(1),
+        oldest_first: false,
+        utc: false,
+        local: false,
+        relative: false,
+        files: FileListMode::Summary,
+        paginate: false,
+        graph: false,
+        remote: None,
+        recursive: true,
+    }))
+    .unwrap_err();
+    assert_eq!(err.to_string(), "1 of 3 repositories failed");
+
+    // Without the broken repository every run succeeds.
+    std::fs::remove_dir_all(t.path().join("beta")).unwrap();
+    execute_cli(cli(Commands::Info {
+        directory: Some(parent.to_string()),
+        rename_threshold: DEFAULT_RENAME_THRESHOLD,
+        since_tag: None,
+        limit: None,
+        oldest_first: true,
+        utc: false,
+        local: false,
+        relative: false,
+        files: FileListMode::Summary,
+        paginate: false,
+        graph: false,
+        remote: None,
+        recursive: true,
+    }))
+    .unwrap();
+}
@@ -0,0 +1,125 @@
+use mdcode::*;
+use std::io::Cursor;
+use tempfile::tempdir;
+
+/// A project with months of work: sources, an unrecognized file, an oversized file and a
+/// nested checkout with its own `.git`.
+fn existing_project(root: &std::path::Path) -> std::path::PathBuf {
+    let dir = root.join("project");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::create_dir_all(dir.join("vendor/lib")).unwrap();
+    std::fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(dir.join("README.md"), "# project\n").unwrap();
+    std::fs::write(dir.join("data.qqq"), "opaque\n").unwrap();
+    std::fs::write(dir.join("big.rs"), vec![b'a'; 2 * 1024 * 1024]).unwrap();
+    git2::Repository::init(dir.join("vendor/lib")).unwrap();
+    std::fs::write(dir.join("vendor/lib/lib.rs"), "pub fn v() {}\n").unwrap();
+    dir
+}
+
+#[test]
+fn test_adoption_plan_flags_skipped_unrecognized_and_nested_repos() {
+    let tmp = tempdir().unwrap();
+    let dir = existing_project(tmp.path());
+    let opts = NewOptions {
+        max_file_mb: 1,
+        author: Some("Dev <dev@example.com>".into()),
+        ..NewOptions::default()
+    };
+    let plan = plan_new_repository(dir.to_str().unwrap(), &opts).unwrap();
+    assert!(plan.has_content);
+    assert_eq!(plan.skipped_by_size, vec!["big.rs"]);
+    assert_eq!(plan.unrecognized, vec!["data.qqq"]);
+    assert_eq!(plan.nested_repositories, vec!["vendor/lib"]);
+    let text = plan.render();
+    assert!(
+        text.contains("Not staged (unrecognized type): data.qqq"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("Warning: nested repositories, not staged: vendor/lib"),
+        "{}",
+        text
+    );
+
+    // Declining (or no answer) creates nothing.
+    let mut out = Vec::new();
+    assert!(!confirm_adoption(&plan, &mut Cursor::new("\n"), &mut out).unwrap());
+    let shown = String::from_utf8(out).unwrap();
+    assert!(shown.contains("Would stage 2 of"), "{}", shown);
+    assert!(shown.ends_with("commit these 2 files? [y/N] "), "{}", shown);
+    assert!(confirm_adoption(&plan, &mut Cursor::new("yes\n"), &mut Vec::new()).unwrap());
+    assert!(!dir.join(".git").exists());
+
+    let empty = tmp.path().join("empty");
+    std::fs::create_dir_all(&empty).unwrap();
+    assert!(
+        !plan_new_repository(empty.to_str().unwrap(), &opts)
+            .unwrap()
+            .has_content
+    );
+}
+
+#[test]
+fn test_adopt_with_yes_commits_what_the_plan_showed() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = existing_project(tmp.path());
+    let s = dir.to_str().unwrap();
+    let opts = NewOptions {
+        max_file_mb: 1,
+        author: Some("Dev <dev@example.com>".into()),
+        ..NewOptions::default()
+    };
+    let plan = plan_new_repository(s, &opts).unwrap();
+
+    execute_cli(Cli {
+        command: Commands::New {
+            directory: s.to_string(),
+            gc: false,
+            author: opts.author.clone(),
+            allow_default_author: false,
+            adopt: true,
+            keep_empty_dirs: true,
+            lfs: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(1),
+        no_color: true,
+        yes: true,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    })
+    .unwrap();
+
+    let repo = git2::Repository::open(&dir).unwrap();
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let mut committed = Vec::new();
+    tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(git2::ObjectType::Blob) {
+            committed.push(format!("{}{}", root, entry.name().unwrap()));
+        }
+        git2::TreeWalkResult::Ok
+    })
+    .unwrap();
+    committed.sort();
+    let mut expected: Vec<String> = plan.files_by_category().into_values().flatten().collect();
+    expected.sort();
+    assert_eq!(committed, expected);
+    assert_eq!(
+        std::fs::read_to_string(dir.join(".gitignore")).unwrap(),
+        plan.gitignore
+    );
+}
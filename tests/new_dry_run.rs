@@ -14,3 +14,101 @@ fn test_new_repository_dry_run_does_not_create_git_dir() {
     new_repository(dir.to_str().unwrap(), true, 50).unwrap();
     assert!(!dir.join(".git").exists());
 }
+
+/// Every path under `root` (directories included), sorted.
+fn tree(root: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut out = Vec::new();
+    for e in std::fs::read_dir(root).unwrap().flatten() {
+        let p = e.path();
+        if p.is_dir() {
+            out.extend(tree(&p));
+        }
+        out.push(p);
+    }
+    out.sort();
+    out
+}
+
+#[test]
+fn test_dry_run_new_leaves_filesystem_untouched() {
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("repo");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+    std::fs::write(dir.join("src/main.rs"), "fn main(){}\n").unwrap();
+    let before = tree(tmp.path());
+    new_repository(dir.to_str().unwrap(), true, 50).unwrap();
+    assert_eq!(tree(tmp.path()), before);
+
+    // Not even the directory is created.
+    let missing = tmp.path().join("missing");
+    new_repository(missing.to_str().unwrap(), true, 50).unwrap();
+    assert!(!missing.exists());
+    let plan = plan_new_repository(missing.to_str().unwrap(), &NewOptions::default()).unwrap();
+    assert!(plan.create_directory);
+    assert!(plan.files.is_empty());
+    assert!(!missing.exists());
+}
+
+#[test]
+fn test_plan_lists_files_by_category_and_size_skips() {
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("repo");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+    std::fs::write(dir.join("src/main.rs"), "fn main(){}\n").unwrap();
+    std::fs::write(dir.join("src/lib.rs"), "pub fn a(){}\n").unwrap();
+    std::fs::write(dir.join("big.rs"), vec![b'a'; 2 * 1024 * 1024]).unwrap();
+    let s = dir.to_str().unwrap();
+
+    let opts = NewOptions {
+        max_file_mb: 1,
+        author: Some("Ada <ada@example.com>".to_string()),
+        ..NewOptions::default()
+    };
+    let plan = plan_new_repository(s, &opts).unwrap();
+    assert!(!plan.create_directory);
+    let groups = plan.files_by_category();
+    assert_eq!(groups["Rust"], vec!["src/lib.rs", "src/main.rs"]);
+    assert_eq!(groups["TOML"], vec!["Cargo.toml"]);
+    assert_eq!(plan.skipped_by_size, vec!["big.rs"]);
+    assert_eq!(plan.total_files, 4);
+    assert!(plan.gitignore.contains("target/"));
+    assert_eq!(plan.gitignore, generate_gitignore_content(s).unwrap());
+    assert_eq!(plan.author.as_deref(), Some("Ada <ada@example.com>"));
+    assert_eq!(plan.author_source, "--author");
+
+    let text = plan.render();
+    assert!(text.contains("Would stage 3 of 4 files:"), "{}", text);
+    assert!(
+        text.contains("  Rust (2): src/lib.rs, src/main.rs"),
+        "{}",
+        text
+    );
+    assert!(text.contains("Skipped by size cap: big.rs"), "{}", text);
+    assert!(
+        text.contains("Author: Ada <ada@example.com> (source: --author)"),
+        "{}",
+        text
+    );
+    assert!(!dir.join(".gitignore").exists());
+
+    // A real run stages exactly the planned files.
+    if !check_git_installed() {
+        return;
+    }
+    new_repository_with_options(s, false, 1, opts.author.as_deref(), &opts.identity).unwrap();
+    let repo = git2::Repository::open(&dir).unwrap();
+    let mut staged: Vec<String> = repo
+        .index()
+        .unwrap()
+        .iter()
+        .map(|e| String::from_utf8_lossy(&e.path).into_owned())
+        .collect();
+    staged.sort();
+    assert_eq!(staged, vec!["Cargo.toml", "src/lib.rs", "src/main.rs"]);
+    assert_eq!(
+        std::fs::read_to_string(dir.join(".gitignore")).unwrap(),
+        plan.gitignore
+    );
+}
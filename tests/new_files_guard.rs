@@ -0,0 +1,147 @@
+use clap::Parser;
+use git2::Repository;
+use mdcode::*;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn change(path: &str, status: ChangeKind) -> FileChange {
+    FileChange {
+        path: PathBuf::from(path),
+        old_path: None,
+        status,
+        insertions: 1,
+        deletions: 0,
+        category: None,
+    }
+}
+
+fn head_id(dir: &std::path::Path) -> git2::Oid {
+    Repository::open(dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id()
+}
+
+#[test]
+fn test_check_new_files_counts_only_added_files() {
+    let mut changes: Vec<FileChange> = (0..3)
+        .map(|i| change(&format!("vendor/lib/f{}.c", i), ChangeKind::Added))
+        .collect();
+    changes.push(change("docs/a.md", ChangeKind::Added));
+    changes.push(change("top.rs", ChangeKind::Added));
+    changes.extend((0..10).map(|i| change(&format!("src/m{}.rs", i), ChangeKind::Modified)));
+    changes.push(change("old.rs", ChangeKind::Deleted));
+
+    // 5 new files: at the threshold nothing triggers, modifications never count.
+    assert_eq!(check_new_files(&changes, 5), None);
+    assert_eq!(check_new_files(&changes, 0), None);
+
+    let report = check_new_files(&changes, 4).unwrap();
+    assert_eq!(report.new_files, 5);
+    assert_eq!(report.threshold, 4);
+    assert_eq!(
+        report.by_directory,
+        vec![
+            ("vendor".to_string(), 3),
+            (".".to_string(), 1),
+            ("docs".to_string(), 1),
+        ]
+    );
+    let text = report.render();
+    assert!(text.contains("adds 5 new files (limit 4)"), "{}", text);
+    assert!(text.contains("vendor"), "{}", text);
+}
+
+#[test]
+fn test_confirm_new_files_reads_answer() {
+    let changes: Vec<FileChange> = (0..3)
+        .map(|i| change(&format!("dump/{}.txt", i), ChangeKind::Added))
+        .collect();
+    let report = check_new_files(&changes, 2).unwrap();
+    let mut out = Vec::new();
+    assert!(confirm_new_files(&report, &mut "y\n".as_bytes(), &mut out).unwrap());
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("Commit all 3 new files? [y/N]"));
+    assert!(!confirm_new_files(&report, &mut "\n".as_bytes(), &mut Vec::new()).unwrap());
+    assert!(!confirm_new_files(&report, &mut "".as_bytes(), &mut Vec::new()).unwrap());
+}
+
+#[test]
+fn test_update_refuses_many_new_files_without_confirmation() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    for i in 0..4 {
+        std::fs::write(dir.join(format!("m{}.rs", i)), "fn a() {}\n").unwrap();
+    }
+    update_repository(s, false, Some("base"), 50).unwrap();
+    let before = head_id(&dir);
+
+    // Modifying tracked files never trips the guard.
+    for i in 0..4 {
+        std::fs::write(dir.join(format!("m{}.rs", i)), "fn b() {}\n").unwrap();
+    }
+    let opts = UpdateOptions {
+        max_new_files: 2,
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, Some("edit"), 50, &opts).unwrap();
+    let edited = head_id(&dir);
+    assert_ne!(edited, before);
+
+    std::fs::create_dir_all(dir.join("vendor/dump")).unwrap();
+    for i in 0..3 {
+        std::fs::write(dir.join(format!("vendor/dump/v{}.rs", i)), "fn v() {}\n").unwrap();
+    }
+    let err = update_repository_with_options(s, false, Some("dump"), 50, &opts)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("3 new files"), "{}", err);
+    assert!(err.contains("vendor"), "{}", err);
+    assert_eq!(head_id(&dir), edited);
+
+    // The threshold also comes from .mdcode.toml; --max-new-files overrides it.
+    std::fs::write(
+        dir.join(MDCODE_CONFIG_FILE),
+        "[update]\nmax_new_files = 1\n",
+    )
+    .unwrap();
+    assert_eq!(configured_max_new_files(s, None).unwrap(), Some(1));
+    assert_eq!(effective_max_new_files(None, s, None).unwrap(), 1);
+    assert_eq!(effective_max_new_files(Some(7), s, None).unwrap(), 7);
+    let run = |extra: &[&str]| {
+        let mut args = vec!["mdcode", "update", s, "-m", "dump"];
+        args.extend_from_slice(extra);
+        execute_cli(Cli::try_parse_from(args).unwrap())
+    };
+    assert!(run(&[]).is_err());
+    assert_eq!(head_id(&dir), edited);
+    run(&["--max-new-files", "10"]).unwrap();
+    assert_ne!(head_id(&dir), edited);
+}
+
+#[test]
+fn test_configured_max_new_files_rejects_bad_value() {
+    let t = tempdir().unwrap();
+    let s = t.path().to_str().unwrap();
+    assert_eq!(configured_max_new_files(s, None).unwrap(), None);
+    assert_eq!(
+        effective_max_new_files(None, s, None).unwrap(),
+        DEFAULT_MAX_NEW_FILES
+    );
+    std::fs::write(
+        t.path().join(MDCODE_CONFIG_FILE),
+        "[update]\nmax_new_files = -3\n",
+    )
+    .unwrap();
+    let err = configured_max_new_files(s, None).unwrap_err().to_string();
+    assert!(err.contains("update.max_new_files"), "{}", err);
+}
@@ -0,0 +1,48 @@
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+#[serial_test::serial]
+fn test_colorize_respects_no_color_env() {
+    std::env::remove_var("NO_COLOR");
+    assert_eq!(colorize(GREEN, "ok"), format!("{}ok{}", GREEN, RESET));
+    std::env::set_var("NO_COLOR", "1");
+    assert_eq!(colorize(GREEN, "ok"), "ok");
+    std::env::remove_var("NO_COLOR");
+}
+
+#[test]
+#[serial_test::serial]
+fn test_binary_output_has_no_ansi_codes_with_no_color() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let repo = tmp.path().join("r");
+    std::fs::create_dir_all(&repo).unwrap();
+    std::fs::write(repo.join("main.rs"), "fn main() {}\n").unwrap();
+    new_repository(repo.to_str().unwrap(), false, 50).unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_mdcode"))
+        .env("NO_COLOR", "1")
+        .arg("info")
+        .arg(&repo)
+        .output()
+        .unwrap();
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&out.stdout),
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(!text.contains('\x1b'), "unexpected ANSI codes: {:?}", text);
+
+    let out = Command::new(env!("CARGO_BIN_EXE_mdcode"))
+        .arg("--no-color")
+        .arg("info")
+        .arg(tmp.path().join("missing"))
+        .output()
+        .unwrap();
+    assert!(!String::from_utf8_lossy(&out.stderr).contains('\x1b'));
+}
@@ -0,0 +1,52 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_open_repo_missing_directory_is_friendly() {
+    let t = tempdir().unwrap();
+    let missing = t.path().join("does-not-exist");
+    let m = missing.to_str().unwrap();
+    let err = open_repo(m).err().unwrap();
+    assert!(matches!(err, MdcodeError::NoRepository(_)), "{:?}", err);
+    assert_eq!(
+        err.to_string(),
+        format!("No git repository in directory '{}'", m)
+    );
+
+    // Commands report the same message instead of their own variants.
+    let expected = err.to_string();
+    assert_eq!(info_repository(m).unwrap_err().to_string(), expected);
+    assert_eq!(
+        update_repository(m, false, None, 50)
+            .unwrap_err()
+            .to_string(),
+        expected
+    );
+    assert_eq!(repo_stats(m, 1024).unwrap_err().to_string(), expected);
+    assert_eq!(
+        file_history(m, "a.rs", 50).unwrap_err().to_string(),
+        expected
+    );
+}
+
+#[test]
+fn test_open_repo_with_commits_rejects_empty_repository() {
+    let t = tempdir().unwrap();
+    let dir = t.path().to_str().unwrap();
+    git2::Repository::init(t.path()).unwrap();
+    assert!(open_repo(dir).is_ok());
+    let err = open_repo_with_commits(dir).err().unwrap();
+    assert!(matches!(err, MdcodeError::EmptyRepository(_)), "{:?}", err);
+    assert_eq!(
+        err.to_string(),
+        format!("Empty repository: no commits exist in '{}'", dir)
+    );
+    assert_eq!(
+        info_repository(dir).unwrap_err().to_string(),
+        err.to_string()
+    );
+    assert_eq!(
+        repo_stats(dir, 1024).unwrap_err().to_string(),
+        err.to_string()
+    );
+}
@@ -0,0 +1,91 @@
+use mdcode::*;
+use std::io::Write;
+use tempfile::tempdir;
+
+#[test]
+#[serial_test::serial]
+fn test_pager_command_and_tty_check() {
+    assert!(should_paginate(true, true));
+    assert!(!should_paginate(true, false));
+    assert!(!should_paginate(false, true));
+
+    let prev = std::env::var("PAGER").ok();
+    std::env::remove_var("PAGER");
+    let default = pager_command();
+    std::env::set_var("PAGER", "more -s");
+    let custom = pager_command();
+    std::env::set_var("PAGER", "cat");
+    let cat = pager_command();
+    std::env::set_var("PAGER", "");
+    let empty = pager_command();
+    match prev {
+        Some(v) => std::env::set_var("PAGER", v),
+        None => std::env::remove_var("PAGER"),
+    }
+    assert_eq!(default.as_deref(), Some(DEFAULT_PAGER));
+    assert_eq!(custom.as_deref(), Some("more -s"));
+    assert_eq!(cat, None);
+    assert_eq!(empty, None);
+
+    use clap::Parser;
+    assert!(Cli::try_parse_from(["mdcode", "info", ".", "--paginate"])
+        .unwrap()
+        .paginate());
+    assert!(!Cli::try_parse_from(["mdcode", "info", "."])
+        .unwrap()
+        .paginate());
+}
+
+/// An executable pager script that copies its input to `out`.
+#[cfg(unix)]
+fn recording_pager(dir: &std::path::Path, out: &std::path::Path) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let script = dir.join("pager.sh");
+    std::fs::write(&script, format!("#!/bin/sh\ncat > '{}'\n", out.display())).unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script.to_str().unwrap().to_string()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pager_receives_written_output() {
+    let t = tempdir().unwrap();
+    let out = t.path().join("paged.txt");
+    let pager = Pager::start(&recording_pager(t.path(), &out)).unwrap();
+    let mut writer = pager.writer();
+    writeln!(writer, "[000] first").unwrap();
+    writeln!(writer, "[001] second").unwrap();
+    writer.flush().unwrap();
+    pager.finish().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&out).unwrap(),
+        "[000] first\n[001] second\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_paginate_is_skipped_when_stdout_is_not_a_terminal() {
+    let t = tempdir().unwrap();
+    let repo = t.path().join("r");
+    std::fs::create_dir_all(&repo).unwrap();
+    std::fs::write(repo.join("main.rs"), "fn main() {}\n").unwrap();
+    new_repository(repo.to_str().unwrap(), false, 50).unwrap();
+    let marker = t.path().join("paged.txt");
+
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_mdcode"))
+        .env("PAGER", recording_pager(t.path(), &marker))
+        .env("NO_COLOR", "1")
+        .args(["info", repo.to_str().unwrap(), "--paginate"])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let text = String::from_utf8_lossy(&out.stderr);
+    assert!(text.contains("[000]"), "{}", text);
+    assert!(text.contains("F: main.rs"), "{}", text);
+    assert!(!marker.exists());
+}
@@ -0,0 +1,168 @@
+use clap::Parser;
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &str, args: &[&str]) {
+    assert!(Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap()
+        .success());
+}
+
+/// Work repo with branches `master` and `feature`, an annotated tag `v1.0.0` on master
+/// and a lightweight tag `v1.1.0` on feature; returns (work dir, bare remote).
+fn setup(t: &Path) -> (String, Repository) {
+    let bare_path = t.join("remote.git");
+    let bare = Repository::init_bare(&bare_path).unwrap();
+    let work = t.join("work");
+    let s = work.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    add_remote(&s, "origin", bare_path.to_str().unwrap()).unwrap();
+    git(&s, &["config", "user.name", "t"]);
+    git(&s, &["config", "user.email", "t@example.com"]);
+    git(&s, &["tag", "-a", "v1.0.0", "-m", "release"]);
+    git(&s, &["checkout", "-q", "-b", "feature"]);
+    std::fs::write(work.join("f.txt"), "f").unwrap();
+    update_repository(&s, false, Some("feature"), 50).unwrap();
+    git(&s, &["tag", "v1.1.0"]);
+    git(&s, &["checkout", "-q", "master"]);
+    (s, bare)
+}
+
+fn has_ref(repo: &Repository, name: &str) -> bool {
+    repo.find_reference(name).is_ok()
+}
+
+#[test]
+fn test_push_args_refspecs() {
+    let opts = PushOptions {
+        set_upstream: false,
+        all_branches: true,
+        tags: true,
+        follow_tags: false,
+        timeout: None,
+    };
+    assert_eq!(
+        push_args("origin", "master", &opts),
+        vec![
+            "--porcelain",
+            "origin",
+            "refs/heads/*:refs/heads/*",
+            "refs/tags/*:refs/tags/*"
+        ]
+    );
+    let follow = PushOptions {
+        follow_tags: true,
+        ..PushOptions::default()
+    };
+    assert_eq!(
+        push_args("up", "main", &follow),
+        vec![
+            "--porcelain",
+            "-u",
+            "--follow-tags",
+            "up",
+            "refs/heads/main:refs/heads/main"
+        ]
+    );
+}
+
+#[test]
+fn test_parse_push_porcelain_lists_changed_refs() {
+    let out = "To /tmp/remote.git\n\
+*\trefs/heads/feature:refs/heads/feature\t[new branch]\n\
+=\trefs/heads/master:refs/heads/master\t[up to date]\n\
+\x20\trefs/heads/dev:refs/heads/dev\tabc1234..def5678\n\
+!\trefs/heads/old:refs/heads/old\t[rejected] (non-fast-forward)\n\
+*\trefs/tags/v1.0.0:refs/tags/v1.0.0\t[new tag]\n\
+Done\n";
+    assert_eq!(
+        parse_push_porcelain(out),
+        vec![
+            "refs/heads/feature ([new branch])",
+            "refs/heads/dev (abc1234..def5678)",
+            "refs/tags/v1.0.0 ([new tag])",
+        ]
+    );
+}
+
+#[test]
+fn test_gh_push_all_branches_and_tags() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (dir, bare) = setup(t.path());
+    let opts = PushOptions {
+        all_branches: true,
+        tags: true,
+        ..PushOptions::default()
+    };
+    let mut updated = gh_push_with_options(&dir, "origin", &opts).unwrap();
+    updated.sort();
+    assert_eq!(
+        updated,
+        vec![
+            "refs/heads/feature ([new branch])",
+            "refs/heads/master ([new branch])",
+            "refs/tags/v1.0.0 ([new tag])",
+            "refs/tags/v1.1.0 ([new tag])",
+        ]
+    );
+    for name in [
+        "refs/heads/master",
+        "refs/heads/feature",
+        "refs/tags/v1.0.0",
+        "refs/tags/v1.1.0",
+    ] {
+        assert!(has_ref(&bare, name), "{} missing on remote", name);
+    }
+
+    // Nothing left to update.
+    assert!(gh_push_with_options(&dir, "origin", &opts)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_gh_push_follow_tags_only_pushes_reachable_annotated_tags() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (dir, bare) = setup(t.path());
+    let opts = PushOptions {
+        follow_tags: true,
+        ..PushOptions::default()
+    };
+    gh_push_with_options(&dir, "origin", &opts).unwrap();
+    assert!(has_ref(&bare, "refs/heads/master"));
+    assert!(has_ref(&bare, "refs/tags/v1.0.0"));
+    assert!(!has_ref(&bare, "refs/heads/feature"));
+    assert!(!has_ref(&bare, "refs/tags/v1.1.0"));
+}
+
+#[test]
+fn test_cli_gh_push_tags_pushes_tag_created_after_branch() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (dir, bare) = setup(t.path());
+    gh_push(&dir, "origin").unwrap();
+    git(&dir, &["tag", "v1.2.0"]);
+    assert!(!has_ref(&bare, "refs/tags/v1.2.0"));
+
+    let cli = Cli::try_parse_from(["mdcode", "gh_push", dir.as_str(), "--tags"]).unwrap();
+    execute_cli(cli).unwrap();
+    assert!(has_ref(&bare, "refs/tags/v1.2.0"));
+}
@@ -0,0 +1,120 @@
+use git2::Repository;
+use mdcode::*;
+use tempfile::tempdir;
+
+fn setup(t: &std::path::Path) -> (String, Repository) {
+    let bare = t.join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    let work = t.join("work");
+    let s = work.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    let repo = Repository::open(&work).unwrap();
+    repo.remote("origin", bare.to_str().unwrap()).unwrap();
+    (s, repo)
+}
+
+fn tracking(repo: &Repository) -> (Option<String>, Option<String>) {
+    let cfg = repo.config().unwrap().snapshot().unwrap();
+    (
+        cfg.get_string("branch.master.remote").ok(),
+        cfg.get_string("branch.master.merge").ok(),
+    )
+}
+
+#[test]
+fn test_gh_push_sets_and_restores_upstream() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (dir, repo) = setup(t.path());
+    gh_push(&dir, "origin").unwrap();
+    assert_eq!(
+        tracking(&repo),
+        (
+            Some("origin".to_string()),
+            Some("refs/heads/master".to_string())
+        )
+    );
+
+    // Tracking removed behind our back is put back on the next push (branch now exists).
+    {
+        let mut cfg = repo.config().unwrap();
+        cfg.remove("branch.master.remote").unwrap();
+        cfg.remove("branch.master.merge").unwrap();
+    }
+    assert_eq!(tracking(&repo), (None, None));
+    gh_push(&dir, "origin").unwrap();
+    assert_eq!(
+        tracking(&repo),
+        (
+            Some("origin".to_string()),
+            Some("refs/heads/master".to_string())
+        )
+    );
+}
+
+#[test]
+fn test_gh_push_no_set_upstream_leaves_config_alone() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (dir, repo) = setup(t.path());
+    execute_cli(Cli {
+        command: Commands::GhPush {
+            directory: Some(dir.clone()),
+            remote: "origin".to_string(),
+            no_set_upstream: true,
+            all_branches: false,
+            tags: false,
+            follow_tags: false,
+            insecure: false,
+            set_default_branch: false,
+            recursive: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    })
+    .unwrap();
+    assert_eq!(tracking(&repo), (None, None));
+    let bare = Repository::open_bare(t.path().join("remote.git")).unwrap();
+    assert!(bare.find_reference("refs/heads/master").is_ok());
+}
+
+#[test]
+fn test_ensure_upstream_keeps_existing_settings() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let (dir, repo) = setup(t.path());
+    repo.config()
+        .unwrap()
+        .set_str("branch.master.remote", "upstream")
+        .unwrap();
+    let got = ensure_upstream(&dir, "origin", "master").unwrap();
+    assert_eq!(
+        got,
+        ("upstream".to_string(), "refs/heads/master".to_string())
+    );
+    assert_eq!(
+        describe_upstream("master", &got),
+        "'master' tracks 'upstream/master'"
+    );
+}
@@ -0,0 +1,68 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_reflog_lists_commit_lost_by_reset() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::write(dir.join("a.txt"), "a2\n").unwrap();
+    update_repository(s, false, Some("second"), 50).unwrap();
+
+    let repo = git2::Repository::open(&dir).unwrap();
+    let lost = repo.head().unwrap().peel_to_commit().unwrap();
+    let parent = lost.parent(0).unwrap();
+    repo.reset(parent.as_object(), git2::ResetType::Hard, None)
+        .unwrap();
+
+    let entries = reflog_command(s, None).unwrap();
+    assert_eq!(entries[0].index, 0);
+    assert_eq!(entries[0].old_id, lost.id());
+    assert_eq!(entries[0].new_id, parent.id());
+    assert!(!entries[0].unreachable);
+    let committed = entries
+        .iter()
+        .find(|e| e.new_id == lost.id())
+        .expect("the reset-away commit is in the reflog");
+    assert!(committed.unreachable, "{:?}", committed);
+    assert!(committed.message.contains("second"), "{:?}", committed);
+
+    assert_eq!(reflog_entries(s, Some(1)).unwrap().len(), 1);
+
+    // Through the CLI entry point.
+    let cli = Cli {
+        command: Commands::Reflog {
+            directory: Some(s.to_string()),
+            limit: Some(5),
+        },
+        dry_run: false,
+        max_file_mb: None,
+        no_color: true,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
+
+#[test]
+fn test_reflog_requires_a_repository() {
+    let tmp = tempdir().unwrap();
+    let err = reflog_entries(tmp.path().to_str().unwrap(), None).unwrap_err();
+    assert!(err.to_string().contains("No git repository"), "{}", err);
+}
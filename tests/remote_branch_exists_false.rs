@@ -12,6 +12,6 @@ fn test_remote_branch_exists_false_when_missing() {
     let s = repo.to_str().unwrap();
     new_repository(s, false, 50).unwrap();
     // No remote configured; should return Ok(false)
-    let exists = remote_branch_exists(s, "origin", "master").unwrap();
+    let exists = remote_branch_exists(s, "origin", "master", None).unwrap();
     assert!(!exists);
 }
@@ -0,0 +1,77 @@
+use git2::Repository;
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let ok = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap()
+        .success();
+    assert!(ok, "git {:?} failed", args);
+}
+
+#[test]
+fn test_diff_l_against_bare_remote_without_head() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    // The bare remote's HEAD names a branch nobody pushes, so it has no usable default.
+    let bare = tmp.path().join("remote.git");
+    let ok = Command::new("git")
+        .args(["init", "--quiet", "--bare", "--initial-branch=trunk"])
+        .arg(&bare)
+        .status()
+        .unwrap()
+        .success();
+    assert!(ok);
+
+    let work = tmp.path().join("work");
+    let work_s = work.to_str().unwrap();
+    std::fs::create_dir_all(&work).unwrap();
+    std::fs::write(work.join("a.txt"), "a\n").unwrap();
+    new_repository(work_s, false, 50).unwrap();
+    add_remote(work_s, "origin", bare.to_str().unwrap()).unwrap();
+    let branch = Repository::open(&work)
+        .unwrap()
+        .head()
+        .unwrap()
+        .shorthand()
+        .unwrap()
+        .to_string();
+    // Two branches on origin, so "the only remote branch" cannot decide either.
+    git(&work, &["push", "--quiet", "origin", &branch]);
+    git(
+        &work,
+        &["push", "--quiet", "origin", &format!("{}:other", branch)],
+    );
+    let repo = Repository::open(&work).unwrap();
+    assert!(repo.find_reference("refs/remotes/origin/HEAD").is_err());
+    assert_eq!(remote_default_branch(work_s, "origin", None).unwrap(), None);
+
+    std::fs::write(work.join("a.txt"), "changed\n").unwrap();
+    let changed = diff_name_only(work_s, &["L".to_string()]).unwrap();
+    // .gitignore, written by `new`, is untracked and still listed.
+    assert_eq!(changed, vec![".gitignore".to_string(), "a.txt".to_string()]);
+    let head = repo.head().unwrap().peel_to_commit().unwrap().id();
+    assert_eq!(get_remote_head_commit(&repo, work_s).unwrap().id(), head);
+
+    // Only a branch origin does not have leaves nothing to fall back to.
+    git(&work, &["checkout", "--quiet", "-b", "local-only"]);
+    git(&work, &["push", "--quiet", "origin", "--delete", "other"]);
+    git(
+        &work,
+        &["push", "--quiet", "origin", &format!("{}:another", branch)],
+    );
+    git(&work, &["fetch", "--quiet", "--prune", "origin"]);
+    let err = get_remote_head_commit(&repo, work_s)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("default branch"), "{}", err);
+    assert!(err.contains("origin/local-only"), "{}", err);
+}
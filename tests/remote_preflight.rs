@@ -0,0 +1,45 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+fn assert_remote_missing(err: Box<dyn std::error::Error>, remote: &str) {
+    let msg = err.to_string();
+    assert!(
+        msg.contains(&format!("remote '{}' not found", remote)),
+        "{}",
+        msg
+    );
+    assert!(msg.contains("mdcode gh_create"), "{}", msg);
+    assert!(
+        msg.contains(&format!("git remote add {} <url>", remote)),
+        "{}",
+        msg
+    );
+    assert_eq!(exit_code_for(err.as_ref()), 8);
+}
+
+#[test]
+fn test_commands_needing_a_remote_fail_early_without_one() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    assert_remote_missing(
+        diff_command(s, &["H".into(), "0".into()], false).unwrap_err(),
+        "origin",
+    );
+    assert_remote_missing(diff_command(s, &["L".into()], false).unwrap_err(), "origin");
+    assert_remote_missing(gh_push(s, "origin").unwrap_err(), "origin");
+    assert_remote_missing(gh_fetch(s, "upstream").unwrap_err(), "upstream");
+    assert_remote_missing(gh_sync(s, "origin").unwrap_err(), "origin");
+    assert_remote_missing(gh_sync_all(s, "origin", None).unwrap_err(), "origin");
+
+    // Numeric diffs never need a remote.
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    update_repository(s, false, Some("second"), 50).unwrap();
+    diff_command(s, &["1".into(), "0".into()], true).unwrap();
+}
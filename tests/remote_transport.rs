@@ -0,0 +1,113 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_classify_remote_url() {
+    let cases = [
+        ("https://github.com/o/r.git", RemoteUrlKind::Https),
+        ("HTTPS://github.com/o/r", RemoteUrlKind::Https),
+        ("http://git.example.com/o/r.git", RemoteUrlKind::Http),
+        ("git@github.com:o/r.git", RemoteUrlKind::Ssh),
+        ("github.com:o/r.git", RemoteUrlKind::Ssh),
+        ("ssh://git@github.com/o/r.git", RemoteUrlKind::Ssh),
+        ("ssh://git@host:2222/o/r.git", RemoteUrlKind::Ssh),
+        ("file:///srv/git/r.git", RemoteUrlKind::Local),
+        ("/srv/git/r.git", RemoteUrlKind::Local),
+        ("../r.git", RemoteUrlKind::Local),
+        ("C:\\repos\\r.git", RemoteUrlKind::Local),
+        ("git://example.com/r.git", RemoteUrlKind::Other),
+    ];
+    for (url, kind) in cases {
+        assert_eq!(classify_remote_url(url), kind, "{}", url);
+    }
+}
+
+fn repo_with_origin(t: &std::path::Path, url: &str) -> String {
+    let dir = t.join("work");
+    let s = dir.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    add_remote(&s, "origin", url).unwrap();
+    s
+}
+
+#[test]
+fn test_plain_http_remote_requires_insecure() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = repo_with_origin(t.path(), "http://git.example.invalid/o/r.git");
+    let err = check_remote_transport(&dir, "origin", false).unwrap_err();
+    assert!(err.to_string().contains("plain http"), "{}", err);
+    assert!(err.to_string().contains("--insecure"));
+    check_remote_transport(&dir, "origin", true).unwrap();
+    // Unknown remotes are left for the git operation to report.
+    check_remote_transport(&dir, "upstream", false).unwrap();
+
+    // The CLI refuses before touching the network.
+    let cli = Cli {
+        command: Commands::GhFetch {
+            directory: Some(dir.clone()),
+            remote: "origin".into(),
+            prune: false,
+            insecure: false,
+            recursive: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    let err = execute_cli(cli).unwrap_err();
+    assert!(err.to_string().contains("plain http"), "{}", err);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_ssh_remote_preflight_checks_agent_and_keys() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = repo_with_origin(t.path(), "git@github.com:o/r.git");
+    let home = t.path().join("home");
+    std::fs::create_dir_all(home.join(".ssh")).unwrap();
+    let keys = ["SSH_AUTH_SOCK", "GIT_SSH_COMMAND", "GIT_SSH", "HOME"];
+    let saved: Vec<_> = keys.iter().map(|k| (*k, std::env::var_os(k))).collect();
+    for k in keys {
+        std::env::remove_var(k);
+    }
+    std::env::set_var("HOME", &home);
+
+    // Only a public key: nothing to authenticate with.
+    std::fs::write(home.join(".ssh/id_ed25519.pub"), "ssh-ed25519 AAAA").unwrap();
+    assert!(!ssh_credentials_available());
+    let err = check_remote_transport(&dir, "origin", false).unwrap_err();
+    assert!(err.to_string().contains("no ssh-agent"), "{}", err);
+
+    std::env::set_var("SSH_AUTH_SOCK", t.path().join("agent.sock"));
+    check_remote_transport(&dir, "origin", false).unwrap();
+    std::env::remove_var("SSH_AUTH_SOCK");
+
+    std::fs::write(home.join(".ssh/id_ed25519"), "private").unwrap();
+    assert!(ssh_credentials_available());
+
+    for (k, v) in saved {
+        match v {
+            Some(v) => std::env::set_var(k, v),
+            None => std::env::remove_var(k),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use mdcode::*;
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+#[serial_test::serial]
+fn test_git_mv_committed_via_update_is_single_rename() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo = t.path().join("repo");
+    let s = repo.to_str().unwrap();
+    std::fs::create_dir_all(&repo).unwrap();
+    let body: String = (0..10).map(|i| format!("let v{} = {};\n", i, i)).collect();
+    std::fs::write(repo.join("old.rs"), &body).unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    assert!(Command::new("git")
+        .args(["-C", s, "mv", "old.rs", "new.rs"])
+        .status()
+        .unwrap()
+        .success());
+    update_repository(s, false, Some("rename"), 50).unwrap();
+
+    let history = commit_history(s, DEFAULT_RENAME_THRESHOLD).unwrap();
+    let last = history.last().unwrap();
+    assert_eq!(last.index, 0);
+    assert_eq!(last.summary, "rename");
+    assert_eq!(last.changes.len(), 1, "{:?}", last.changes);
+    let change = &last.changes[0];
+    assert_eq!(change.status, ChangeKind::Renamed);
+    assert_eq!(change.path, PathBuf::from("new.rs"));
+    assert_eq!(change.old_path, Some(PathBuf::from("old.rs")));
+
+    std::env::set_var("NO_COLOR", "1");
+    assert_eq!(render_change(change), "old.rs -> new.rs");
+    std::env::remove_var("NO_COLOR");
+
+    // Above 100% similarity nothing pairs, so the rename splits into delete + add.
+    std::fs::rename(repo.join("new.rs"), repo.join("newer.rs")).unwrap();
+    let edited = body.replace("let v0 = 0;", "let v0 = 42;");
+    std::fs::write(repo.join("newer.rs"), edited).unwrap();
+    assert!(Command::new("git")
+        .args(["-C", s, "add", "-A", "--", "new.rs", "newer.rs"])
+        .status()
+        .unwrap()
+        .success());
+    update_repository(s, false, Some("rename+edit"), 50).unwrap();
+    let strict = commit_history(s, 100).unwrap();
+    let kinds: Vec<ChangeKind> = strict
+        .last()
+        .unwrap()
+        .changes
+        .iter()
+        .map(|c| c.status)
+        .collect();
+    assert!(kinds.contains(&ChangeKind::Added) && kinds.contains(&ChangeKind::Deleted));
+    let loose = commit_history(s, DEFAULT_RENAME_THRESHOLD).unwrap();
+    let newer = loose
+        .last()
+        .unwrap()
+        .changes
+        .iter()
+        .find(|c| c.path == std::path::Path::new("newer.rs"))
+        .unwrap();
+    assert_eq!(newer.status, ChangeKind::Renamed);
+    assert_eq!(newer.old_path, Some(PathBuf::from("new.rs")));
+
+    info_repository_with_threshold(s, 80).unwrap();
+}
@@ -134,7 +134,8 @@ fn test_detect_file_type_images_and_assets() {
 
 #[test]
 fn test_generate_gitignore_content() {
-    let content = generate_gitignore_content(".").unwrap();
+    let tmp = tempdir().unwrap();
+    let content = generate_gitignore_content(tmp.path().to_str().unwrap()).unwrap();
     let expected = "target/\ntarget_ci/\nbin/\nobj/\nvenv/\n.venv/\nenv/\n*.tmp\n*.log";
     assert_eq!(content, expected);
 }
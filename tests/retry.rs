@@ -0,0 +1,160 @@
+use mdcode::*;
+use std::cell::Cell;
+use std::time::Duration;
+
+fn meta(status: u16) -> ResponseMeta {
+    ResponseMeta {
+        status,
+        ..ResponseMeta::default()
+    }
+}
+
+#[test]
+fn test_server_errors_back_off_exponentially_until_max_attempts() {
+    let m = meta(502);
+    assert_eq!(
+        retry_decision(&m, 1, 0),
+        RetryDecision::RetryAfter(Duration::from_secs(1))
+    );
+    assert_eq!(
+        retry_decision(&m, 2, 0),
+        RetryDecision::RetryAfter(Duration::from_secs(2))
+    );
+    assert_eq!(
+        retry_decision(&m, 4, 0),
+        RetryDecision::RetryAfter(Duration::from_secs(8))
+    );
+    assert_eq!(retry_decision(&m, MAX_ATTEMPTS, 0), RetryDecision::GiveUp);
+    // Network errors (no response) are retried like 5xx.
+    assert!(matches!(
+        retry_decision(&meta(0), 1, 0),
+        RetryDecision::RetryAfter(_)
+    ));
+    assert_eq!(backoff_delay(30), Duration::from_secs(60));
+}
+
+#[test]
+fn test_rate_limits_honor_retry_after_and_reset_headers() {
+    let retry_after = ResponseMeta {
+        status: 429,
+        retry_after: Some("7".into()),
+        ..ResponseMeta::default()
+    };
+    assert_eq!(
+        retry_decision(&retry_after, 1, 0),
+        RetryDecision::RetryAfter(Duration::from_secs(7))
+    );
+    let reset = ResponseMeta {
+        status: 403,
+        rate_limit_remaining: Some(0),
+        rate_limit_reset: Some(1_000_030),
+        ..ResponseMeta::default()
+    };
+    assert_eq!(
+        retry_decision(&reset, 1, 1_000_000),
+        RetryDecision::RetryAfter(Duration::from_secs(30))
+    );
+    // A reset already in the past still waits a moment.
+    assert_eq!(
+        retry_decision(&reset, 1, 2_000_000),
+        RetryDecision::RetryAfter(Duration::from_secs(1))
+    );
+    // Waiting for an hour-long reset is not worth it.
+    let far = ResponseMeta {
+        rate_limit_reset: Some(1_003_600),
+        ..reset
+    };
+    assert_eq!(retry_decision(&far, 1, 1_000_000), RetryDecision::GiveUp);
+}
+
+#[test]
+fn test_auth_and_client_errors_are_not_retried() {
+    assert_eq!(retry_decision(&meta(401), 1, 0), RetryDecision::GiveUp);
+    assert_eq!(retry_decision(&meta(403), 1, 0), RetryDecision::GiveUp);
+    assert_eq!(retry_decision(&meta(422), 1, 0), RetryDecision::GiveUp);
+    let limited_401 = ResponseMeta {
+        status: 401,
+        retry_after: Some("1".into()),
+        ..ResponseMeta::default()
+    };
+    assert_eq!(retry_decision(&limited_401, 1, 0), RetryDecision::GiveUp);
+}
+
+#[test]
+fn test_creation_requests_only_retry_rate_limits() {
+    // A 5xx or a dropped connection may follow a successful create.
+    assert_eq!(rate_limit_decision(&meta(502), 1, 0), RetryDecision::GiveUp);
+    assert_eq!(rate_limit_decision(&meta(0), 1, 0), RetryDecision::GiveUp);
+    assert_eq!(rate_limit_decision(&meta(422), 1, 0), RetryDecision::GiveUp);
+    let limited = ResponseMeta {
+        status: 429,
+        retry_after: Some("3".into()),
+        ..ResponseMeta::default()
+    };
+    assert_eq!(
+        rate_limit_decision(&limited, 1, 0),
+        RetryDecision::RetryAfter(Duration::from_secs(3))
+    );
+    assert_eq!(
+        rate_limit_decision(&limited, MAX_ATTEMPTS, 0),
+        RetryDecision::GiveUp
+    );
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let calls = Cell::new(0);
+    let err = rt
+        .block_on(with_rate_limit_retries("create", || {
+            calls.set(calls.get() + 1);
+            async {
+                Err::<(), _>(ApiFailure {
+                    meta: meta(503),
+                    message: "busy".into(),
+                })
+            }
+        }))
+        .unwrap_err();
+    assert_eq!(calls.get(), 1);
+    assert!(err.to_string().contains("after 1 attempt(s)"), "{}", err);
+}
+
+#[test]
+fn test_with_retries_retries_transient_failures_and_stops_on_auth() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let calls = Cell::new(0);
+    let value = rt
+        .block_on(with_retries("op", || {
+            calls.set(calls.get() + 1);
+            let n = calls.get();
+            async move {
+                if n < 3 {
+                    Err(ApiFailure {
+                        meta: ResponseMeta {
+                            status: 503,
+                            retry_after: Some("0".into()),
+                            ..ResponseMeta::default()
+                        },
+                        message: "busy".into(),
+                    })
+                } else {
+                    Ok(n)
+                }
+            }
+        }))
+        .unwrap();
+    assert_eq!(value, 3);
+
+    calls.set(0);
+    let err = rt
+        .block_on(with_retries("op", || {
+            calls.set(calls.get() + 1);
+            async {
+                Err::<(), _>(ApiFailure {
+                    meta: meta(401),
+                    message: "Bad credentials".into(),
+                })
+            }
+        }))
+        .unwrap_err();
+    assert_eq!(calls.get(), 1);
+    assert!(err.to_string().contains("HTTP 401: Bad credentials"));
+}
@@ -0,0 +1,173 @@
+use git2::{Repository, Signature, Time};
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// Commit `file` as `name <email>` (author and committer) at `when` (Unix seconds).
+fn commit_as(dir: &Path, file: &str, name: &str, email: &str, when: i64) -> git2::Oid {
+    std::fs::write(dir.join(file), file).unwrap();
+    let repo = Repository::open(dir).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file)).unwrap();
+    index.write().unwrap();
+    let sig = Signature::new(name, email, &Time::new(when, 120)).unwrap();
+    commit_current_index(&repo, &format!("Add {}", file), &sig, &sig, false).unwrap()
+}
+
+/// (author, committer, author time, message) of the newest `n` commits, newest first.
+fn identities(dir: &Path, n: usize) -> Vec<(String, String, i64, String)> {
+    let repo = Repository::open(dir).unwrap();
+    let mut commit = repo.head().unwrap().peel_to_commit().unwrap();
+    let mut out = Vec::new();
+    for _ in 0..n {
+        let who = |s: Signature| format!("{} <{}>", s.name().unwrap(), s.email().unwrap());
+        out.push((
+            who(commit.author()),
+            who(commit.committer()),
+            commit.author().when().seconds(),
+            commit.message().unwrap().to_string(),
+        ));
+        match commit.parent(0) {
+            Ok(p) => commit = p,
+            Err(_) => break,
+        }
+    }
+    out
+}
+
+#[test]
+fn test_rewrite_author_on_local_commits() {
+    let t = tempdir().unwrap();
+    let dir = t.path();
+    Repository::init(dir).unwrap();
+    let s = dir.to_str().unwrap();
+    commit_as(dir, "a.txt", "Right", "right@example.com", 1_000_000_000);
+    commit_as(dir, "b.txt", "Wrong", "WRONG@example.com", 1_000_000_100);
+    commit_as(dir, "c.txt", "Other", "other@example.com", 1_000_000_200);
+    commit_as(dir, "d.txt", "Wrong", "wrong@example.com", 1_000_000_300);
+    let before = Repository::open(dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id();
+
+    let dry = rewrite_author(
+        s,
+        "wrong@example.com",
+        "Fixed <fixed@example.com>",
+        3,
+        false,
+        false,
+        true,
+    );
+    assert_eq!(dry.unwrap(), 2);
+    assert_eq!(identities(dir, 1)[0].0, "Wrong <wrong@example.com>");
+
+    let n = rewrite_author(
+        s,
+        "wrong@example.com",
+        "Fixed <fixed@example.com>",
+        3,
+        false,
+        false,
+        false,
+    );
+    assert_eq!(n.unwrap(), 2);
+    let after = identities(dir, 4);
+    assert_eq!(after[0].0, "Fixed <fixed@example.com>");
+    assert_eq!(after[0].1, "Fixed <fixed@example.com>");
+    assert_eq!(after[0].2, 1_000_000_300);
+    assert_eq!(after[0].3, "Add d.txt");
+    assert_eq!(after[1].0, "Other <other@example.com>");
+    assert_eq!(after[2].0, "Fixed <fixed@example.com>");
+    assert_eq!(after[2].2, 1_000_000_100);
+    assert_eq!(after[3].0, "Right <right@example.com>");
+
+    let repo = Repository::open(dir).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_ne!(head.id(), before);
+    assert_eq!(head.tree_id(), repo.find_commit(before).unwrap().tree_id());
+    // Nothing left to rewrite.
+    assert_eq!(
+        rewrite_author(
+            s,
+            "wrong@example.com",
+            "Fixed <fixed@example.com>",
+            4,
+            false,
+            false,
+            false
+        )
+        .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_rewrite_author_refuses_pushed_commits_without_force() {
+    let t = tempdir().unwrap();
+    let dir = t.path();
+    let repo = Repository::init(dir).unwrap();
+    let s = dir.to_str().unwrap();
+    let pushed = commit_as(dir, "a.txt", "Wrong", "wrong@example.com", 1_000_000_000);
+    commit_as(dir, "b.txt", "Wrong", "wrong@example.com", 1_000_000_100);
+    repo.reference("refs/remotes/origin/master", pushed, true, "test")
+        .unwrap();
+
+    // The newest commit alone is not pushed.
+    assert_eq!(
+        rewrite_author(
+            s,
+            "wrong@example.com",
+            "Fixed <f@example.com>",
+            1,
+            false,
+            false,
+            false
+        )
+        .unwrap(),
+        1
+    );
+    let err = rewrite_author(
+        s,
+        "wrong@example.com",
+        "Fixed <f@example.com>",
+        2,
+        false,
+        false,
+        false,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("pushed") && err.contains("--force"), "{}", err);
+    assert_eq!(identities(dir, 2)[1].0, "Wrong <wrong@example.com>");
+
+    rewrite_author(
+        s,
+        "wrong@example.com",
+        "Fixed <f@example.com>",
+        2,
+        true,
+        false,
+        false,
+    )
+    .unwrap();
+    assert!(identities(dir, 2)
+        .iter()
+        .all(|(author, ..)| author == "Fixed <f@example.com>"));
+
+    let err = rewrite_author(s, "x@example.com", "no email", 1, false, false, false).unwrap_err();
+    assert!(err.to_string().contains("invalid author"));
+    let err = rewrite_author(
+        s,
+        "x@example.com",
+        "X <x@example.com>",
+        5,
+        false,
+        false,
+        false,
+    );
+    assert!(err.unwrap_err().to_string().contains("only 2 commits"));
+}
@@ -0,0 +1,87 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+fn repo_with_history(root: &std::path::Path) -> String {
+    let dir = root.join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    let s = dir.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    std::fs::write(dir.join("b.txt"), "b\n").unwrap();
+    update_repository(&s, false, Some("add b"), 50).unwrap();
+    s
+}
+
+#[test]
+fn test_short_sha_from_info_selects_the_same_commit_later() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let s = repo_with_history(tmp.path());
+    let history = commit_history(&s, 50).unwrap();
+    let add_b = history.iter().find(|c| c.summary == "add b").unwrap();
+    let sha = short_sha(add_b.id);
+    assert_eq!(sha.len(), SHORT_SHA_LEN);
+    assert!(is_short_sha(&sha));
+    assert!(!is_short_sha("3"));
+
+    // A new commit shifts the indexes, but not the short SHA.
+    std::fs::write(tmp.path().join("r").join("c.txt"), "c\n").unwrap();
+    update_repository(&s, false, Some("add c"), 50).unwrap();
+    let repo = git2::Repository::open(&s).unwrap();
+    assert_eq!(commit_by_index_or_sha(&repo, &sha).unwrap().id(), add_b.id);
+    assert_ne!(
+        commit_by_index_or_sha(&repo, &add_b.index.to_string())
+            .unwrap()
+            .id(),
+        add_b.id
+    );
+
+    let changes = compute_diff(&s, &sha, "0").unwrap();
+    let paths: Vec<String> = changes
+        .iter()
+        .map(|c| c.path.display().to_string())
+        .collect();
+    assert_eq!(paths, vec!["c.txt"]);
+    diff_command(&s, &[sha.clone(), "0".into()], true).unwrap();
+    diff_command(&s, &[sha], true).unwrap();
+}
+
+#[test]
+fn test_short_sha_errors() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let s = repo_with_history(tmp.path());
+    let repo = git2::Repository::open(&s).unwrap();
+    let err = commit_by_index_or_sha(&repo, "deadbeef").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("no commit matches short SHA 'deadbeef'"),
+        "{}",
+        err
+    );
+    let err = diff_command(&s, &["0".into(), "deadbeef".into()], true).unwrap_err();
+    assert!(err.to_string().contains("no commit matches"), "{}", err);
+
+    // A blob id is not a commit.
+    let blob = repo.blob(b"x\n").unwrap();
+    let err = commit_by_index_or_sha(&repo, &blob.to_string()[..10]).unwrap_err();
+    assert!(err.to_string().contains("is not a commit"), "{}", err);
+
+    // Write blobs until two ids share a 4-digit prefix, the shortest libgit2 resolves.
+    let mut seen = std::collections::HashMap::new();
+    let prefix = (0u32..)
+        .find_map(|i| {
+            let id = repo.blob(format!("blob {}\n", i).as_bytes()).unwrap();
+            let prefix = id.to_string()[..4].to_string();
+            seen.insert(prefix.clone(), id).map(|_| prefix)
+        })
+        .unwrap();
+    let err = resolve_short_sha(&repo, &prefix).unwrap_err();
+    assert!(err.to_string().contains("is ambiguous"), "{}", err);
+}
@@ -0,0 +1,100 @@
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn repo_with_change(root: &std::path::Path) -> (String, git2::Oid) {
+    let dir = root.join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    let s = dir.to_str().unwrap().to_string();
+    new_repository(&s, false, 50).unwrap();
+    let head = git2::Repository::open(&dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .target()
+        .unwrap();
+    std::fs::write(dir.join("b.txt"), "b\n").unwrap();
+    (s, head)
+}
+
+fn signed_update(dir: &str, message: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let opts = UpdateOptions {
+        identity: SignatureOptions {
+            sign: true,
+            ..SignatureOptions::default()
+        },
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(dir, false, Some(message), 50, &opts)
+}
+
+#[test]
+#[serial_test::serial]
+fn test_sign_commits_without_a_key_fails_clearly() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let (s, head) = repo_with_change(tmp.path());
+    // An empty GnuPG home has no secret key to sign with.
+    let gnupg = tmp.path().join("gnupg");
+    std::fs::create_dir_all(&gnupg).unwrap();
+    let prev = std::env::var("GNUPGHOME").ok();
+    std::env::set_var("GNUPGHOME", &gnupg);
+
+    let err = signed_update(&s, "signed").unwrap_err();
+    match prev {
+        Some(v) => std::env::set_var("GNUPGHOME", v),
+        None => std::env::remove_var("GNUPGHOME"),
+    }
+    assert!(
+        err.to_string().contains("signing the commit failed"),
+        "{}",
+        err
+    );
+    assert!(err.to_string().contains("user.signingkey"), "{}", err);
+    let repo = git2::Repository::open(&s).unwrap();
+    assert_eq!(repo.head().unwrap().target().unwrap(), head);
+
+    // Without signing the same update commits normally.
+    update_repository(&s, false, Some("unsigned"), 50).unwrap();
+    let commit = repo.head().unwrap().peel_to_commit().unwrap();
+    assert!(repo.extract_signature(&commit.id(), None).is_err());
+}
+
+#[test]
+#[serial_test::serial]
+fn test_sign_commits_with_ssh_key_signs_commit() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let key = tmp.path().join("id_ed25519");
+    let generated = Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key)
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+    if !generated {
+        eprintln!("ssh-keygen not available; skipping");
+        return;
+    }
+    let (s, head) = repo_with_change(tmp.path());
+    let repo = git2::Repository::open(&s).unwrap();
+    let mut cfg = repo.config().unwrap();
+    cfg.set_str("gpg.format", "ssh").unwrap();
+    cfg.set_str("user.signingkey", key.to_str().unwrap())
+        .unwrap();
+
+    signed_update(&s, "signed").unwrap();
+
+    let commit = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(commit.parent_id(0).unwrap(), head);
+    assert_eq!(commit.message(), Some("signed"));
+    let (signature, _) = repo.extract_signature(&commit.id(), None).unwrap();
+    assert!(String::from_utf8_lossy(&signature).contains("SSH SIGNATURE"));
+}
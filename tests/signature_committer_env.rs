@@ -18,7 +18,7 @@ fn test_resolve_signature_uses_committer_env_when_author_unset() {
     std::env::set_var("GIT_COMMITTER_NAME", "Committer Name");
     std::env::set_var("GIT_COMMITTER_EMAIL", "committer@example.com");
 
-    let (sig, src) = resolve_signature_with_source(&repo).unwrap();
+    let (sig, src) = resolve_signature_with_source(&repo, &SignatureOptions::default()).unwrap();
     assert_eq!(sig.name(), Some("Committer Name"));
     assert_eq!(sig.email(), Some("committer@example.com"));
     assert_eq!(src, "env:GIT_COMMITTER_NAME/GIT_COMMITTER_EMAIL");
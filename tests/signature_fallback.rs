@@ -20,7 +20,7 @@ fn test_resolve_signature_fallback_when_no_env_or_config() {
     // Ensure repo config has no identity
     // (libgit2 global config may still exist; we don't control it reliably here)
     // If global config is present, this test still passes by not asserting exact source string.
-    let (_sig, _src) = resolve_signature_with_source(&repo).unwrap();
+    let (_sig, _src) = resolve_signature_with_source(&repo, &SignatureOptions::default()).unwrap();
     // Restore env
     for (k, v) in saved {
         if let Some(v) = v {
@@ -23,7 +23,7 @@ fn test_resolve_signature_mdcode_fallback_with_clean_env() {
     std::env::set_var("MDCODE_IGNORE_GLOBAL_GIT", "1");
     // Ensure repo-local config has no identity
     // Call and assert fallback
-    let (sig, src) = resolve_signature_with_source(&repo).unwrap();
+    let (sig, src) = resolve_signature_with_source(&repo, &SignatureOptions::default()).unwrap();
     assert_eq!(sig.name(), Some("mdcode"));
     assert_eq!(sig.email(), Some("mdcode@example.com"));
     assert_eq!(src, "mdcode fallback");
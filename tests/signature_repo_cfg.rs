@@ -16,7 +16,7 @@ fn test_resolve_signature_uses_repo_or_global_config() {
     cfg.set_str("user.name", "Repo User").unwrap();
     cfg.set_str("user.email", "repo@example.com").unwrap();
 
-    let (sig, src) = resolve_signature_with_source(&repo).unwrap();
+    let (sig, src) = resolve_signature_with_source(&repo, &SignatureOptions::default()).unwrap();
     assert_eq!(sig.name(), Some("Repo User"));
     assert_eq!(sig.email(), Some("repo@example.com"));
     assert_eq!(src, "git config (repo/global)");
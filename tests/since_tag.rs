@@ -0,0 +1,125 @@
+use clap::Parser;
+use git2::Repository;
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &str, args: &[&str]) {
+    assert!(Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap()
+        .success());
+}
+
+fn commit_file(dir: &str, name: &str) -> git2::Oid {
+    std::fs::write(std::path::Path::new(dir).join(name), name).unwrap();
+    update_repository(dir, false, Some(name), 50).unwrap();
+    Repository::open(dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .target()
+        .unwrap()
+}
+
+#[test]
+fn test_since_tag_lists_only_newer_commits() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("notes.txt"), "v0\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    git(s, &["config", "user.name", "t"]);
+    git(s, &["config", "user.email", "t@example.com"]);
+
+    let old = commit_file(s, "a.txt");
+    git(s, &["tag", "v0.9.0", &old.to_string()]);
+    let released = commit_file(s, "b.txt");
+    git(
+        s,
+        &[
+            "tag",
+            "-a",
+            "v0.10.0",
+            "-m",
+            "release",
+            &released.to_string(),
+        ],
+    );
+    commit_file(s, "c.txt");
+    std::fs::write(dir.join("notes.txt"), "v1\n").unwrap();
+    update_repository(s, false, Some("notes"), 50).unwrap();
+    git(s, &["tag", "nightly"]);
+
+    // Numeric semver ordering, non-semver tags ignored.
+    let repo = Repository::open(&dir).unwrap();
+    assert_eq!(
+        latest_semver_tag(&repo).unwrap().as_deref(),
+        Some("v0.10.0")
+    );
+    assert_eq!(
+        resolve_since_tag(s, None).unwrap(),
+        ("v0.10.0".to_string(), released)
+    );
+    assert_eq!(resolve_since_tag(s, Some("v0.9.0")).unwrap().1, old);
+    let err = resolve_since_tag(s, Some("v9.9.9")).unwrap_err();
+    assert!(err.to_string().contains("tag 'v9.9.9' not found"));
+
+    let summaries = |since| {
+        commit_history_since(s, DEFAULT_RENAME_THRESHOLD, since)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.summary)
+            .collect::<std::collections::BTreeSet<_>>()
+    };
+    // Commits made within the same second have no stable order; compare as sets.
+    let set = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+    assert_eq!(summaries(Some(released)), set(&["c.txt", "notes"]));
+    assert_eq!(summaries(Some(old)), set(&["b.txt", "c.txt", "notes"]));
+    assert_eq!(summaries(None).len(), 5);
+
+    let log = file_history_since(s, "notes.txt", DEFAULT_RENAME_THRESHOLD, Some(released)).unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].summary, "notes");
+
+    // Flag without a value selects the latest semver tag; with one, that tag.
+    let cli = Cli::try_parse_from(["mdcode", "info", s, "--since-tag"]).unwrap();
+    match &cli.command {
+        Commands::Info { since_tag, .. } => assert_eq!(since_tag, &Some(None)),
+        _ => panic!("expected info"),
+    }
+    execute_cli(cli).unwrap();
+    let cli =
+        Cli::try_parse_from(["mdcode", "log", s, "notes.txt", "--since-tag", "v0.9.0"]).unwrap();
+    match &cli.command {
+        Commands::Log { since_tag, .. } => {
+            assert_eq!(since_tag, &Some(Some("v0.9.0".to_string())))
+        }
+        _ => panic!("expected log"),
+    }
+    execute_cli(cli).unwrap();
+}
+
+#[test]
+fn test_since_tag_without_semver_tags_errors() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let err = resolve_since_tag(s, None).unwrap_err();
+    assert!(err.to_string().contains("no semver tags found"));
+}
@@ -0,0 +1,110 @@
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+const MB: usize = 1024 * 1024;
+
+fn scanned(dir: &str, max_file_mb: u64) -> Vec<String> {
+    list_source_files(dir, max_file_mb)
+        .unwrap()
+        .into_iter()
+        .map(|(p, _)| p)
+        .collect()
+}
+
+#[test]
+fn test_max_file_mb_zero_means_no_limit() {
+    assert_eq!(cap_bytes(0), None);
+    assert_eq!(cap_bytes(2), Some(2 * MB as u64));
+    assert!(!exceeds_cap(u64::MAX, 0));
+    assert!(exceeds_cap(2 * MB as u64 + 1, 2));
+
+    let t = tempdir().unwrap();
+    let d = t.path();
+    std::fs::write(d.join("small.rs"), "fn a() {}").unwrap();
+    std::fs::write(d.join("huge.rs"), vec![b'a'; 2 * MB]).unwrap();
+    let s = d.to_str().unwrap();
+    assert_eq!(scanned(s, 1), vec!["small.rs"]);
+    assert_eq!(scanned(s, 0), vec!["huge.rs", "small.rs"]);
+}
+
+#[test]
+fn test_category_cap_beats_default() {
+    let t = tempdir().unwrap();
+    let d = t.path();
+    std::fs::write(
+        d.join(MDCODE_CONFIG_FILE),
+        "[size_caps]\nImage = 5\naudio = 0\ndefault = 1\n",
+    )
+    .unwrap();
+    std::fs::write(d.join("logo.png"), vec![0u8; 2 * MB]).unwrap();
+    std::fs::write(d.join("poster.png"), vec![0u8; 6 * MB]).unwrap();
+    std::fs::write(d.join("sample.wav"), vec![0u8; 3 * MB]).unwrap();
+    std::fs::write(d.join("big.rs"), vec![b'a'; 2 * MB]).unwrap();
+    let s = d.to_str().unwrap();
+
+    let caps = SizeCaps::load(s, None).unwrap();
+    assert_eq!(caps.default_mb, Some(1));
+    let cap = caps.cap_for(Path::new("logo.png"), 1);
+    assert_eq!(
+        cap,
+        AppliedCap {
+            mb: 5,
+            category: Some("Image")
+        }
+    );
+    assert_eq!(caps.source(&cap), "size_caps.Image in .mdcode.toml");
+    assert_eq!(caps.cap_for(Path::new("big.rs"), 1).category, None);
+
+    // Images get 5 MB and audio is unlimited although the default cap is 1 MB.
+    // The config itself is a TOML file and gets committed like any other.
+    assert_eq!(
+        scanned(s, 1),
+        vec![".mdcode.toml", "logo.png", "sample.wav"]
+    );
+    let ignored: Vec<(String, IgnoreReason)> = list_ignored_files(s, 1)
+        .unwrap()
+        .into_iter()
+        .map(|f| (f.path, f.reason))
+        .collect();
+    assert!(ignored.contains(&("big.rs".to_string(), IgnoreReason::SizeCap)));
+    assert!(ignored.contains(&("poster.png".to_string(), IgnoreReason::SizeCap)));
+
+    // --max-file-mb replaces only the default; the Image cap still applies.
+    assert_eq!(effective_max_file_mb(None, s, None), 1);
+    assert_eq!(effective_max_file_mb(Some(10), s, None), 10);
+    assert_eq!(
+        scanned(s, 10),
+        vec![".mdcode.toml", "big.rs", "logo.png", "sample.wav"]
+    );
+}
+
+#[test]
+fn test_size_caps_config_errors_and_defaults() {
+    let t = tempdir().unwrap();
+    let s = t.path().to_str().unwrap();
+    assert_eq!(SizeCaps::load(s, None).unwrap(), SizeCaps::default());
+    assert_eq!(effective_max_file_mb(None, s, None), DEFAULT_MAX_FILE_MB);
+
+    assert!(SizeCaps::parse("[size_caps]\nImage = -1\n").is_err());
+    assert!(SizeCaps::parse("[size_caps]\nImage = \"big\"\n").is_err());
+    assert!(SizeCaps::parse("size_caps = 3\n").is_err());
+    assert_eq!(
+        SizeCaps::parse("[other]\nx = 1\n").unwrap(),
+        SizeCaps::default()
+    );
+
+    std::fs::write(t.path().join(MDCODE_CONFIG_FILE), "[size_caps\n").unwrap();
+    let err = scan_source_files(s, 50).unwrap_err().to_string();
+    assert!(err.contains(".mdcode.toml"), "{}", err);
+}
+
+#[test]
+fn test_max_file_mb_flag_is_optional() {
+    use clap::Parser;
+    let cli = Cli::try_parse_from(["mdcode", "list-files", "."]).unwrap();
+    assert_eq!(cli.max_file_mb, None);
+    let cli = Cli::try_parse_from(["mdcode", "--max-file-mb", "0", "list-files", "."]).unwrap();
+    assert_eq!(cli.max_file_mb, Some(0));
+    assert_eq!(cli.max_file_mb_for(".", None), 0);
+}
@@ -0,0 +1,112 @@
+use mdcode::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn commit_as(dir: &Path, name: &str, email: &str, msg: &str) {
+    let ok = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", &format!("user.name={}", name)])
+        .args(["-c", &format!("user.email={}", email)])
+        .args(["commit", "-q", "-m", msg])
+        .status()
+        .unwrap()
+        .success();
+    assert!(ok);
+}
+
+fn fixture() -> tempfile::TempDir {
+    let tmp = tempdir().unwrap();
+    let d = tmp.path();
+    Command::new("git")
+        .arg("-C")
+        .arg(d)
+        .arg("init")
+        .arg("-q")
+        .status()
+        .unwrap();
+    std::fs::write(d.join("main.rs"), "fn main() {\n    println!(\"hi\");\n}\n").unwrap();
+    std::fs::write(d.join("README.md"), "# Title\nno trailing newline").unwrap();
+    Command::new("git")
+        .arg("-C")
+        .arg(d)
+        .args(["add", "."])
+        .status()
+        .unwrap();
+    commit_as(d, "Alice", "alice@example.com", "first");
+    std::fs::write(d.join("logo.png"), vec![0u8; 4096]).unwrap();
+    std::fs::write(d.join("notes.xyz"), "a\nb\n").unwrap();
+    Command::new("git")
+        .arg("-C")
+        .arg(d)
+        .args(["add", "."])
+        .status()
+        .unwrap();
+    commit_as(d, "Bob", "bob@example.com", "second");
+    tmp
+}
+
+#[test]
+fn test_repo_stats_counts_fixture() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = fixture();
+    let stats = repo_stats(tmp.path().to_str().unwrap(), 1024 * 1024).unwrap();
+    assert_eq!(stats.tracked_files, 4);
+    assert_eq!(
+        stats.categories["Rust"],
+        CategoryStats { files: 1, lines: 3 }
+    );
+    assert_eq!(
+        stats.categories["Documentation"],
+        CategoryStats { files: 1, lines: 2 }
+    );
+    assert_eq!(
+        stats.categories["Image"],
+        CategoryStats { files: 1, lines: 0 }
+    );
+    assert_eq!(
+        stats.categories["Other"],
+        CategoryStats { files: 1, lines: 2 }
+    );
+    assert_eq!(stats.total_lines, 7);
+    assert_eq!(stats.commit_count, 2);
+    assert_eq!(stats.contributors, 2);
+    assert!(stats.first_commit.unwrap() <= stats.last_commit.unwrap());
+    assert_eq!(stats.largest_files[0], ("logo.png".to_string(), 4096));
+    assert_eq!(stats.largest_files.len(), 4);
+    assert!(stats.object_count >= 8);
+    assert!(stats.git_dir_bytes > 0);
+}
+
+#[test]
+fn test_repo_stats_caps_line_counting_and_json_shape() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = fixture();
+    // 20-byte cap: main.rs (35 bytes) is sized but not line-counted.
+    let stats = repo_stats(tmp.path().to_str().unwrap(), 20).unwrap();
+    assert_eq!(stats.categories["Rust"].lines, 0);
+    assert!(stats.skipped_line_counts >= 1);
+
+    let json = stats.to_json();
+    assert_eq!(json["tracked_files"], 4);
+    assert_eq!(json["categories"]["Image"]["files"], 1);
+    assert_eq!(json["largest_files"][0]["path"], "logo.png");
+    assert!(json["first_commit"].is_string());
+
+    stats_command(tmp.path().to_str().unwrap(), true, 1024).unwrap();
+}
+
+#[test]
+fn test_repo_stats_empty_repo_errors() {
+    let tmp = tempdir().unwrap();
+    git2::Repository::init(tmp.path()).unwrap();
+    let err = repo_stats(tmp.path().to_str().unwrap(), 1024).unwrap_err();
+    assert!(err.to_string().contains("no commits"));
+}
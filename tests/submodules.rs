@@ -0,0 +1,88 @@
+use mdcode::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+        .args(["-c", "protocol.file.allow=always"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "git {:?}: {}",
+        args,
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+#[test]
+fn test_submodule_contents_are_not_scanned_or_staged() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let lib = t.path().join("lib");
+    std::fs::create_dir_all(&lib).unwrap();
+    std::fs::write(lib.join("lib.rs"), "pub fn lib() {}\n").unwrap();
+    git(&lib, &["init", "-q"]);
+    git(&lib, &["add", "-A"]);
+    git(&lib, &["commit", "-q", "-m", "lib"]);
+
+    let main = t.path().join("main");
+    let s = main.to_str().unwrap();
+    std::fs::create_dir_all(&main).unwrap();
+    std::fs::write(main.join("main.rs"), "fn main() {}\n").unwrap();
+    new_repository(s, false, 50).unwrap();
+    git(
+        &main,
+        &[
+            "submodule",
+            "add",
+            "-q",
+            lib.to_str().unwrap(),
+            "vendor/lib",
+        ],
+    );
+    git(&main, &["commit", "-q", "-m", "add submodule"]);
+
+    // Edits inside the submodule's working tree, plus an unregistered nested repository.
+    std::fs::write(main.join("vendor/lib/lib.rs"), "pub fn changed() {}\n").unwrap();
+    std::fs::write(main.join("vendor/lib/extra.rs"), "pub fn extra() {}\n").unwrap();
+    std::fs::create_dir_all(main.join("nested")).unwrap();
+    git(&main.join("nested"), &["init", "-q"]);
+    std::fs::write(main.join("nested/inner.rs"), "fn inner() {}\n").unwrap();
+    std::fs::write(main.join("app.rs"), "fn app() {}\n").unwrap();
+
+    assert_eq!(submodule_paths(s), vec![main.join("vendor/lib")]);
+    let files: Vec<String> = list_source_files(s, 50)
+        .unwrap()
+        .into_iter()
+        .map(|(p, _)| p)
+        .collect();
+    assert_eq!(files, vec!["app.rs", "main.rs"]);
+    // .gitignore, .gitmodules, main.rs, app.rs
+    assert_eq!(scan_total_files(s).unwrap(), 4);
+    assert!(modified_tracked_files(s).unwrap().is_empty());
+
+    // Coverage builds stage with plain `git add -A`, which has its own submodule handling.
+    #[cfg(not(coverage))]
+    {
+        use git2::{ObjectType, Repository};
+
+        update_repository(s, false, Some("app"), 50).unwrap();
+        let repo = Repository::open(&main).unwrap();
+        let tree = repo.head().unwrap().peel_to_tree().unwrap();
+        assert!(tree.get_path(Path::new("app.rs")).is_ok());
+        // The submodule is still a single gitlink entry, with no blobs committed beneath it.
+        let link = tree.get_path(Path::new("vendor/lib")).unwrap();
+        assert_eq!(link.kind(), Some(ObjectType::Commit));
+        assert!(tree.get_path(Path::new("vendor/lib/extra.rs")).is_err());
+        assert!(tree.get_path(Path::new("nested/inner.rs")).is_err());
+    }
+}
@@ -14,6 +14,22 @@ fn test_normalize_semver_tag_variants() {
     assert_eq!(t3, "v2.0.0");
 }
 
+#[test]
+fn test_normalize_semver_tag_prerelease_and_build_metadata() {
+    let (v, t) = normalize_semver_tag("1.2.3-rc.1+build.5").unwrap();
+    assert_eq!(t, "v1.2.3-rc.1+build.5");
+    assert_eq!(v.pre.as_str(), "rc.1");
+    assert_eq!(v.build.as_str(), "build.5");
+    let (_, t) = normalize_semver_tag("v1.2.3-alpha").unwrap();
+    assert_eq!(t, "v1.2.3-alpha");
+    let (_, t) = normalize_semver_tag("1.2.3+20240101.sha-abc").unwrap();
+    assert_eq!(t, "v1.2.3+20240101.sha-abc");
+    let (_, t) = normalize_semver_tag("v0.1.0-beta.2.x-y+exp.sha.5114f85").unwrap();
+    assert_eq!(t, "v0.1.0-beta.2.x-y+exp.sha.5114f85");
+    assert!(normalize_semver_tag("1.2.3-").is_err());
+    assert!(normalize_semver_tag("1.2.3+").is_err());
+}
+
 #[test]
 fn test_read_version_from_cargo_toml() {
     let dir = tempdir().unwrap();
@@ -11,8 +11,8 @@ fn test_tag_release_push_errors_when_remote_missing() {
     let dir = tmp.path().join("repo");
     let s = dir.to_str().unwrap();
     new_repository(s, false, 50).unwrap();
-    // push=true but remote doesn't exist
-    let err = tag_release(
+    // push=true but remote doesn't exist: --strict-push fails before the tag is created.
+    let err = tag_release_with_options(
         s,
         Some("0.1.0".into()),
         None,
@@ -21,7 +21,40 @@ fn test_tag_release_push_errors_when_remote_missing() {
         false,
         true,
         false,
+        None,
+        true,
+        false,
     )
     .unwrap_err();
     assert!(err.to_string().contains("remote 'origin' not found"));
+    assert!(err.to_string().contains("mdcode gh_create"), "{}", err);
+    assert!(err.to_string().contains("git remote add origin"), "{}", err);
+    assert_eq!(exit_code_for(err.as_ref()), 8);
+    let repo = git2::Repository::open(&dir).unwrap();
+    assert!(repo.find_reference("refs/tags/v0.1.0").is_err());
+}
+
+#[test]
+fn test_tag_release_without_remote_downgrades_to_local_tag() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    tag_release(
+        s,
+        Some("0.1.0".into()),
+        None,
+        true,
+        "origin",
+        false,
+        true,
+        false,
+    )
+    .unwrap();
+    let repo = git2::Repository::open(&dir).unwrap();
+    assert!(repo.find_reference("refs/tags/v0.1.0").is_ok());
 }
@@ -0,0 +1,72 @@
+use clap::Parser;
+use git2::Repository;
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_tag_with_multi_line_message_file() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    // Outside the repository so the working tree stays clean.
+    let notes = t.path().join("notes.md");
+    std::fs::write(
+        &notes,
+        "Release 1.2.0\n\n- faster diffs\n- new tag option\n",
+    )
+    .unwrap();
+
+    let cli = Cli::try_parse_from([
+        "mdcode",
+        "tag",
+        s,
+        "--version",
+        "1.2.0",
+        "--message-file",
+        notes.to_str().unwrap(),
+        "--no-push",
+    ])
+    .unwrap();
+    execute_cli(cli).unwrap();
+
+    let repo = Repository::open(&dir).unwrap();
+    let tag = repo
+        .find_reference("refs/tags/v1.2.0")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    assert_eq!(
+        tag.message().unwrap().trim_end(),
+        "Release 1.2.0\n\n- faster diffs\n- new tag option"
+    );
+}
+
+#[test]
+fn test_tag_message_and_message_file_conflict() {
+    let err = Cli::try_parse_from([
+        "mdcode",
+        "tag",
+        ".",
+        "--message",
+        "m",
+        "--message-file",
+        "notes.md",
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+
+    let err = tag_message(Some("m".into()), Some("notes.md")).unwrap_err();
+    assert!(err.to_string().contains("not both"));
+    assert_eq!(
+        tag_message(Some("m".into()), None).unwrap().as_deref(),
+        Some("m")
+    );
+    let err = tag_message(None, Some("/nonexistent/notes.md")).unwrap_err();
+    assert!(err.to_string().contains("cannot read message file"));
+}
@@ -0,0 +1,79 @@
+use git2::Repository;
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &std::path::Path, args: &[&str]) {
+    assert!(Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap()
+        .success());
+}
+
+#[test]
+fn test_tag_warns_when_tagged_commit_is_not_pushed() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let bare = tmp.path().join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    let work = tmp.path().join("work");
+    let w = work.to_str().unwrap();
+    new_repository(w, false, 50).unwrap();
+    git(&work, &["remote", "add", "origin", bare.to_str().unwrap()]);
+    git(&work, &["push", "-q", "origin", "master"]);
+    assert_eq!(unpushed_head_warning(w, "origin").unwrap(), None);
+
+    // A local commit that has not been pushed yet.
+    std::fs::write(work.join("lib.rs"), "pub fn f() {}\n").unwrap();
+    git(&work, &["add", "lib.rs"]);
+    git(&work, &["commit", "-q", "-m", "local only"]);
+    let warning = unpushed_head_warning(w, "origin").unwrap().unwrap();
+    assert!(warning.contains("is not on 'origin/master'"), "{}", warning);
+    assert!(warning.contains("1 commit(s) not pushed"), "{}", warning);
+    assert!(warning.contains("gh_push"), "{}", warning);
+
+    // The warning does not block the tag push.
+    tag_release(
+        w,
+        Some("0.1.0".into()),
+        None,
+        true,
+        "origin",
+        false,
+        true,
+        false,
+    )
+    .unwrap();
+    let remote = Repository::open_bare(&bare).unwrap();
+    assert!(remote.find_reference("refs/tags/v0.1.0").is_ok());
+
+    git(&work, &["push", "-q", "origin", "master"]);
+    assert_eq!(unpushed_head_warning(w, "origin").unwrap(), None);
+}
+
+#[test]
+fn test_tag_warns_when_branch_missing_on_remote() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let bare = tmp.path().join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    let work = tmp.path().join("work");
+    let w = work.to_str().unwrap();
+    new_repository(w, false, 50).unwrap();
+    git(&work, &["remote", "add", "origin", bare.to_str().unwrap()]);
+    let warning = unpushed_head_warning(w, "origin").unwrap().unwrap();
+    assert!(
+        warning.contains("does not exist on 'origin'"),
+        "{}",
+        warning
+    );
+}
@@ -0,0 +1,89 @@
+use git2::Repository;
+use mdcode::*;
+use tempfile::tempdir;
+
+fn head_message(dir: &std::path::Path) -> String {
+    let repo = Repository::open(dir).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    head.message().unwrap_or("").to_string()
+}
+
+#[test]
+fn test_parse_trailer() {
+    assert_eq!(
+        parse_trailer("Reviewed-by:  Ann <ann@example.com> ").unwrap(),
+        (
+            "Reviewed-by".to_string(),
+            "Ann <ann@example.com>".to_string()
+        )
+    );
+    for bad in ["no colon", "Bad Key: value", "Key:", ": value"] {
+        let err = parse_trailer(bad).unwrap_err().to_string();
+        assert!(err.contains("Key: Value"), "{}", err);
+    }
+}
+
+#[test]
+fn test_append_trailers_joins_existing_block() {
+    let t = |s: &str| s.to_string();
+    assert_eq!(
+        append_trailers("Fix parser\n", &[t("Refs: #12")]),
+        "Fix parser\n\nRefs: #12"
+    );
+    // A subject that looks like a trailer is not a trailer block.
+    assert_eq!(
+        append_trailers("Fix: parser", &[t("Refs: #12")]),
+        "Fix: parser\n\nRefs: #12"
+    );
+    let signed = "Fix parser\n\nLonger body.\n\nSigned-off-by: Ann <ann@example.com>\n";
+    assert_eq!(
+        append_trailers(
+            signed,
+            &[t("Refs: #12"), t("Signed-off-by: Ann <ann@example.com>")]
+        ),
+        "Fix parser\n\nLonger body.\n\nSigned-off-by: Ann <ann@example.com>\nRefs: #12"
+    );
+    assert_eq!(append_trailers("Fix parser", &[]), "Fix parser");
+}
+
+#[test]
+fn test_update_signoff_and_trailer_in_commit_message() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    std::fs::write(dir.join("lib.rs"), "pub fn a() {}\n").unwrap();
+    let opts = UpdateOptions {
+        author: Some("Jane Doe <jane@example.com>".to_string()),
+        signoff: true,
+        trailers: vec!["Reviewed-by:Bob <bob@example.com>".to_string()],
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, Some("Add lib"), 50, &opts).unwrap();
+    assert_eq!(
+        head_message(&dir).trim_end(),
+        "Add lib\n\nReviewed-by: Bob <bob@example.com>\nSigned-off-by: Jane Doe <jane@example.com>"
+    );
+
+    // An amend keeps the existing block without repeating it.
+    let amend = UpdateOptions {
+        amend: true,
+        ..opts.clone()
+    };
+    update_repository_with_options(s, false, None, 50, &amend).unwrap();
+    assert_eq!(
+        head_message(&dir).trim_end(),
+        "Add lib\n\nReviewed-by: Bob <bob@example.com>\nSigned-off-by: Jane Doe <jane@example.com>"
+    );
+
+    let bad = UpdateOptions {
+        trailers: vec!["not a trailer".to_string()],
+        ..UpdateOptions::default()
+    };
+    std::fs::write(dir.join("lib.rs"), "pub fn b() {}\n").unwrap();
+    assert!(update_repository_with_options(s, false, Some("x"), 50, &bad).is_err());
+    assert!(head_message(&dir).starts_with("Add lib"));
+}
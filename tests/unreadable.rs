@@ -0,0 +1,54 @@
+#![cfg(unix)]
+
+use mdcode::*;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::tempdir;
+
+#[test]
+fn test_unreadable_file_policies() {
+    let t = tempdir().unwrap();
+    let s = t.path().to_str().unwrap();
+    std::fs::write(t.path().join("main.rs"), "fn main() {}").unwrap();
+    let secret = t.path().join("secret.rs");
+    std::fs::write(&secret, "fn hidden() {}").unwrap();
+    std::fs::set_permissions(&secret, std::fs::Permissions::from_mode(0o000)).unwrap();
+    if std::fs::File::open(&secret).is_ok() {
+        // Running as root: permissions do not stop reads.
+        return;
+    }
+
+    let (files, count) = scan_source_files(s, 50).unwrap();
+    assert_eq!(count, 1);
+    assert!(files[0].ends_with("main.rs"));
+
+    let with = |unreadable| ScanOptions {
+        unreadable,
+        ..ScanOptions::default()
+    };
+    let (_, keep_going) =
+        scan_source_files_with_options(s, 50, &with(UnreadablePolicy::KeepGoing)).unwrap();
+    let strict = scan_source_files_with_options(s, 50, &with(UnreadablePolicy::Strict));
+    std::fs::set_permissions(&secret, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    assert_eq!(keep_going, 1);
+    let err = strict.unwrap_err().to_string();
+    assert!(err.contains("secret.rs"), "{}", err);
+    assert!(err.contains("--strict"), "{}", err);
+}
+
+#[test]
+fn test_keep_going_conflicts_with_strict() {
+    use clap::Parser;
+    let cli = Cli::try_parse_from(["mdcode", "--keep-going", "list-files", "."]).unwrap();
+    assert!(cli.keep_going && !cli.strict);
+    assert_eq!(
+        cli.scan_options(None).unreadable,
+        UnreadablePolicy::KeepGoing
+    );
+    let cli = Cli::try_parse_from(["mdcode", "--strict", "list-files", "."]).unwrap();
+    assert!(cli.strict);
+    assert_eq!(cli.scan_options(None).unreadable, UnreadablePolicy::Strict);
+    assert!(
+        Cli::try_parse_from(["mdcode", "--keep-going", "--strict", "list-files", "."]).is_err()
+    );
+}
@@ -0,0 +1,100 @@
+use git2::Repository;
+use mdcode::*;
+use tempfile::tempdir;
+
+fn head_commit(dir: &std::path::Path) -> (git2::Oid, git2::Oid, String) {
+    let repo = Repository::open(dir).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    (
+        head.id(),
+        head.tree_id(),
+        head.message().unwrap_or("").to_string(),
+    )
+}
+
+#[test]
+fn test_update_allow_empty_commits_unchanged_tree() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let (before, tree, _) = head_commit(&dir);
+
+    // Without the flag an unchanged tree makes no commit.
+    update_repository(s, false, Some("nothing"), 50).unwrap();
+    assert_eq!(head_commit(&dir).0, before);
+
+    let opts = UpdateOptions {
+        allow_empty: true,
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, Some("Release 1.0"), 50, &opts).unwrap();
+    let (after, after_tree, message) = head_commit(&dir);
+    assert_ne!(after, before);
+    assert_eq!(after_tree, tree);
+    assert_eq!(message.trim(), "Release 1.0");
+    let repo = Repository::open(&dir).unwrap();
+    assert_eq!(
+        repo.find_commit(after).unwrap().parent_id(0).unwrap(),
+        before
+    );
+}
+
+#[test]
+fn test_update_allow_empty_from_cli() {
+    if !check_git_installed() {
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let (before, tree, _) = head_commit(&dir);
+
+    let cli = Cli {
+        command: Commands::Update {
+            directory: Some(s.to_string()),
+            allow_merge_commit: false,
+            interactive: false,
+            rename_threshold: 50,
+            gc: false,
+            author: None,
+            allow_default_author: true,
+            no_secret_scan: false,
+            only: Vec::new(),
+            message: Some("Marker".to_string()),
+            auto_message: false,
+            allow_empty: true,
+            keep_empty_dirs: false,
+            amend: false,
+            max_new_files: None,
+            signoff: false,
+            trailer: Vec::new(),
+            detached_ok: false,
+            branch: None,
+            recursive: false,
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+    let (after, after_tree, message) = head_commit(&dir);
+    assert_ne!(after, before);
+    assert_eq!(after_tree, tree);
+    assert_eq!(message.trim(), "Marker");
+}
@@ -0,0 +1,121 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn head_message(dir: &Path) -> String {
+    Repository::open(dir)
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .message()
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+#[test]
+fn test_auto_message_counts_changed_file_types() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("old.md"), "# old\n").unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    for name in ["main.rs", "lib.rs", "util.rs"] {
+        std::fs::write(dir.join("src").join(name), format!("// {}\n", name)).unwrap();
+    }
+    std::fs::write(dir.join("notes.md"), "# notes\n").unwrap();
+    std::fs::remove_file(dir.join("old.md")).unwrap();
+    std::fs::write(dir.join("config.yml"), "a: 1\n").unwrap();
+
+    let opts = UpdateOptions {
+        auto_message: true,
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, None, 50, &opts).unwrap();
+    assert_eq!(
+        head_message(&dir),
+        "Update: 6 files (3 Rust, 2 Documentation, 1 YAML)"
+    );
+
+    // An explicit message still wins.
+    std::fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+    update_repository_with_options(s, false, Some("Tweak main"), 50, &opts).unwrap();
+    assert_eq!(head_message(&dir), "Tweak main");
+
+    #[cfg(not(coverage))]
+    {
+        std::fs::write(dir.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        let cli = Cli {
+            command: Commands::Update {
+                directory: Some(s.to_string()),
+                allow_merge_commit: false,
+                interactive: false,
+                rename_threshold: DEFAULT_RENAME_THRESHOLD,
+                gc: false,
+                author: None,
+                allow_default_author: true,
+                no_secret_scan: false,
+                only: vec![],
+                message: None,
+                auto_message: true,
+                allow_empty: false,
+                keep_empty_dirs: false,
+                amend: false,
+                max_new_files: None,
+                signoff: false,
+                trailer: Vec::new(),
+                detached_ok: false,
+                branch: None,
+                recursive: false,
+            },
+            dry_run: false,
+            max_file_mb: Some(50),
+            no_color: false,
+            yes: false,
+            timeout: None,
+            max_depth: None,
+            keep_going: false,
+            strict: false,
+            config: None,
+            env_file: None,
+            include_hidden: Vec::new(),
+            verbose: 0,
+            quiet: false,
+            sign_commits: false,
+        };
+        execute_cli(cli).unwrap();
+        assert_eq!(head_message(&dir), "Update: 1 file (1 Rust)");
+    }
+}
+
+#[test]
+fn test_auto_commit_message_format() {
+    let change = |path: &str| FileChange {
+        path: path.into(),
+        old_path: None,
+        status: ChangeKind::Modified,
+        insertions: 1,
+        deletions: 0,
+        category: detect_file_type(Path::new(path)),
+    };
+    assert_eq!(auto_commit_message(&[]), "Update: 0 files");
+    assert_eq!(
+        auto_commit_message(&[
+            change("a.py"),
+            change("b.zz"),
+            change("c.go"),
+            change("d.zz")
+        ]),
+        "Update: 4 files (2 other, 1 Go, 1 Python)"
+    );
+}
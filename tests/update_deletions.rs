@@ -0,0 +1,48 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_update_commits_deleted_tracked_file() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo_dir = t.path().join("repo");
+    std::fs::create_dir_all(repo_dir.join("src")).unwrap();
+    std::fs::write(repo_dir.join("src/keep.rs"), "fn keep() {}\n").unwrap();
+    std::fs::write(repo_dir.join("src/gone.rs"), "fn gone() {}\n").unwrap();
+    let s = repo_dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let repo = git2::Repository::open(&repo_dir).unwrap();
+    let first = repo.head().unwrap().peel_to_commit().unwrap().id();
+    assert!(repo
+        .find_commit(first)
+        .unwrap()
+        .tree()
+        .unwrap()
+        .get_path(std::path::Path::new("src/gone.rs"))
+        .is_ok());
+
+    std::fs::remove_file(repo_dir.join("src/gone.rs")).unwrap();
+    // Dry run reports the deletion without touching the index.
+    assert_eq!(stage_deletions(s, true).unwrap(), vec!["src/gone.rs"]);
+    assert!(repo
+        .index()
+        .unwrap()
+        .get_path(std::path::Path::new("src/gone.rs"), 0)
+        .is_some());
+
+    update_repository(s, false, Some("remove gone.rs"), 50).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_ne!(head.id(), first);
+    assert_eq!(head.parent_id(0).unwrap(), first);
+    let tree = head.tree().unwrap();
+    assert!(tree.get_path(std::path::Path::new("src/gone.rs")).is_err());
+    assert!(tree.get_path(std::path::Path::new("src/keep.rs")).is_ok());
+    let changes = compute_diff(s, &first.to_string(), &head.id().to_string()).unwrap();
+    assert!(changes
+        .iter()
+        .any(|c| c.status == ChangeKind::Deleted && c.path == std::path::Path::new("src/gone.rs")));
+    assert!(stage_deletions(s, false).unwrap().is_empty());
+}
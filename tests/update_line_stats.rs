@@ -0,0 +1,35 @@
+use mdcode::*;
+use tempfile::tempdir;
+
+#[test]
+fn test_diff_line_stats_for_known_change() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let tmp = tempdir().unwrap();
+    let dir = tmp.path().join("r");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+    std::fs::write(dir.join("b.rs"), "fn b() {}\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    // Replace one line of a.txt, append two, delete b.rs (1 line).
+    std::fs::write(dir.join("a.txt"), "one\n2\nthree\nfour\nfive\n").unwrap();
+    std::fs::remove_file(dir.join("b.rs")).unwrap();
+    update_repository(s, false, Some("second"), 50).unwrap();
+
+    let repo = git2::Repository::open(&dir).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let parent = head.parent(0).unwrap();
+    let diff = repo
+        .diff_tree_to_tree(
+            Some(&parent.tree().unwrap()),
+            Some(&head.tree().unwrap()),
+            None,
+        )
+        .unwrap();
+    assert_eq!(diff_line_stats(&diff).unwrap(), (3, 2));
+    assert_eq!(format_line_stats(3, 2), "(+3, -2 lines)");
+}
@@ -0,0 +1,67 @@
+use clap::Parser;
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn head_paths(dir: &Path) -> Vec<String> {
+    let repo = Repository::open(dir).unwrap();
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let mut out = Vec::new();
+    tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(git2::ObjectType::Blob) {
+            out.push(format!("{}{}", root, entry.name().unwrap()));
+        }
+        git2::TreeWalkResult::Ok
+    })
+    .unwrap();
+    out
+}
+
+#[test]
+fn test_matches_only_labels() {
+    let rust = vec!["rust".to_string()];
+    assert!(matches_only_labels(Path::new("src/lib.rs"), &rust));
+    assert!(!matches_only_labels(Path::new("readme.md"), &rust));
+    assert!(!matches_only_labels(Path::new("data.weird"), &rust));
+    assert!(matches_only_labels(Path::new("data.weird"), &[]));
+    let both = vec!["Rust".to_string(), "Documentation".to_string()];
+    assert!(matches_only_labels(Path::new("readme.md"), &both));
+
+    let cli =
+        Cli::try_parse_from(["mdcode", "update", ".", "--only", "Rust", "--only", "TOML"]).unwrap();
+    match cli.command {
+        Commands::Update { only, .. } => assert_eq!(only, vec!["Rust", "TOML"]),
+        _ => panic!("expected update"),
+    }
+}
+
+#[test]
+fn test_update_only_rust_commits_only_rust_files() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    let s = dir.to_str().unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("gone.md"), "# old\n").unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(dir.join("notes.md"), "# notes\n").unwrap();
+    std::fs::remove_file(dir.join("gone.md")).unwrap();
+
+    let opts = UpdateOptions {
+        only: vec!["Rust".into()],
+        ..UpdateOptions::default()
+    };
+    update_repository_with_options(s, false, Some("rust only"), 50, &opts).unwrap();
+    let paths = head_paths(&dir);
+    assert!(paths.contains(&"src/main.rs".to_string()), "{:?}", paths);
+    assert!(!paths.contains(&"notes.md".to_string()), "{:?}", paths);
+    // Deleting a Markdown file is not a Rust change either.
+    assert!(paths.contains(&"gone.md".to_string()), "{:?}", paths);
+}
@@ -0,0 +1,76 @@
+use mdcode::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+const BODY: &str = "pub fn helper() -> u32 {\n    let a = 1;\n    let b = 2;\n    a + b\n}\n";
+
+#[test]
+fn test_update_commits_rename_as_single_move() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let repo_dir = t.path().join("repo");
+    std::fs::create_dir_all(repo_dir.join("src")).unwrap();
+    std::fs::write(repo_dir.join("src/old_name.rs"), BODY).unwrap();
+    let s = repo_dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+    let repo = git2::Repository::open(&repo_dir).unwrap();
+    let first = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+    // Move and lightly edit the file, as a typical rename-then-tweak would.
+    std::fs::remove_file(repo_dir.join("src/old_name.rs")).unwrap();
+    std::fs::write(
+        repo_dir.join("src/new_name.rs"),
+        format!("{}// moved\n", BODY),
+    )
+    .unwrap();
+
+    // What `update --interactive` would offer: one rename, not a delete plus an add.
+    let (files, _) = scan_source_files(s, 50).unwrap();
+    add_files_to_git(s, &files, false).unwrap();
+    stage_deletions(s, false).unwrap();
+    let staged = staged_changes(s, DEFAULT_RENAME_THRESHOLD).unwrap();
+    assert_eq!(staged.len(), 1, "{:?}", staged);
+    assert_eq!(staged[0].status, ChangeKind::Renamed);
+    assert_eq!(
+        change_label(&staged[0]),
+        "src/old_name.rs -> src/new_name.rs"
+    );
+
+    update_repository(s, false, Some("rename"), 50).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let tree = head.tree().unwrap();
+    assert!(tree.get_path(Path::new("src/new_name.rs")).is_ok());
+    assert!(tree.get_path(Path::new("src/old_name.rs")).is_err());
+    assert!(repo
+        .index()
+        .unwrap()
+        .get_path(Path::new("src/old_name.rs"), 0)
+        .is_none());
+
+    let changes = compute_diff(s, &first.to_string(), &head.id().to_string()).unwrap();
+    assert_eq!(changes.len(), 1, "{:?}", changes);
+    assert_eq!(changes[0].status, ChangeKind::Renamed);
+    assert_eq!(changes[0].path, Path::new("src/new_name.rs"));
+    assert_eq!(
+        changes[0].old_path.as_deref(),
+        Some(Path::new("src/old_name.rs"))
+    );
+
+    // Without rename detection the same commit is a delete/add pair.
+    let plain = compute_diff_with_options(
+        s,
+        &first.to_string(),
+        &head.id().to_string(),
+        &DiffOptions {
+            rename_threshold: 100,
+            ..DiffOptions::default()
+        },
+    )
+    .unwrap();
+    let mut kinds: Vec<char> = plain.iter().map(|c| c.status.code()).collect();
+    kinds.sort();
+    assert_eq!(kinds, vec!['A', 'D']);
+}
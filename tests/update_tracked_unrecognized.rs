@@ -0,0 +1,62 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &str, args: &[&str]) {
+    assert!(Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+        .args(args)
+        .status()
+        .unwrap()
+        .success());
+}
+
+fn head_blob(repo: &Repository, path: &str) -> Option<Vec<u8>> {
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let entry = tree.get_path(Path::new(path)).ok()?;
+    Some(repo.find_blob(entry.id()).unwrap().content().to_vec())
+}
+
+#[test]
+fn test_update_commits_changes_to_tracked_unrecognized_file() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path().join("repo");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    let s = dir.to_str().unwrap();
+    new_repository(s, false, 50).unwrap();
+
+    // `.weird` is not recognized by detect_file_type, so only raw git can add it.
+    assert!(detect_file_type(Path::new("data.weird")).is_none());
+    std::fs::write(dir.join("data.weird"), "v1\n").unwrap();
+    git(s, &["add", "data.weird"]);
+    git(s, &["commit", "-q", "-m", "add data"]);
+
+    std::fs::write(dir.join("data.weird"), "v2\n").unwrap();
+    std::fs::write(dir.join("other.weird"), "untracked\n").unwrap();
+    assert_eq!(
+        modified_tracked_files(s).unwrap(),
+        vec![dir.join("data.weird")]
+    );
+
+    update_repository(s, false, Some("update data"), 50).unwrap();
+    let repo = Repository::open(&dir).unwrap();
+    assert_eq!(
+        head_blob(&repo, "data.weird").as_deref(),
+        Some(&b"v2\n"[..])
+    );
+    // Unrecognized files that were never tracked are still left out (the coverage build
+    // stages with `git add -A`).
+    if cfg!(not(coverage)) {
+        assert!(head_blob(&repo, "other.weird").is_none());
+    }
+    assert!(modified_tracked_files(s).unwrap().is_empty());
+}
@@ -0,0 +1,30 @@
+use clap::Parser;
+use log::LevelFilter;
+use mdcode::*;
+
+#[test]
+fn test_log_level_from_verbosity_flags() {
+    assert_eq!(log_level_filter(0, false), LevelFilter::Info);
+    assert_eq!(log_level_filter(1, false), LevelFilter::Debug);
+    assert_eq!(log_level_filter(2, false), LevelFilter::Trace);
+    assert_eq!(log_level_filter(5, false), LevelFilter::Trace);
+    assert_eq!(log_level_filter(0, true), LevelFilter::Warn);
+
+    let level = |args: &[&str]| {
+        Cli::try_parse_from(args.iter().copied())
+            .map(|cli| cli.log_level())
+            .map_err(|e| e.kind())
+    };
+    assert_eq!(level(&["mdcode", "info"]), Ok(LevelFilter::Info));
+    assert_eq!(level(&["mdcode", "-v", "info"]), Ok(LevelFilter::Debug));
+    assert_eq!(level(&["mdcode", "-vv", "info"]), Ok(LevelFilter::Trace));
+    assert_eq!(
+        level(&["mdcode", "--verbose", "--verbose", "info"]),
+        Ok(LevelFilter::Trace)
+    );
+    assert_eq!(level(&["mdcode", "--quiet", "info"]), Ok(LevelFilter::Warn));
+    assert_eq!(
+        level(&["mdcode", "-q", "-v", "info"]),
+        Err(clap::error::ErrorKind::ArgumentConflict)
+    );
+}
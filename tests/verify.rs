@@ -0,0 +1,242 @@
+use git2::Repository;
+use mdcode::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "git {:?}: {}",
+        args,
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8_lossy(&out.stdout).trim().to_string()
+}
+
+/// A Rust project at version 0.3.0, committed by `mdcode new`.
+fn project(root: &Path) -> std::path::PathBuf {
+    let dir = root.join("proj");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"proj\"\nversion = \"0.3.0\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    new_repository(dir.to_str().unwrap(), false, 50).unwrap();
+    dir
+}
+
+#[test]
+fn test_clean_and_untracked_checks() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = project(t.path());
+    let s = dir.to_str().unwrap();
+
+    assert!(check_clean(s).passed);
+    assert!(check_untracked_sources(s, 50, None).passed);
+
+    std::fs::write(dir.join("main.rs"), "fn main() { println!(); }\n").unwrap();
+    std::fs::write(dir.join("new.rs"), "fn new() {}\n").unwrap();
+    std::fs::write(dir.join("data.unknownext"), "x").unwrap();
+    let clean = check_clean(s);
+    assert!(!clean.passed);
+    assert!(clean.detail.contains("main.rs"), "{}", clean.detail);
+    assert!(clean.hint.unwrap().contains("mdcode update"));
+    let untracked = check_untracked_sources(s, 50, None);
+    assert!(!untracked.passed);
+    assert_eq!(untracked.detail, "untracked source files: new.rs");
+}
+
+#[test]
+fn test_upstream_check() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = project(t.path());
+    let s = dir.to_str().unwrap();
+    let branch = current_branch_name(s).unwrap();
+
+    let none = check_upstream(s);
+    assert!(!none.passed);
+    assert!(none.detail.contains("has no upstream"), "{}", none.detail);
+
+    let bare = t.path().join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    add_remote(s, "origin", bare.to_str().unwrap()).unwrap();
+    gh_push(s, "origin").unwrap();
+    let ok = check_upstream(s);
+    assert!(ok.passed, "{:?}", ok);
+    assert_eq!(
+        ok.detail,
+        format!("'{0}' tracks 'origin/{0}' (0 ahead, 0 behind)", branch)
+    );
+
+    // A local commit and a different remote-tracking commit on top of the pushed one.
+    let base = git(&dir, &["rev-parse", "HEAD"]);
+    std::fs::write(dir.join("a.txt"), "a").unwrap();
+    git(&dir, &["add", "a.txt"]);
+    git(&dir, &["commit", "-q", "-m", "local"]);
+    let theirs = git(
+        &dir,
+        &[
+            "commit-tree",
+            &format!("{}^{{tree}}", base),
+            "-p",
+            &base,
+            "-m",
+            "remote",
+        ],
+    );
+    git(
+        &dir,
+        &[
+            "update-ref",
+            &format!("refs/remotes/origin/{}", branch),
+            &theirs,
+        ],
+    );
+    let diverged = check_upstream(s);
+    assert!(!diverged.passed);
+    assert!(diverged.detail.ends_with("(1 ahead, 1 behind): diverged"));
+    assert!(diverged.hint.unwrap().contains("gh_sync"));
+
+    git(&dir, &["checkout", "-q", "--detach"]);
+    assert_eq!(check_upstream(s).detail, "HEAD is detached");
+}
+
+#[test]
+fn test_version_gitignore_and_large_file_checks() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = project(t.path());
+    let s = dir.to_str().unwrap();
+
+    let untagged = check_version_tag(s);
+    assert!(untagged.passed);
+    assert_eq!(untagged.detail, "v0.3.0 is not tagged yet");
+    git(&dir, &["tag", "v0.3.0"]);
+    let tagged = check_version_tag(s);
+    assert!(!tagged.passed);
+    assert!(tagged.hint.unwrap().contains("bump the version"));
+
+    let gi = check_gitignore(s);
+    assert!(gi.passed, "{:?}", gi);
+    let content = std::fs::read_to_string(dir.join(".gitignore")).unwrap();
+    let trimmed: Vec<&str> = content
+        .lines()
+        .filter(|l| *l != "*.log" && *l != "**/*.rs.bk")
+        .collect();
+    std::fs::write(dir.join(".gitignore"), trimmed.join("\n")).unwrap();
+    assert_eq!(
+        check_gitignore(s).detail,
+        ".gitignore is missing *.log, **/*.rs.bk"
+    );
+    std::fs::remove_file(dir.join(".gitignore")).unwrap();
+    assert_eq!(check_gitignore(s).detail, ".gitignore is missing");
+
+    std::fs::write(dir.join("big.rs"), vec![b'a'; 2 * 1024 * 1024]).unwrap();
+    git(&dir, &["add", "big.rs"]);
+    git(&dir, &["commit", "-q", "-m", "big"]);
+    assert!(check_large_files(s, 50, None).passed);
+    // 0 means no limit.
+    assert!(check_large_files(s, 0, None).passed);
+    let large = check_large_files(s, 1, None);
+    assert!(!large.passed);
+    assert!(large.detail.contains("big.rs (2"), "{}", large.detail);
+
+    // Without a Cargo.toml version there is nothing to compare.
+    let plain = t.path().join("plain");
+    std::fs::create_dir_all(&plain).unwrap();
+    std::fs::write(plain.join("a.md"), "# a\n").unwrap();
+    new_repository(plain.to_str().unwrap(), false, 50).unwrap();
+    assert_eq!(
+        check_version_tag(plain.to_str().unwrap()).detail,
+        "no Cargo.toml version to check"
+    );
+}
+
+#[test]
+#[serial_test::serial]
+fn test_verify_runs_checks_and_honours_skip() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = project(t.path());
+    let s = dir.to_str().unwrap();
+
+    let results = run_verify_checks(s, 50, &[], None).unwrap();
+    let names: Vec<&str> = results.iter().map(|r| r.name).collect();
+    assert_eq!(names, VERIFY_CHECKS);
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|r| !r.passed)
+        .map(|r| r.name)
+        .collect();
+    assert_eq!(failed, vec!["upstream"]);
+
+    let err = verify_command(s, 50, &[], None).unwrap_err();
+    assert_eq!(err.to_string(), "1 of 6 verify checks failed");
+    assert_eq!(exit_code_for(err.as_ref()), 1);
+    let passed = verify_command(s, 50, &["upstream".to_string()], None).unwrap();
+    assert_eq!(passed.len(), 5);
+
+    let err = run_verify_checks(s, 50, &["nope".to_string()], None).unwrap_err();
+    assert!(err.to_string().starts_with("unknown check 'nope'"));
+    assert!(run_verify_checks(t.path().join("missing").to_str().unwrap(), 50, &[], None).is_err());
+
+    std::env::set_var("NO_COLOR", "1");
+    assert_eq!(
+        format_verify_result(&results[0]),
+        "PASS clean: no uncommitted changes"
+    );
+    assert_eq!(
+        format_verify_result(&results[2]),
+        format!(
+            "FAIL upstream: branch '{}' has no upstream\n     hint: push it with 'mdcode gh_push' (which sets the upstream)",
+            current_branch_name(s).unwrap()
+        )
+    );
+    std::env::remove_var("NO_COLOR");
+
+    let cli = Cli {
+        command: Commands::Verify {
+            directory: Some(s.to_string()),
+            skip: vec!["upstream".into()],
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    execute_cli(cli).unwrap();
+}
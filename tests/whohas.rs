@@ -0,0 +1,74 @@
+use mdcode::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn commit_as(dir: &str, name: &str, message: &str) {
+    let email = format!("{}@example.com", name.to_lowercase());
+    for args in [vec!["add", "-A"], vec!["commit", "-q", "-m", message]] {
+        assert!(Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", &format!("user.name={}", name)])
+            .args(["-c", &format!("user.email={}", email)])
+            .args(&args)
+            .status()
+            .unwrap()
+            .success());
+    }
+}
+
+#[test]
+fn test_whohas_reports_latest_commit_touching_file() {
+    if !check_git_installed() {
+        eprintln!("git not installed; skipping test");
+        return;
+    }
+    let t = tempdir().unwrap();
+    let dir = t.path();
+    let s = dir.to_str().unwrap();
+    git2::Repository::init(dir).unwrap();
+
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    commit_as(s, "Alice", "Add lib");
+    std::fs::write(dir.join("src/lib.rs"), "pub fn a() {}\npub fn b() {}\n").unwrap();
+    commit_as(s, "Bob", "Add b to lib");
+    // A later commit elsewhere must not be reported for src/lib.rs.
+    std::fs::write(dir.join("notes.md"), "notes\n").unwrap();
+    commit_as(s, "Carol", "Add notes");
+
+    let repo = git2::Repository::open(dir).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let last = last_commit_for_path(s, "src/lib.rs").unwrap().unwrap();
+    assert_eq!(last.id, head.parent_id(0).unwrap());
+    assert_eq!(last.author, "Bob <bob@example.com>");
+    assert_eq!(last.summary, "Add b to lib");
+
+    let last = last_commit_for_path(s, "./notes.md").unwrap().unwrap();
+    assert_eq!(last.id, head.id());
+    assert_eq!(last.author, "Carol <carol@example.com>");
+
+    assert!(last_commit_for_path(s, "missing.rs").unwrap().is_none());
+    let cli = Cli {
+        command: Commands::Whohas {
+            directory: s.to_string(),
+            path: "missing.rs".into(),
+        },
+        dry_run: false,
+        max_file_mb: Some(50),
+        no_color: false,
+        yes: false,
+        timeout: None,
+        max_depth: None,
+        keep_going: false,
+        strict: false,
+        config: None,
+        env_file: None,
+        include_hidden: Vec::new(),
+        verbose: 0,
+        quiet: false,
+        sign_commits: false,
+    };
+    let err = execute_cli(cli).unwrap_err();
+    assert!(err.to_string().contains("no commit touched 'missing.rs'"));
+}